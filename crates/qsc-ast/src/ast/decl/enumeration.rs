@@ -0,0 +1,32 @@
+use crate::{
+    ast::node::{attr::Attribute, ty::TypeNode, vis::Visibility},
+    span::StaticSpan,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    pub vis: Visibility,
+    pub attrs: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumVariant {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub fields: Vec<TypeNode>,
+}
+
+impl EnumNode {
+    /// Find a variant along with its index, which is also its tag.
+    pub fn variant(&self, name: impl AsRef<str>) -> Option<(usize, &EnumVariant)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == name.as_ref())
+    }
+}
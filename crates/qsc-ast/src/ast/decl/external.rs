@@ -1,6 +1,6 @@
 use super::func::FunctionArgument;
 use crate::{
-    ast::node::{ty::TypeNode, vis::Visibility},
+    ast::node::{attr::Attribute, ty::TypeNode, vis::Visibility},
     span::StaticSpan,
 };
 
@@ -12,4 +12,8 @@ pub struct ExternFunctionNode {
     pub args: Vec<FunctionArgument>,
     pub ret: Option<TypeNode>,
     pub vis: Visibility,
+    pub attrs: Vec<Attribute>,
+
+    /// Whether this takes more arguments after `args`, like `printf`.
+    pub variadic: bool,
 }
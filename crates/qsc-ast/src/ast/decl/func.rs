@@ -1,6 +1,6 @@
-use super::var::VariableNode;
+use super::{traits::TraitBound, var::VariableNode};
 use crate::{
-    ast::node::{block::Block, ty::TypeNode, vis::Visibility},
+    ast::node::{attr::Attribute, block::Block, ty::TypeNode, vis::Visibility},
     span::StaticSpan,
 };
 use std::collections::HashMap;
@@ -10,26 +10,40 @@ pub struct FunctionNode {
     #[serde(skip)]
     pub span: StaticSpan,
     pub name: String,
+
+    /// The names of the type parameters, like `T` in `fn max<T>(...)`.
+    pub generics: Vec<String>,
+
+    /// The traits the type parameters have to implement, from both the
+    /// parameter list and the `where` clause.
+    pub bounds: Vec<TraitBound>,
+
     pub args: Vec<FunctionArgument>,
     pub ret: Option<TypeNode>,
     pub content: Block,
     pub vis: Visibility,
+    pub attrs: Vec<Attribute>,
+
+    /// Whether this is a `const fn`, which can be called at compile time.
+    pub constant: bool,
 }
 
 impl FunctionNode {
     pub fn variables(&self) -> HashMap<String, VariableNode> {
-        let mut vars = HashMap::new();
+        let mut vars = HashMap::<String, VariableNode>::new();
 
         for arg in &self.args {
             vars.insert(arg.name.clone(), arg.clone().into());
         }
 
-        for node in &self.content.data {
-            if let Ok(decl) = node.data.as_decl() {
-                if let Ok(var) = decl.as_variable() {
-                    vars.insert(var.name.clone(), var);
-                }
+        for var in self.content.vars() {
+            // Until the processor gets to a variable declared again, its
+            // type isn't known, and the earlier one is still the one used.
+            if var.type_.is_none() && vars.get(&var.name).is_some_and(|v| v.type_.is_some()) {
+                continue;
             }
+
+            vars.insert(var.name.clone(), var);
         }
 
         vars
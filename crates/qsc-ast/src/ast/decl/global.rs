@@ -1,5 +1,5 @@
 use crate::{
-    ast::node::{ty::TypeNode, vis::Visibility, Node},
+    ast::node::{attr::Attribute, ty::TypeNode, vis::Visibility, Node},
     span::StaticSpan,
 };
 
@@ -11,4 +11,9 @@ pub struct GlobalVariable {
     pub type_: TypeNode,
     pub value: Node,
     pub vis: Visibility,
+    pub attrs: Vec<Attribute>,
+    pub mutable: bool,
+
+    /// Constants have no storage and get inlined wherever they're used.
+    pub constant: bool,
 }
@@ -0,0 +1,27 @@
+use crate::span::StaticSpan;
+
+use super::func::FunctionNode;
+
+/// The type `Self` stands for inside of an `impl` block.
+pub const SELF_TYPE: &str = "Self";
+
+/// An `impl Type { ... }` or `impl Trait for Type { ... }` block. The
+/// processor turns its functions into plain ones named like `Type::method`,
+/// with the receiver as a first argument called `self`. Trait impls are
+/// left in the tree without their functions, to record that the type
+/// implements the trait.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImplNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub trait_: Option<String>,
+    pub functions: Vec<FunctionNode>,
+}
+
+impl ImplNode {
+    /// The name a function in the block gets.
+    pub fn method_name(&self, func: &str) -> String {
+        format!("{}::{}", self.name, func)
+    }
+}
@@ -0,0 +1,16 @@
+use crate::span::StaticSpan;
+
+/// A `use a::b;` declaration, which makes `b` usable by itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UseNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub path: String,
+}
+
+impl UseNode {
+    /// The name the import is known by, which is the last part of its path.
+    pub fn name(&self) -> &str {
+        self.path.rsplit("::").next().unwrap()
+    }
+}
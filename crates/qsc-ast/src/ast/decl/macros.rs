@@ -0,0 +1,34 @@
+use crate::{
+    ast::node::{block::Block, vis::Visibility},
+    span::StaticSpan,
+};
+
+/// A `macro name(args) { ... }` definition. Invocations like `name!(...)`
+/// are replaced with a copy of its body, with the arguments put in place
+/// of the parameters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub params: Vec<MacroParam>,
+    pub body: Block,
+    pub vis: Visibility,
+}
+
+/// A macro parameter. The last one can be variadic, like `args...`, and
+/// take all the remaining arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroParam {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub variadic: bool,
+}
+
+impl MacroNode {
+    /// The variadic parameter, if there is one.
+    pub fn variadic(&self) -> Option<&MacroParam> {
+        self.params.last().filter(|param| param.variadic)
+    }
+}
@@ -1,12 +1,21 @@
 use crate::{get_enum_variant_value_impl, is_enum_variant_impl};
 
 use self::{
-    external::ExternFunctionNode, func::FunctionNode, global::GlobalVariable, var::VariableNode,
+    enumeration::EnumNode, external::ExternFunctionNode, func::FunctionNode,
+    global::GlobalVariable, implementation::ImplNode, import::UseNode, macros::MacroNode,
+    module::ModuleNode, structure::StructNode, traits::TraitNode, var::VariableNode,
 };
 
+pub mod enumeration;
 pub mod external;
 pub mod func;
 pub mod global;
+pub mod implementation;
+pub mod import;
+pub mod macros;
+pub mod module;
+pub mod structure;
+pub mod traits;
 pub mod var;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -15,14 +24,35 @@ pub enum DeclarationNode {
     Variable(VariableNode),
     Global(GlobalVariable),
     Extern(ExternFunctionNode),
+    Struct(StructNode),
+    Enum(EnumNode),
+    Module(ModuleNode),
+    Use(UseNode),
+    Impl(ImplNode),
+    Trait(TraitNode),
+    Macro(MacroNode),
 }
 
 is_enum_variant_impl!(is_function -> DeclarationNode::Function);
 is_enum_variant_impl!(is_variable -> DeclarationNode::Variable);
 is_enum_variant_impl!(is_global -> DeclarationNode::Global);
 is_enum_variant_impl!(is_extern -> DeclarationNode::Extern);
+is_enum_variant_impl!(is_struct -> DeclarationNode::Struct);
+is_enum_variant_impl!(is_enum -> DeclarationNode::Enum);
+is_enum_variant_impl!(is_module -> DeclarationNode::Module);
+is_enum_variant_impl!(is_use -> DeclarationNode::Use);
+is_enum_variant_impl!(is_impl -> DeclarationNode::Impl);
+is_enum_variant_impl!(is_trait -> DeclarationNode::Trait);
+is_enum_variant_impl!(is_macro -> DeclarationNode::Macro);
 
 get_enum_variant_value_impl!(as_function -> DeclarationNode::Function: FunctionNode);
 get_enum_variant_value_impl!(as_variable -> DeclarationNode::Variable: VariableNode);
 get_enum_variant_value_impl!(as_global -> DeclarationNode::Global: GlobalVariable);
 get_enum_variant_value_impl!(as_extern -> DeclarationNode::Extern: ExternFunctionNode);
+get_enum_variant_value_impl!(as_struct -> DeclarationNode::Struct: StructNode);
+get_enum_variant_value_impl!(as_enum -> DeclarationNode::Enum: EnumNode);
+get_enum_variant_value_impl!(as_module -> DeclarationNode::Module: ModuleNode);
+get_enum_variant_value_impl!(as_use -> DeclarationNode::Use: UseNode);
+get_enum_variant_value_impl!(as_impl -> DeclarationNode::Impl: ImplNode);
+get_enum_variant_value_impl!(as_trait -> DeclarationNode::Trait: TraitNode);
+get_enum_variant_value_impl!(as_macro -> DeclarationNode::Macro: MacroNode);
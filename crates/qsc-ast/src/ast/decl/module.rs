@@ -0,0 +1,23 @@
+use crate::{
+    ast::node::{vis::Visibility, Node},
+    compat::WrappedNamedSource,
+    span::StaticSpan,
+};
+
+/// A `mod name;` declaration. The lexer fills in the declarations from
+/// the module's file, and the processor flattens them into the main tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+
+    /// Private modules can't be named from outside of the module that
+    /// declares them, whatever is inside of them.
+    pub vis: Visibility,
+
+    /// The module's own source, for errors in its declarations.
+    #[serde(skip)]
+    pub src: WrappedNamedSource<String>,
+    pub data: Vec<Node>,
+}
@@ -0,0 +1,28 @@
+use crate::{
+    ast::node::{attr::Attribute, ty::TypeNode, vis::Visibility},
+    span::StaticSpan,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub fields: Vec<StructField>,
+    pub vis: Visibility,
+    pub attrs: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructField {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub type_: TypeNode,
+}
+
+impl StructNode {
+    pub fn field(&self, name: impl AsRef<str>) -> Option<&StructField> {
+        self.fields.iter().find(|field| field.name == name.as_ref())
+    }
+}
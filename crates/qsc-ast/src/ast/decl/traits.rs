@@ -0,0 +1,63 @@
+use crate::{
+    ast::node::{attr::Attribute, block::Block, ty::TypeNode, vis::Visibility},
+    span::StaticSpan,
+};
+
+use super::func::{FunctionArgument, FunctionNode};
+
+/// A `trait Name { ... }` declaration, which lists the methods a type has
+/// to have to implement it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraitNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub functions: Vec<TraitFunction>,
+    pub vis: Visibility,
+    pub attrs: Vec<Attribute>,
+}
+
+/// A function in a trait. Impls can leave out the ones with a default
+/// body, and get a copy of it instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraitFunction {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub args: Vec<FunctionArgument>,
+    pub ret: Option<TypeNode>,
+    pub default: Option<Block>,
+}
+
+/// A bound on a type parameter, like `T: Display`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraitBound {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub param: String,
+    pub trait_: String,
+}
+
+impl TraitNode {
+    pub fn function(&self, name: &str) -> Option<&TraitFunction> {
+        self.functions.iter().find(|func| func.name == name)
+    }
+}
+
+impl TraitFunction {
+    /// Make a function out of the default body, if there is one.
+    pub fn to_function(&self, vis: Visibility) -> Option<FunctionNode> {
+        Some(FunctionNode {
+            span: self.span.clone(),
+            name: self.name.clone(),
+            generics: Vec::new(),
+            bounds: Vec::new(),
+            args: self.args.clone(),
+            ret: self.ret.clone(),
+            content: self.default.clone()?,
+            vis,
+            attrs: Vec::new(),
+            constant: false,
+        })
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{ast::node::Node, span::StaticSpan};
+
+/// An array literal, like `[1, 2, 3]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArrayExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub values: Vec<Node>,
+}
+
+/// An index into an array, like `values[i]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+    pub index: Node,
+}
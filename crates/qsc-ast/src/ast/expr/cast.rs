@@ -0,0 +1,13 @@
+use crate::{
+    ast::node::{ty::TypeNode, Node},
+    span::StaticSpan,
+};
+
+/// A conversion to another type, like `x as i64`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CastExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+    pub type_: TypeNode,
+}
@@ -0,0 +1,53 @@
+use crate::{
+    ast::node::{block::Block, ty::TypeNode},
+    span::StaticSpan,
+};
+
+/// An anonymous function, like `|x: i32| x + offset`. The processor moves
+/// its body into a function of its own, named `func`, which gets the
+/// values of the `captures` from an environment passed after its
+/// arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClosureExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub args: Vec<ClosureArgument>,
+    pub ret: Option<TypeNode>,
+    pub body: Block,
+    pub func: String,
+    pub captures: Vec<String>,
+}
+
+/// A closure argument. Its type can be left out when the closure is used
+/// where a function of a known type is expected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClosureArgument {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub mutable: bool,
+    pub name: String,
+    pub type_: Option<TypeNode>,
+}
+
+impl ClosureExpr {
+    /// The name of the struct holding the environment, which starts with a
+    /// pointer to the code of the closure.
+    pub fn env(&self) -> String {
+        format!("{}_env", self.func)
+    }
+
+    /// Get the type of the closure, once the types of its arguments are
+    /// known.
+    pub fn get_type(&self) -> Option<String> {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.type_.as_ref().map(|ty| ty.as_str()))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(TypeNode::function(
+            &args,
+            self.ret.as_ref().map(|ty| ty.as_str()).as_deref(),
+        ))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{ast::node::Node, span::StaticSpan};
+
+/// A value worked out while compiling, like `comptime fib(10)`. The
+/// `value` is either an expression, or a block that gives its value with
+/// `return`. The processor replaces it with a literal of the result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComptimeExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+}
+
+impl ComptimeExpr {
+    /// The value the block form returns, if it returns one.
+    pub fn returned(&self) -> Option<Node> {
+        if !self.value.data.is_block() {
+            return Some(self.value.clone());
+        }
+
+        let mut found = None;
+
+        self.value.walk(&mut |node| {
+            if let Ok(ret) = node.data.as_stmt().and_then(|stmt| stmt.as_return()) {
+                found = found.take().or(ret.value);
+            }
+        });
+
+        found
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    ast::{
+        decl::var::VariableNode,
+        node::{data::NodeData, sym::SymbolNode, Node},
+    },
+    span::StaticSpan,
+};
+
+use super::ExpressionNode;
+
+/// An enum value, like `Shape::Circle(1.0)` or `Color::Red`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub variant: String,
+    pub values: Vec<Node>,
+}
+
+/// A `match` on an enum value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchArm {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub pattern: Pattern,
+    pub body: Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Pattern {
+    Variant(VariantPattern),
+    Wildcard(#[serde(skip)] StaticSpan),
+}
+
+/// A pattern like `Shape::Rect(w, h)`. Fields bound to `_` are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariantPattern {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub variant: String,
+    pub bindings: Vec<Option<SymbolNode>>,
+}
+
+/// A field of an enum variant. These can't be written directly, they are
+/// what the variables bound by a match pattern get initialized with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariantFieldExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+    pub name: String,
+    pub variant: String,
+    pub index: usize,
+}
+
+impl MatchArm {
+    /// Get the variables bound by this arm's pattern.
+    pub fn variables(&self, value: &Node) -> Vec<VariableNode> {
+        let Pattern::Variant(pattern) = &self.pattern else {
+            return Vec::new();
+        };
+
+        pattern
+            .bindings
+            .iter()
+            .enumerate()
+            .filter_map(|(index, binding)| {
+                let binding = binding.as_ref()?;
+
+                Some(VariableNode {
+                    span: binding.span.clone(),
+                    mutable: false,
+                    name: binding.value.clone(),
+                    type_: None,
+                    value: Some(Node {
+                        span: binding.span.clone(),
+                        data: Box::new(NodeData::Expr(ExpressionNode::VariantField(
+                            VariantFieldExpr {
+                                span: binding.span.clone(),
+                                value: value.clone(),
+                                name: pattern.name.clone(),
+                                variant: pattern.variant.clone(),
+                                index,
+                            },
+                        ))),
+                    }),
+                })
+            })
+            .collect()
+    }
+}
+
+impl MatchExpr {
+    /// Get every variable declared in this match, including the ones
+    /// bound by patterns and the ones declared in the arms.
+    pub fn vars(&self) -> Vec<VariableNode> {
+        let mut vars = Vec::new();
+
+        for arm in &self.arms {
+            vars.extend(arm.variables(&self.value));
+
+            match &*arm.body.data {
+                NodeData::Block(block) => vars.extend(block.vars()),
+                NodeData::Expr(ExpressionNode::Match(expr)) => vars.extend(expr.vars()),
+                _ => {}
+            }
+        }
+
+        vars
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{
+    ast::node::{block::Block, Node},
+    span::StaticSpan,
+};
+
+/// An invocation of a macro, like `log!("x = %d", x)`. The processor
+/// fills in the `expansion` with a copy of the macro's body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroCallExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub args: Vec<Node>,
+    pub expansion: Option<Block>,
+}
+
+/// `args...` in a macro, which stands for all the arguments given for a
+/// variadic parameter. It can only be used in a list of arguments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpreadExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+}
@@ -0,0 +1,18 @@
+use crate::{
+    ast::{
+        node::{sym::SymbolNode, Node},
+        stmt::call::CallArgument,
+    },
+    span::StaticSpan,
+};
+
+/// A method call, like `point.length()`. The processor turns it into a
+/// call to the method with the value (or its address) as the receiver.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MethodCallExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+    pub method: SymbolNode,
+    pub args: Vec<CallArgument>,
+}
@@ -5,18 +5,61 @@ use qsc_core::{
 
 use crate::{get_enum_variant_value_impl, is_enum_variant_impl};
 
-use self::{binary::BinaryExpr, unary::UnaryExpr};
+use self::{
+    array::{ArrayExpr, IndexExpr},
+    binary::BinaryExpr,
+    cast::CastExpr,
+    closure::ClosureExpr,
+    comptime::ComptimeExpr,
+    enumeration::{EnumExpr, MatchExpr, VariantFieldExpr},
+    macros::{MacroCallExpr, SpreadExpr},
+    method::MethodCallExpr,
+    operator::Operator,
+    pointer::{DerefExpr, RefExpr},
+    structure::{FieldExpr, StructExpr},
+    tuple::TupleExpr,
+    unary::UnaryExpr,
+};
 
-use super::AbstractTree;
+use super::{
+    node::{data::NodeData, ty::TypeNode},
+    AbstractTree,
+};
 
+pub mod array;
 pub mod binary;
+pub mod cast;
+pub mod closure;
+pub mod comptime;
+pub mod enumeration;
+pub mod macros;
+pub mod method;
 pub mod operator;
+pub mod pointer;
+pub mod structure;
+pub mod tuple;
 pub mod unary;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExpressionNode {
     Binary(BinaryExpr),
     Unary(UnaryExpr),
+    Struct(StructExpr),
+    Field(FieldExpr),
+    Enum(EnumExpr),
+    Match(MatchExpr),
+    VariantField(VariantFieldExpr),
+    Array(ArrayExpr),
+    Tuple(TupleExpr),
+    Index(IndexExpr),
+    Ref(RefExpr),
+    Deref(DerefExpr),
+    Cast(CastExpr),
+    Closure(ClosureExpr),
+    Method(MethodCallExpr),
+    Comptime(ComptimeExpr),
+    MacroCall(MacroCallExpr),
+    Spread(SpreadExpr),
 }
 
 impl ExpressionNode {
@@ -26,6 +69,22 @@ impl ExpressionNode {
                 let lhs = expr.lhs.data.get_type(func, tree)?;
                 let rhs = expr.rhs.data.get_type(func, tree)?;
 
+                // Pointers can be offset by an integer, and subtracting two
+                // of them gives the number of elements between them.
+                if TypeNode::pointee(&lhs).is_some() {
+                    let operator = expr.operator.base_op().unwrap_or(expr.operator);
+
+                    if matches!(operator, Operator::Add | Operator::Subtract)
+                        && expr.rhs.data.is_int(func, tree)?
+                    {
+                        return Ok(lhs);
+                    }
+
+                    if expr.operator == Operator::Subtract && rhs == lhs {
+                        return Ok("i64".to_string());
+                    }
+                }
+
                 if rhs != lhs {
                     Err(LexicalError {
                         location: expr.span.into_source_span(),
@@ -33,18 +92,263 @@ impl ExpressionNode {
                         error: miette!("Left and right operands' types do not match!"),
                     }
                     .into())
+                } else if expr.operator.is_comparison() || expr.operator.is_logical() {
+                    Ok("bool".to_string())
                 } else {
                     Ok(lhs)
                 }
             }
 
-            ExpressionNode::Unary(expr) => expr.value.data.get_type(func, tree),
+            ExpressionNode::Unary(expr) => {
+                if expr.operator.is_logical() {
+                    Ok("bool".to_string())
+                } else {
+                    expr.value.data.get_type(func, tree)
+                }
+            }
+
+            ExpressionNode::Struct(expr) => Ok(expr.name),
+
+            ExpressionNode::Field(expr) => {
+                let ty = expr.value.data.get_type(func, tree)?;
+
+                // Fields can be reached through a pointer too.
+                let ty = TypeNode::pointee(&ty).map(|v| v.to_string()).unwrap_or(ty);
+
+                // The fields of a tuple are its elements, like `pair.0`.
+                if let Some(elems) = TypeNode::tuple_parts(&ty) {
+                    return expr
+                        .field
+                        .value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|idx| elems.get(idx).cloned())
+                        .ok_or_else(|| {
+                            LexicalError {
+                                location: expr.field.span.into_source_span(),
+                                src: tree.src.clone().into(),
+                                error: miette!("Tuple {} has no field {}!", ty, expr.field.value),
+                            }
+                            .into()
+                        });
+                }
+
+                tree.structs()
+                    .get(&ty)
+                    .and_then(|item| item.field(&expr.field.value))
+                    .map(|field| field.type_.as_str())
+                    .ok_or_else(|| {
+                        LexicalError {
+                            location: expr.field.span.into_source_span(),
+                            src: tree.src.clone().into(),
+                            error: miette!("Type {} has no field named {}!", ty, expr.field.value),
+                        }
+                        .into()
+                    })
+            }
+
+            ExpressionNode::Method(expr) => {
+                let ty = expr.value.data.get_type(func, tree)?;
+
+                let Some(method) = tree.method(&ty, &expr.method.value) else {
+                    return Err(LexicalError {
+                        location: expr.method.span.into_source_span(),
+                        src: tree.src.clone().into(),
+                        error: miette!("Type {} has no method named {}!", ty, expr.method.value),
+                    }
+                    .into());
+                };
+
+                method.ret.map(|v| v.as_str()).ok_or(
+                    LexicalError {
+                        location: expr.span.into_source_span(),
+                        src: tree.src.clone().into(),
+                        error: miette!("Cannot find a return type for call!"),
+                    }
+                    .into(),
+                )
+            }
+
+            ExpressionNode::Enum(expr) => Ok(expr.name),
+
+            ExpressionNode::Match(expr) => {
+                let types = expr
+                    .arms
+                    .iter()
+                    .map(|arm| arm.body.data.get_type(func, tree))
+                    .collect::<Result<Vec<_>>>()?;
+
+                match types.first() {
+                    Some(ty) if types.iter().all(|v| v == ty) => Ok(ty.clone()),
+
+                    _ => Err(LexicalError {
+                        location: expr.span.into_source_span(),
+                        src: tree.src.clone().into(),
+                        error: miette!("Match arms do not all have the same type!"),
+                    }
+                    .into()),
+                }
+            }
+
+            ExpressionNode::VariantField(expr) => tree
+                .enums()
+                .get(&expr.name)
+                .and_then(|item| item.variant(&expr.variant))
+                .and_then(|(_, variant)| variant.fields.get(expr.index))
+                .map(|field| field.as_str())
+                .ok_or_else(|| {
+                    LexicalError {
+                        location: expr.span.into_source_span(),
+                        src: tree.src.clone().into(),
+                        error: miette!(
+                            "Variant {}::{} has no field {}!",
+                            expr.name,
+                            expr.variant,
+                            expr.index
+                        ),
+                    }
+                    .into()
+                }),
+
+            ExpressionNode::Array(expr) => {
+                let Some(first) = expr.values.first() else {
+                    return Err(LexicalError {
+                        location: expr.span.into_source_span(),
+                        src: tree.src.clone().into(),
+                        error: miette!("Cannot infer the type of an empty array!"),
+                    }
+                    .into());
+                };
+
+                Ok(TypeNode::array(
+                    &first.data.get_type(func, tree)?,
+                    expr.values.len(),
+                ))
+            }
+
+            ExpressionNode::Tuple(expr) => Ok(TypeNode::tuple(
+                &expr
+                    .values
+                    .iter()
+                    .map(|value| value.data.get_type(func, tree))
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+
+            ExpressionNode::Cast(expr) => Ok(expr.type_.as_str()),
+
+            ExpressionNode::Comptime(expr) => match expr.returned() {
+                Some(value) => value.data.get_type(func, tree),
+                None => Ok("void".to_string()),
+            },
+
+            // A macro gives the value of the last thing in its body.
+            ExpressionNode::MacroCall(expr) => {
+                let Some(expansion) = expr.expansion else {
+                    return Err(LexicalError {
+                        location: expr.span.into_source_span(),
+                        src: tree.src.clone().into(),
+                        error: miette!("Macro {} hasn't been expanded!", expr.name),
+                    }
+                    .into());
+                };
+
+                match expansion.data.last().map(|node| node.data.as_ref()) {
+                    Some(NodeData::Declaration(_)) | Some(NodeData::Block(_)) | None => {
+                        Ok("void".to_string())
+                    }
+
+                    Some(NodeData::Statement(stmt)) if !stmt.is_call() => Ok("void".to_string()),
+                    Some(data) => data.get_type(func, tree),
+                }
+            }
+
+            ExpressionNode::Spread(expr) => Err(LexicalError {
+                location: expr.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("{}... can only be used in a macro!", expr.name),
+            }
+            .into()),
+
+            ExpressionNode::Closure(expr) => expr.get_type().ok_or_else(|| {
+                LexicalError {
+                    location: expr.span.into_source_span(),
+                    src: tree.src.clone().into(),
+                    error: miette!("Cannot infer the types of the closure's arguments!"),
+                }
+                .into()
+            }),
+
+            ExpressionNode::Ref(expr) => {
+                Ok(TypeNode::pointer(&expr.value.data.get_type(func, tree)?))
+            }
+
+            ExpressionNode::Deref(expr) => {
+                let ty = expr.value.data.get_type(func, tree)?;
+
+                TypeNode::pointee(&ty)
+                    .map(|v| v.to_string())
+                    .ok_or_else(|| {
+                        LexicalError {
+                            location: expr.value.span.into_source_span(),
+                            src: tree.src.clone().into(),
+                            error: miette!("Type {} cannot be dereferenced!", ty),
+                        }
+                        .into()
+                    })
+            }
+
+            ExpressionNode::Index(expr) => {
+                let ty = expr.value.data.get_type(func, tree)?;
+
+                TypeNode::array_parts(&ty)
+                    .map(|(elem, _)| elem.to_string())
+                    .ok_or_else(|| {
+                        LexicalError {
+                            location: expr.value.span.into_source_span(),
+                            src: tree.src.clone().into(),
+                            error: miette!("Type {} cannot be indexed!", ty),
+                        }
+                        .into()
+                    })
+            }
         }
     }
 }
 
 is_enum_variant_impl!(is_binary -> ExpressionNode::Binary);
 is_enum_variant_impl!(is_unary -> ExpressionNode::Unary);
+is_enum_variant_impl!(is_struct -> ExpressionNode::Struct);
+is_enum_variant_impl!(is_field -> ExpressionNode::Field);
+is_enum_variant_impl!(is_enum -> ExpressionNode::Enum);
+is_enum_variant_impl!(is_match -> ExpressionNode::Match);
+is_enum_variant_impl!(is_variant_field -> ExpressionNode::VariantField);
+is_enum_variant_impl!(is_array -> ExpressionNode::Array);
+is_enum_variant_impl!(is_tuple -> ExpressionNode::Tuple);
+is_enum_variant_impl!(is_index -> ExpressionNode::Index);
+is_enum_variant_impl!(is_ref -> ExpressionNode::Ref);
+is_enum_variant_impl!(is_deref -> ExpressionNode::Deref);
+is_enum_variant_impl!(is_cast -> ExpressionNode::Cast);
+is_enum_variant_impl!(is_closure -> ExpressionNode::Closure);
+is_enum_variant_impl!(is_method -> ExpressionNode::Method);
+is_enum_variant_impl!(is_comptime -> ExpressionNode::Comptime);
+is_enum_variant_impl!(is_macro_call -> ExpressionNode::MacroCall);
+is_enum_variant_impl!(is_spread -> ExpressionNode::Spread);
 
 get_enum_variant_value_impl!(as_binary -> ExpressionNode::Binary: BinaryExpr);
 get_enum_variant_value_impl!(as_unary -> ExpressionNode::Unary: UnaryExpr);
+get_enum_variant_value_impl!(as_struct -> ExpressionNode::Struct: StructExpr);
+get_enum_variant_value_impl!(as_field -> ExpressionNode::Field: FieldExpr);
+get_enum_variant_value_impl!(as_enum -> ExpressionNode::Enum: EnumExpr);
+get_enum_variant_value_impl!(as_match -> ExpressionNode::Match: MatchExpr);
+get_enum_variant_value_impl!(as_variant_field -> ExpressionNode::VariantField: VariantFieldExpr);
+get_enum_variant_value_impl!(as_array -> ExpressionNode::Array: ArrayExpr);
+get_enum_variant_value_impl!(as_tuple -> ExpressionNode::Tuple: TupleExpr);
+get_enum_variant_value_impl!(as_index -> ExpressionNode::Index: IndexExpr);
+get_enum_variant_value_impl!(as_ref -> ExpressionNode::Ref: RefExpr);
+get_enum_variant_value_impl!(as_deref -> ExpressionNode::Deref: DerefExpr);
+get_enum_variant_value_impl!(as_cast -> ExpressionNode::Cast: CastExpr);
+get_enum_variant_value_impl!(as_closure -> ExpressionNode::Closure: ClosureExpr);
+get_enum_variant_value_impl!(as_method -> ExpressionNode::Method: MethodCallExpr);
+get_enum_variant_value_impl!(as_comptime -> ExpressionNode::Comptime: ComptimeExpr);
+get_enum_variant_value_impl!(as_macro_call -> ExpressionNode::MacroCall: MacroCallExpr);
+get_enum_variant_value_impl!(as_spread -> ExpressionNode::Spread: SpreadExpr);
@@ -62,6 +62,12 @@ pub enum Operator {
     #[strum(serialize = "^")]
     BitwiseXor,
 
+    #[strum(serialize = "<<")]
+    ShiftLeft,
+
+    #[strum(serialize = ">>")]
+    ShiftRight,
+
     // Bitwise Assignment Operators
     #[strum(serialize = "&=")]
     BitwiseAndAssign,
@@ -75,6 +81,12 @@ pub enum Operator {
     #[strum(serialize = "^=")]
     BitwiseXorAssign,
 
+    #[strum(serialize = "<<=")]
+    ShiftLeftAssign,
+
+    #[strum(serialize = ">>=")]
+    ShiftRightAssign,
+
     // Equality Operators
     #[strum(serialize = "==")]
     Equal,
@@ -94,3 +106,58 @@ pub enum Operator {
     #[strum(serialize = "<=")]
     LessEqual,
 }
+
+impl Operator {
+    pub fn is_assign(&self) -> bool {
+        matches!(
+            self,
+            Self::Assign
+                | Self::AddAssign
+                | Self::SubtractAssign
+                | Self::MultiplyAssign
+                | Self::DivideAssign
+                | Self::ModuloAssign
+                | Self::BitwiseAndAssign
+                | Self::BitwiseOrAssign
+                | Self::BitwiseNotAssign
+                | Self::BitwiseXorAssign
+                | Self::ShiftLeftAssign
+                | Self::ShiftRightAssign
+        )
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Equal
+                | Self::NotEqual
+                | Self::Greater
+                | Self::Less
+                | Self::GreaterEqual
+                | Self::LessEqual
+        )
+    }
+
+    pub fn is_logical(&self) -> bool {
+        matches!(self, Self::And | Self::Or | Self::Not)
+    }
+
+    /// Get the operation a compound assignment applies before storing
+    /// its result, e.g. [`Operator::Add`] for [`Operator::AddAssign`].
+    pub fn base_op(&self) -> Option<Operator> {
+        match self {
+            Self::AddAssign => Some(Self::Add),
+            Self::SubtractAssign => Some(Self::Subtract),
+            Self::MultiplyAssign => Some(Self::Multiply),
+            Self::DivideAssign => Some(Self::Divide),
+            Self::ModuloAssign => Some(Self::Modulo),
+            Self::BitwiseAndAssign => Some(Self::BitwiseAnd),
+            Self::BitwiseOrAssign => Some(Self::BitwiseOr),
+            Self::BitwiseNotAssign => Some(Self::BitwiseNot),
+            Self::BitwiseXorAssign => Some(Self::BitwiseXor),
+            Self::ShiftLeftAssign => Some(Self::ShiftLeft),
+            Self::ShiftRightAssign => Some(Self::ShiftRight),
+            _ => None,
+        }
+    }
+}
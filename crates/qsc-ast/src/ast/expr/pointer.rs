@@ -0,0 +1,17 @@
+use crate::{ast::node::Node, span::StaticSpan};
+
+/// Taking the address of a place, like `&x` or `&point.x`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+}
+
+/// Reading (or writing) through a pointer, like `*p`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerefExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+}
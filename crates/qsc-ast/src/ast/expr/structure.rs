@@ -0,0 +1,30 @@
+use crate::{
+    ast::node::{sym::SymbolNode, Node},
+    span::StaticSpan,
+};
+
+/// A struct literal, like `Point { x: 1, y: 2 }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub fields: Vec<StructFieldValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructFieldValue {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub value: Node,
+}
+
+/// A field access, like `point.x`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub value: Node,
+    pub field: SymbolNode,
+}
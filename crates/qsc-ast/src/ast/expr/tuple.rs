@@ -0,0 +1,9 @@
+use crate::{ast::node::Node, span::StaticSpan};
+
+/// A tuple literal, like `(1, true)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TupleExpr {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub values: Vec<Node>,
+}
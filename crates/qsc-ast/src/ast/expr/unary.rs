@@ -1,8 +1,10 @@
 use crate::{ast::node::Node, span::StaticSpan};
 
+use super::operator::Operator;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnaryExpr {
-    pub negative: bool,
+    pub operator: Operator,
     #[serde(skip)]
     pub span: StaticSpan,
     pub value: Node,
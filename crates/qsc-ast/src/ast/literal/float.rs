@@ -5,4 +5,8 @@ pub struct FloatNode {
     #[serde(skip)]
     pub span: StaticSpan,
     pub value: f64,
+
+    /// The type from the literal's suffix, or from its context. Literals
+    /// without either are `f64`s.
+    pub type_: Option<String>,
 }
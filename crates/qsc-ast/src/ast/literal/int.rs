@@ -5,4 +5,8 @@ pub struct IntNode {
     #[serde(skip)]
     pub span: StaticSpan,
     pub value: i64,
+
+    /// The type from the literal's suffix, or from its context. Literals
+    /// without either are `i32`s.
+    pub type_: Option<String>,
 }
@@ -22,13 +22,22 @@ pub enum LiteralNode {
 impl LiteralNode {
     pub fn get_type(&self) -> Result<String> {
         Ok(match self.clone() {
-            LiteralNode::Bool(_) => "bool",
-            LiteralNode::Char(_) => "char",
-            LiteralNode::Float(_) => "f32",
-            LiteralNode::Int(_) => "i32",
-            LiteralNode::String(_) => "str",
+            LiteralNode::Bool(_) => "bool".to_string(),
+            LiteralNode::Char(_) => "char".to_string(),
+            LiteralNode::Float(float) => float.type_.unwrap_or("f64".to_string()),
+            LiteralNode::Int(int) => int.type_.unwrap_or("i32".to_string()),
+            LiteralNode::String(_) => "str".to_string(),
+        })
+    }
+
+    /// Whether this is a number without a suffix, which can still take its
+    /// type from wherever it is used.
+    pub fn is_untyped(&self) -> bool {
+        match self {
+            LiteralNode::Float(float) => float.type_.is_none(),
+            LiteralNode::Int(int) => int.type_.is_none(),
+            _ => false,
         }
-        .to_string())
     }
 }
 
@@ -12,8 +12,11 @@ use std::collections::HashMap;
 use crate::{compat::WrappedNamedSource, span::StaticSpan};
 
 use self::{
-    decl::{external::ExternFunctionNode, func::FunctionNode, global::GlobalVariable},
-    node::Node,
+    decl::{
+        enumeration::EnumNode, external::ExternFunctionNode, func::FunctionNode,
+        global::GlobalVariable, structure::StructNode, traits::TraitNode,
+    },
+    node::{attr::Attribute, ty::TypeNode, Node},
 };
 
 use miette::NamedSource;
@@ -69,9 +72,111 @@ impl AbstractTree {
         globals
     }
 
-    // TODO: `use` statements and actually do this
-    pub fn imported_functions(&self) -> &[&str] {
-        &["printf", "puts", "getchar"]
+    pub fn structs(&self) -> HashMap<String, StructNode> {
+        let mut structs = HashMap::new();
+
+        for node in &self.data {
+            if let Ok(decl) = node.data.as_decl() {
+                if let Ok(item) = decl.as_struct() {
+                    structs.insert(item.name.clone(), item);
+                }
+            }
+        }
+
+        structs
+    }
+
+    pub fn traits(&self) -> HashMap<String, TraitNode> {
+        let mut traits = HashMap::new();
+
+        for node in &self.data {
+            if let Ok(decl) = node.data.as_decl() {
+                if let Ok(item) = decl.as_trait() {
+                    traits.insert(item.name.clone(), item);
+                }
+            }
+        }
+
+        traits
+    }
+
+    /// Whether there is an `impl Trait for Type` for `ty`.
+    pub fn implements(&self, ty: &str, trait_: &str) -> bool {
+        self.data.iter().any(|node| {
+            node.data
+                .as_decl()
+                .and_then(|decl| decl.as_impl())
+                .is_ok_and(|item| item.name == ty && item.trait_.as_deref() == Some(trait_))
+        })
+    }
+
+    pub fn enums(&self) -> HashMap<String, EnumNode> {
+        let mut enums = HashMap::new();
+
+        for node in &self.data {
+            if let Ok(decl) = node.data.as_decl() {
+                if let Ok(item) = decl.as_enum() {
+                    enums.insert(item.name.clone(), item);
+                }
+            }
+        }
+
+        enums
+    }
+
+    /// Whether values of this type live in memory and get passed around
+    /// by pointer, which is the case for structs, enums, arrays and tuples.
+    pub fn is_aggregate(&self, ty: impl AsRef<str>) -> bool {
+        self.structs().contains_key(ty.as_ref())
+            || self.enums().contains_key(ty.as_ref())
+            || TypeNode::array_parts(ty.as_ref()).is_some()
+            || TypeNode::tuple_parts(ty.as_ref()).is_some()
+    }
+
+    /// Get the elements of a tuple that is small enough to be returned
+    /// from functions as one value for each element, instead of through
+    /// memory. That's tuples of up to two elements that aren't aggregates.
+    pub fn split_tuple(&self, ty: impl AsRef<str>) -> Option<Vec<String>> {
+        TypeNode::tuple_parts(ty.as_ref())
+            .filter(|elems| elems.len() <= 2 && !elems.iter().any(|ty| self.is_aggregate(ty)))
+    }
+
+    /// Whether functions returning this type write it to storage the caller
+    /// passes them a pointer to, which all aggregates except small tuples do.
+    pub fn returns_by_pointer(&self, ty: impl AsRef<str>) -> bool {
+        self.is_aggregate(ty.as_ref()) && self.split_tuple(ty.as_ref()).is_none()
+    }
+
+    /// Get the type of a named function used as a value, like
+    /// `fn(i32) -> i32`.
+    pub fn function_type(&self, name: &str) -> Option<String> {
+        let (args, ret) = match self.functions().get(name) {
+            Some(func) => (func.args.clone(), func.ret.clone()),
+
+            None => {
+                let func = self.externs().get(name)?.clone();
+
+                (func.args, func.ret)
+            }
+        };
+
+        let args = args
+            .iter()
+            .map(|arg| arg.type_.as_str())
+            .collect::<Vec<_>>();
+
+        Some(TypeNode::function(
+            &args,
+            ret.map(|v| v.as_str()).as_deref(),
+        ))
+    }
+
+    /// Find the method `name` of a type, or of the type a pointer points
+    /// to. Methods are functions named like `Type::method`.
+    pub fn method(&self, ty: &str, name: &str) -> Option<FunctionNode> {
+        let ty = TypeNode::pointee(ty).unwrap_or(ty);
+
+        self.functions().remove(&format!("{}::{}", ty, name))
     }
 
     // Intrinsics
@@ -90,11 +195,38 @@ impl AbstractTree {
         map
     }
 
-    // TODO: add support for custom structs and types
-    pub fn types(&self) -> &[&str] {
+    /// The libraries the externs ask to be linked with, using `@Link`.
+    pub fn links(&self) -> Vec<String> {
+        let mut libs = Vec::new();
+
+        for node in &self.data {
+            let Ok(func) = node.data.as_decl().and_then(|decl| decl.as_extern()) else {
+                continue;
+            };
+
+            if let Some(lib) = Attribute::find(&func.attrs, "Link").and_then(|attr| attr.string()) {
+                if !libs.contains(&lib) {
+                    libs.push(lib);
+                }
+            }
+        }
+
+        libs
+    }
+
+    pub fn primitives(&self) -> &[&str] {
         &[
             "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "bool", "char",
             "str",
         ]
     }
+
+    pub fn types(&self) -> Vec<String> {
+        self.primitives()
+            .iter()
+            .map(|ty| ty.to_string())
+            .chain(self.structs().into_keys())
+            .chain(self.enums().into_keys())
+            .collect()
+    }
 }
@@ -0,0 +1,28 @@
+use crate::span::StaticSpan;
+
+use super::Node;
+
+/// An attribute on a declaration, like `@Inline` or `@Export("name")`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub name: String,
+    pub args: Vec<Node>,
+}
+
+impl Attribute {
+    /// Find the attribute called `name` in a list of them.
+    pub fn find<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+        attrs.iter().find(|attr| attr.name == name)
+    }
+
+    /// The value of the first argument, if it is a string.
+    pub fn string(&self) -> Option<String> {
+        self.args
+            .first()
+            .and_then(|arg| arg.data.as_literal().ok())
+            .and_then(|lit| lit.as_string().ok())
+            .map(|lit| lit.value)
+    }
+}
@@ -1,5 +1,8 @@
-use super::Node;
-use crate::{ast::decl::var::VariableNode, span::StaticSpan};
+use super::{data::NodeData, Node};
+use crate::{
+    ast::{decl::var::VariableNode, expr::ExpressionNode, stmt::StatementNode},
+    span::StaticSpan,
+};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Block {
@@ -9,15 +12,78 @@ pub struct Block {
 }
 
 impl Block {
+    /// Call `f` on every node in this block, including nested ones.
+    pub fn walk(&self, f: &mut impl FnMut(&Node)) {
+        for node in &self.data {
+            node.walk(f);
+        }
+    }
+
+    /// Like [`Block::walk`], but `f` can change the nodes it is given.
+    pub fn walk_mut(&mut self, f: &mut impl FnMut(&mut Node)) {
+        for node in &mut self.data {
+            node.walk_mut(f);
+        }
+    }
+
+    /// Get every variable declared in this block, including the ones
+    /// declared in nested blocks, loop variables and match bindings.
     pub fn vars(&self) -> Vec<VariableNode> {
         let mut vars = Vec::new();
 
         for node in &self.data {
-            if let Ok(decl) = node.data.as_decl() {
-                if let Ok(var) = decl.as_variable() {
-                    vars.push(var);
+            match &*node.data {
+                NodeData::Declaration(decl) => {
+                    if let Ok(var) = decl.as_variable() {
+                        if let Some(value) = &var.value {
+                            if let NodeData::Expr(ExpressionNode::Match(expr)) = &*value.data {
+                                vars.extend(expr.vars());
+                            }
+                        }
+
+                        vars.push(var);
+                    }
+                }
+
+                NodeData::Expr(ExpressionNode::Match(expr)) => vars.extend(expr.vars()),
+
+                NodeData::Statement(StatementNode::Return(ret)) => {
+                    if let Some(value) = &ret.value {
+                        if let NodeData::Expr(ExpressionNode::Match(expr)) = &*value.data {
+                            vars.extend(expr.vars());
+                        }
+                    }
                 }
+
+                NodeData::Block(block) => vars.extend(block.vars()),
+
+                NodeData::Statement(StatementNode::Condition(cond)) => {
+                    vars.extend(cond.block.vars());
+
+                    if let Some(else_block) = &cond.else_block {
+                        vars.extend(else_block.vars());
+                    }
+                }
+
+                NodeData::Statement(StatementNode::While(node)) => vars.extend(node.block.vars()),
+
+                NodeData::Statement(StatementNode::Repeat(node)) => vars.extend(node.block.vars()),
+
+                NodeData::Statement(StatementNode::For(node)) => {
+                    vars.push(node.variable());
+                    vars.extend(node.block.vars());
+                }
+
+                _ => {}
             }
+
+            // Macros can be expanded anywhere, even in the middle of an
+            // expression, and their variables belong to the block around them.
+            node.walk(&mut |node| {
+                if let NodeData::Expr(ExpressionNode::MacroCall(expr)) = &*node.data {
+                    vars.extend(expr.expansion.iter().flat_map(|block| block.vars()));
+                }
+            });
         }
 
         vars
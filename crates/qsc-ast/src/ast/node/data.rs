@@ -11,7 +11,7 @@ use crate::{
     get_enum_variant_value_impl, is_enum_variant_impl, is_enum_variant_no_field_impl,
 };
 
-use super::{block::Block, sym::SymbolNode, ty::TypeNode};
+use super::{block::Block, sym::SymbolNode, ty::TypeNode, Node};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum NodeData {
@@ -56,6 +56,8 @@ impl NodeData {
                 if func.is_none() {
                     if let Some(var) = globals.get(&sym.value) {
                         Ok(var.type_.as_str())
+                    } else if let Some(ty) = tree.function_type(&sym.value) {
+                        Ok(ty)
                     } else {
                         Err(LexicalError {
                             location: sym.span.into_source_span(),
@@ -83,6 +85,8 @@ impl NodeData {
                         }
                     } else if let Some(var) = globals.get(&sym.value) {
                         Ok(var.type_.as_str())
+                    } else if let Some(ty) = tree.function_type(&sym.value) {
+                        Ok(ty)
                     } else {
                         Err(LexicalError {
                             location: sym.span.into_source_span(),
@@ -95,6 +99,154 @@ impl NodeData {
         }
     }
 
+    /// Get the nodes directly nested inside of this one. Blocks are
+    /// wrapped in nodes of their own.
+    pub fn children(&self) -> Vec<Node> {
+        let block = |block: &Block| Node {
+            span: block.span.clone(),
+            data: Box::new(NodeData::Block(block.clone())),
+        };
+
+        match self {
+            NodeData::Block(item) => item.data.clone(),
+
+            NodeData::Expr(expr) => match expr {
+                ExpressionNode::Binary(expr) => vec![expr.lhs.clone(), expr.rhs.clone()],
+                ExpressionNode::Unary(expr) => vec![expr.value.clone()],
+                ExpressionNode::Struct(expr) => {
+                    expr.fields.iter().map(|v| v.value.clone()).collect()
+                }
+                ExpressionNode::Field(expr) => vec![expr.value.clone()],
+                ExpressionNode::Enum(expr) => expr.values.clone(),
+                ExpressionNode::Match(expr) => std::iter::once(expr.value.clone())
+                    .chain(expr.arms.iter().map(|v| v.body.clone()))
+                    .collect(),
+                ExpressionNode::VariantField(expr) => vec![expr.value.clone()],
+                ExpressionNode::Array(expr) => expr.values.clone(),
+                ExpressionNode::Tuple(expr) => expr.values.clone(),
+                ExpressionNode::Index(expr) => vec![expr.value.clone(), expr.index.clone()],
+                ExpressionNode::Ref(expr) => vec![expr.value.clone()],
+                ExpressionNode::Deref(expr) => vec![expr.value.clone()],
+                ExpressionNode::Cast(expr) => vec![expr.value.clone()],
+                ExpressionNode::Closure(expr) => vec![block(&expr.body)],
+
+                ExpressionNode::Method(expr) => std::iter::once(expr.value.clone())
+                    .chain(expr.args.iter().map(|arg| arg.value.clone()))
+                    .collect(),
+
+                ExpressionNode::Comptime(expr) => vec![expr.value.clone()],
+
+                ExpressionNode::MacroCall(expr) => match &expr.expansion {
+                    Some(expansion) => vec![block(expansion)],
+                    None => expr.args.clone(),
+                },
+
+                ExpressionNode::Spread(_) => Vec::new(),
+            },
+
+            NodeData::Statement(stmt) => match stmt {
+                StatementNode::Call(call) => call.args.iter().map(|v| v.value.clone()).collect(),
+                StatementNode::Return(ret) => ret.value.clone().into_iter().collect(),
+                StatementNode::Condition(cond) => vec![cond.condition.clone(), block(&cond.block)]
+                    .into_iter()
+                    .chain(cond.else_block.as_ref().map(block))
+                    .collect(),
+                StatementNode::While(node) => vec![node.condition.clone(), block(&node.block)],
+                StatementNode::For(node) => vec![
+                    node.range.start.clone(),
+                    node.range.end.clone(),
+                    block(&node.block),
+                ],
+                StatementNode::Repeat(node) => vec![block(&node.block)],
+                StatementNode::Break(_) | StatementNode::Continue(_) => Vec::new(),
+            },
+
+            NodeData::Declaration(decl) => match decl {
+                DeclarationNode::Function(func) => func.content.data.clone(),
+                DeclarationNode::Macro(item) => item.body.data.clone(),
+                DeclarationNode::Variable(var) => var.value.clone().into_iter().collect(),
+                DeclarationNode::Global(global) => vec![global.value.clone()],
+                _ => Vec::new(),
+            },
+
+            NodeData::Literal(_) | NodeData::Symbol(_) | NodeData::Type(_) | NodeData::EOI => {
+                Vec::new()
+            }
+        }
+    }
+
+    /// Like [`NodeData::children`], but for changing them in place. Blocks
+    /// aren't nodes on their own, so their contents are returned instead.
+    pub fn children_mut(&mut self) -> Vec<&mut Node> {
+        match self {
+            NodeData::Block(item) => item.data.iter_mut().collect(),
+
+            NodeData::Expr(expr) => match expr {
+                ExpressionNode::Binary(expr) => vec![&mut expr.lhs, &mut expr.rhs],
+                ExpressionNode::Unary(expr) => vec![&mut expr.value],
+                ExpressionNode::Struct(expr) => {
+                    expr.fields.iter_mut().map(|v| &mut v.value).collect()
+                }
+                ExpressionNode::Field(expr) => vec![&mut expr.value],
+                ExpressionNode::Enum(expr) => expr.values.iter_mut().collect(),
+                ExpressionNode::Match(expr) => std::iter::once(&mut expr.value)
+                    .chain(expr.arms.iter_mut().map(|v| &mut v.body))
+                    .collect(),
+                ExpressionNode::VariantField(expr) => vec![&mut expr.value],
+                ExpressionNode::Array(expr) => expr.values.iter_mut().collect(),
+                ExpressionNode::Tuple(expr) => expr.values.iter_mut().collect(),
+                ExpressionNode::Index(expr) => vec![&mut expr.value, &mut expr.index],
+                ExpressionNode::Ref(expr) => vec![&mut expr.value],
+                ExpressionNode::Deref(expr) => vec![&mut expr.value],
+                ExpressionNode::Cast(expr) => vec![&mut expr.value],
+                ExpressionNode::Closure(expr) => expr.body.data.iter_mut().collect(),
+
+                ExpressionNode::Method(expr) => std::iter::once(&mut expr.value)
+                    .chain(expr.args.iter_mut().map(|arg| &mut arg.value))
+                    .collect(),
+
+                ExpressionNode::Comptime(expr) => vec![&mut expr.value],
+
+                ExpressionNode::MacroCall(expr) => match &mut expr.expansion {
+                    Some(expansion) => expansion.data.iter_mut().collect(),
+                    None => expr.args.iter_mut().collect(),
+                },
+
+                ExpressionNode::Spread(_) => Vec::new(),
+            },
+
+            NodeData::Statement(stmt) => match stmt {
+                StatementNode::Call(call) => call.args.iter_mut().map(|v| &mut v.value).collect(),
+                StatementNode::Return(ret) => ret.value.iter_mut().collect(),
+                StatementNode::Condition(cond) => std::iter::once(&mut cond.condition)
+                    .chain(cond.block.data.iter_mut())
+                    .chain(cond.else_block.iter_mut().flat_map(|v| v.data.iter_mut()))
+                    .collect(),
+                StatementNode::While(node) => std::iter::once(&mut node.condition)
+                    .chain(node.block.data.iter_mut())
+                    .collect(),
+                StatementNode::For(node) => [&mut node.range.start, &mut node.range.end]
+                    .into_iter()
+                    .chain(node.block.data.iter_mut())
+                    .collect(),
+                StatementNode::Repeat(node) => node.block.data.iter_mut().collect(),
+                StatementNode::Break(_) | StatementNode::Continue(_) => Vec::new(),
+            },
+
+            NodeData::Declaration(decl) => match decl {
+                DeclarationNode::Function(func) => func.content.data.iter_mut().collect(),
+                DeclarationNode::Macro(item) => item.body.data.iter_mut().collect(),
+                DeclarationNode::Variable(var) => var.value.iter_mut().collect(),
+                DeclarationNode::Global(global) => vec![&mut global.value],
+                _ => Vec::new(),
+            },
+
+            NodeData::Literal(_) | NodeData::Symbol(_) | NodeData::Type(_) | NodeData::EOI => {
+                Vec::new()
+            }
+        }
+    }
+
     pub fn is_int(&self, func: &Option<String>, tree: &AbstractTree) -> Result<bool> {
         if let Ok(lit) = self.as_literal() {
             Ok(lit.is_int())
@@ -104,6 +256,11 @@ impl NodeData {
         }
     }
 
+    /// Whether this is a signed integer.
+    pub fn is_signed(&self, func: &Option<String>, tree: &AbstractTree) -> Result<bool> {
+        Ok(["i8", "i16", "i32", "i64"].contains(&self.get_type(func, tree)?.as_str()))
+    }
+
     pub fn is_float(&self, func: &Option<String>, tree: &AbstractTree) -> Result<bool> {
         if let Ok(lit) = self.as_literal() {
             Ok(lit.is_float())
@@ -1,3 +1,4 @@
+pub mod attr;
 pub mod block;
 pub mod data;
 pub mod sym;
@@ -14,3 +15,23 @@ pub struct Node {
     pub span: StaticSpan,
     pub data: Box<NodeData>,
 }
+
+impl Node {
+    /// Call `f` on this node and on every node nested inside of it.
+    pub fn walk(&self, f: &mut impl FnMut(&Node)) {
+        f(self);
+
+        for child in self.data.children() {
+            child.walk(f);
+        }
+    }
+
+    /// Like [`Node::walk`], but `f` can change the nodes it is given.
+    pub fn walk_mut(&mut self, f: &mut impl FnMut(&mut Node)) {
+        f(self);
+
+        for child in self.data.children_mut() {
+            child.walk_mut(f);
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use qsc_core::util::{split_function_type, split_generics};
+
 use crate::span::StaticSpan;
 
 use super::sym::SymbolNode;
@@ -26,4 +28,68 @@ impl TypeNode {
             format!("{}", self.name)
         }
     }
+
+    /// Build the name of a typed pointer, like `ptr<i32>`.
+    pub fn pointer(pointee: &str) -> String {
+        format!("ptr<{}>", pointee)
+    }
+
+    /// Get the type a typed pointer points to. Plain `ptr`s don't have one.
+    pub fn pointee(ty: &str) -> Option<&str> {
+        ty.strip_prefix("ptr<")?.strip_suffix('>')
+    }
+
+    /// Build the name of a function type, like `fn(i32, i32) -> i32`.
+    pub fn function(params: &[String], ret: Option<&str>) -> String {
+        match ret {
+            Some(ret) => format!("fn({}) -> {}", params.join(", "), ret),
+            None => format!("fn({})", params.join(", ")),
+        }
+    }
+
+    /// Split a function type into its parameter types and return type.
+    pub fn function_parts(ty: &str) -> Option<(Vec<String>, Option<String>)> {
+        let (params, ret) = split_function_type(ty)?;
+
+        Some((
+            params.into_iter().map(|v| v.to_string()).collect(),
+            ret.map(|v| v.to_string()),
+        ))
+    }
+
+    /// Build the name of a fixed-size array type, like `[i32; 4]`.
+    pub fn array(elem: &str, len: usize) -> String {
+        format!("[{}; {}]", elem, len)
+    }
+
+    /// Split an array type into its element type and length.
+    pub fn array_parts(ty: &str) -> Option<(&str, usize)> {
+        let (elem, len) = ty.strip_prefix('[')?.strip_suffix(']')?.rsplit_once("; ")?;
+
+        Some((elem, len.parse().ok()?))
+    }
+
+    /// Build the name of a tuple type, like `(i32, bool)`. Tuples with
+    /// just one element keep a trailing comma, like `(i32,)`.
+    pub fn tuple(elems: &[String]) -> String {
+        match elems {
+            [elem] => format!("({},)", elem),
+            _ => format!("({})", elems.join(", ")),
+        }
+    }
+
+    /// Split a tuple type into the types of its elements.
+    pub fn tuple_parts(ty: &str) -> Option<Vec<String>> {
+        let elems = split_generics(ty.strip_prefix('(')?.strip_suffix(')')?);
+
+        (!elems.is_empty()).then(|| elems.into_iter().map(|v| v.to_string()).collect())
+    }
+
+    /// Split an array type whose length is still the name of a constant,
+    /// like `[i32; SIZE]`.
+    pub fn named_array_parts(ty: &str) -> Option<(&str, &str)> {
+        let (elem, len) = ty.strip_prefix('[')?.strip_suffix(']')?.rsplit_once("; ")?;
+
+        len.parse::<usize>().is_err().then_some((elem, len))
+    }
 }
@@ -15,9 +15,11 @@ use strum::EnumString;
     Deserialize,
 )]
 pub enum Visibility {
+    /// Usable anywhere, and exported from the object.
     #[strum(ascii_case_insensitive, serialize = "public", serialize = "pub")]
     Public,
 
+    /// Usable anywhere in the program, but not exported.
     #[strum(
         ascii_case_insensitive,
         serialize = "internal",
@@ -25,6 +27,7 @@ pub enum Visibility {
     )]
     Internal,
 
+    /// Usable in the declaring module and the modules inside of it.
     #[default]
     #[strum(ascii_case_insensitive, serialize = "private", serialize = "priv")]
     Private,
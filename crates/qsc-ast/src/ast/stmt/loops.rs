@@ -0,0 +1,71 @@
+use crate::{
+    ast::{
+        decl::var::VariableNode,
+        node::{block::Block, sym::SymbolNode, ty::TypeNode, Node},
+    },
+    span::StaticSpan,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhileNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub condition: Node,
+    pub block: Block,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BreakNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ContinueNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub start: Node,
+    pub end: Node,
+    pub inclusive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub var: SymbolNode,
+    pub type_: Option<TypeNode>,
+    pub range: RangeNode,
+    pub block: Block,
+}
+
+impl ForNode {
+    /// Get the loop variable as if it was declared with
+    /// `let i = <start>;`, so its type can be inferred from the range.
+    pub fn variable(&self) -> VariableNode {
+        VariableNode {
+            span: self.var.span.clone(),
+            name: self.var.value.clone(),
+            type_: self.type_.clone(),
+            value: Some(self.range.start.clone()),
+            mutable: false,
+        }
+    }
+}
+
+/// `for arg in args... { ... }` in a macro, which is unrolled into a copy
+/// of the block for each argument given for the variadic `args`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepeatNode {
+    #[serde(skip)]
+    pub span: StaticSpan,
+    pub var: SymbolNode,
+    pub list: SymbolNode,
+    pub block: Block,
+}
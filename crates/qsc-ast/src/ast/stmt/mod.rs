@@ -5,12 +5,21 @@ use qsc_core::{
 
 use crate::{get_enum_variant_value_impl, is_enum_variant_impl};
 
-use self::{call::CallNode, cond::ConditionalNode, ret::ReturnNode};
+use self::{
+    call::CallNode,
+    cond::ConditionalNode,
+    loops::{BreakNode, ContinueNode, ForNode, RepeatNode, WhileNode},
+    ret::ReturnNode,
+};
 
-use super::AbstractTree;
+use super::{
+    node::{data::NodeData, sym::SymbolNode, ty::TypeNode},
+    AbstractTree,
+};
 
 pub mod call;
 pub mod cond;
+pub mod loops;
 pub mod ret;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,6 +27,11 @@ pub enum StatementNode {
     Call(CallNode),
     Return(ReturnNode),
     Condition(ConditionalNode),
+    While(WhileNode),
+    For(ForNode),
+    Break(BreakNode),
+    Continue(ContinueNode),
+    Repeat(RepeatNode),
 }
 
 impl StatementNode {
@@ -38,8 +52,15 @@ impl StatementNode {
                 } else {
                     if let Some(func) = tree.externs().get(&call.func) {
                         Ok(func.ret.clone().map(|v| v.as_str()).unwrap_or("ptr".into()))
-                    } else if tree.imported_functions().contains(&call.func.as_str()) {
-                        Ok("ptr".to_string())
+                    } else if let Some((_, ret)) = NodeData::Symbol(SymbolNode {
+                        span: call.span.clone(),
+                        value: call.func.clone(),
+                    })
+                    .get_type(_func, tree)
+                    .ok()
+                    .and_then(|ty| TypeNode::function_parts(&ty))
+                    {
+                        Ok(ret.unwrap_or("ptr".into()))
                     } else {
                         Err(LexicalError {
                             location: call.span.into_source_span(),
@@ -58,6 +79,41 @@ impl StatementNode {
             }
             .into()),
 
+            Self::While(node) => Err(LexicalError {
+                location: node.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("Loop return values are not currently supported!"),
+            }
+            .into()),
+
+            Self::For(node) => Err(LexicalError {
+                location: node.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("Loop return values are not currently supported!"),
+            }
+            .into()),
+
+            Self::Repeat(node) => Err(LexicalError {
+                location: node.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("Loop return values are not currently supported!"),
+            }
+            .into()),
+
+            Self::Break(node) => Err(LexicalError {
+                location: node.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("Break statements cannot have a type!"),
+            }
+            .into()),
+
+            Self::Continue(node) => Err(LexicalError {
+                location: node.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("Continue statements cannot have a type!"),
+            }
+            .into()),
+
             Self::Return(ret) => Err(LexicalError {
                 location: ret.span.into_source_span(),
                 src: tree.src.clone().into(),
@@ -70,6 +126,16 @@ impl StatementNode {
 
 is_enum_variant_impl!(is_call -> StatementNode::Call);
 is_enum_variant_impl!(is_return -> StatementNode::Return);
+is_enum_variant_impl!(is_while -> StatementNode::While);
+is_enum_variant_impl!(is_for -> StatementNode::For);
+is_enum_variant_impl!(is_break -> StatementNode::Break);
+is_enum_variant_impl!(is_continue -> StatementNode::Continue);
+is_enum_variant_impl!(is_repeat -> StatementNode::Repeat);
 
 get_enum_variant_value_impl!(as_call -> StatementNode::Call: CallNode);
 get_enum_variant_value_impl!(as_return -> StatementNode::Return: ReturnNode);
+get_enum_variant_value_impl!(as_while -> StatementNode::While: WhileNode);
+get_enum_variant_value_impl!(as_for -> StatementNode::For: ForNode);
+get_enum_variant_value_impl!(as_break -> StatementNode::Break: BreakNode);
+get_enum_variant_value_impl!(as_continue -> StatementNode::Continue: ContinueNode);
+get_enum_variant_value_impl!(as_repeat -> StatementNode::Repeat: RepeatNode);
@@ -23,6 +23,15 @@ impl StaticSpan {
     pub fn as_str(&self) -> String {
         self.input[self.start..self.end].to_string()
     }
+
+    /// Get the (1-based) line and column where this span starts.
+    pub fn line_col(&self) -> (usize, usize) {
+        let before = &self.input[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let col = before.len() - before.rfind('\n').map(|v| v + 1).unwrap_or(0) + 1;
+
+        (line, col)
+    }
 }
 
 impl<'i> From<Span<'i>> for StaticSpan {
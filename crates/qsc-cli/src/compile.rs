@@ -10,7 +10,7 @@ use tempfile::NamedTempFile;
 use qsc_codegen::aot::AotGenerator;
 use qsc_linker::run_linker;
 
-use super::Command;
+use super::{report_warnings, Command};
 
 #[derive(Debug, Clone, Parser)]
 pub struct CompileCommand {
@@ -45,6 +45,10 @@ pub struct CompileCommand {
     #[arg(short = 'i', long = "clif")]
     pub clif: bool,
 
+    /// Build in release mode, without runtime checks.
+    #[arg(short = 'r', long = "release")]
+    pub release: bool,
+
     /// Instead of compiling, dump the Abstract Syntax Tree.
     #[arg(long = "dump-ast")]
     pub dump_ast: bool,
@@ -74,11 +78,8 @@ impl Command for CompileCommand {
             .map(|v| Triple::from_str(v.as_str()).unwrap())
             .unwrap_or(Triple::host());
 
-        let name = self.file.file_name().unwrap().to_str().unwrap();
-        let content = fs::read_to_string(self.file.clone()).into_diagnostic()?;
-
         if self.dump_ast {
-            let ast = Compiler::<AotGenerator>::dump_ast(name, &content)?;
+            let ast = Compiler::<AotGenerator>::dump_ast(&self.file)?;
             let mut file = self.file.clone();
 
             file.set_extension("ast.ron");
@@ -87,7 +88,7 @@ impl Command for CompileCommand {
         }
 
         if self.dump_pst {
-            let ast = Compiler::<AotGenerator>::dump_pst(name, &content)?;
+            let ast = Compiler::<AotGenerator>::dump_pst(&self.file)?;
             let mut file = self.file.clone();
 
             file.set_extension("pst.ron");
@@ -95,13 +96,15 @@ impl Command for CompileCommand {
             fs::write(file, ast).into_diagnostic()?;
         }
 
-        let compiler = Compiler::<AotGenerator>::compile(
-            name,
-            content,
+        let mut compiler = Compiler::<AotGenerator>::compile(
+            &self.file,
             triple.clone(),
             self.libraries.clone(),
+            self.release,
         )?;
 
+        report_warnings(std::mem::take(&mut compiler.warnings));
+
         debug!("Emitting object(s)...");
 
         if self.vcode {
@@ -154,6 +157,7 @@ impl Command for CompileCommand {
         }
 
         let tmp_file = NamedTempFile::new().into_diagnostic()?;
+        let libs = compiler.libs.clone();
         let obj = compiler.finalize();
         let data = obj.object.write().into_diagnostic()?;
 
@@ -200,7 +204,7 @@ impl Command for CompileCommand {
             tmp_file.path().into(),
             triple,
             self.extra_flags.clone(),
-            self.libraries.clone(),
+            libs,
         )?;
 
         Ok(())
@@ -16,8 +16,9 @@ use clap::{Parser, Subcommand};
 use clap_verbosity_flag::Verbosity;
 use const_format::formatcp;
 use log::LevelFilter;
+use miette::Report;
 use pretty_env_logger::formatted_builder;
-use qsc_core::error::Result;
+use qsc_core::error::{processor::ProcessorWarning, Result};
 
 use self::{
     compile::CompileCommand, completions::CompletionsCommand, run::RunCommand, style::get_styles,
@@ -41,6 +42,13 @@ pub trait Command {
     fn execute(&mut self) -> Result<()>;
 }
 
+/// Print the warnings found while compiling, the same way errors are shown.
+pub fn report_warnings(warnings: Vec<ProcessorWarning>) {
+    for warning in warnings {
+        eprintln!("{:?}", Report::new(warning));
+    }
+}
+
 #[derive(Debug, Clone, Parser)]
 #[command(
     name = env!("PRODUCT_NAME"),
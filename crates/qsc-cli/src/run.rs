@@ -8,13 +8,17 @@ use target_lexicon::Triple;
 
 use qsc_codegen::jit::JitGenerator;
 
-use super::Command;
+use super::{report_warnings, Command};
 
 #[derive(Debug, Clone, Parser)]
 pub struct RunCommand {
     /// The path to the file to compile.
     pub file: PathBuf,
 
+    /// Run in release mode, without runtime checks.
+    #[arg(short = 'r', long = "release")]
+    pub release: bool,
+
     /// Instead of running, dump the Abstract Syntax Tree.
     #[arg(long = "dump-ast")]
     pub dump_ast: bool,
@@ -42,11 +46,8 @@ pub struct RunCommand {
 
 impl Command for RunCommand {
     fn execute(&mut self) -> Result<()> {
-        let name = self.file.file_name().unwrap().to_str().unwrap();
-        let content = fs::read_to_string(self.file.clone()).into_diagnostic()?;
-
         if self.dump_ast {
-            let ast = Compiler::<JitGenerator>::dump_ast(name, &content)?;
+            let ast = Compiler::<JitGenerator>::dump_ast(&self.file)?;
             let mut file = self.file.clone();
 
             file.set_extension("ast.ron");
@@ -55,7 +56,7 @@ impl Command for RunCommand {
         }
 
         if self.dump_pst {
-            let ast = Compiler::<JitGenerator>::dump_pst(name, &content)?;
+            let ast = Compiler::<JitGenerator>::dump_pst(&self.file)?;
             let mut file = self.file.clone();
 
             file.set_extension("pst.ron");
@@ -63,13 +64,15 @@ impl Command for RunCommand {
             fs::write(file, ast).into_diagnostic()?;
         }
 
-        let compiler = Compiler::<JitGenerator>::compile(
-            name,
-            content,
+        let mut compiler = Compiler::<JitGenerator>::compile(
+            &self.file,
             Triple::host(),
             self.libraries.clone(),
+            self.release,
         )?;
 
+        report_warnings(std::mem::take(&mut compiler.warnings));
+
         if self.vcode {
             let mut file = self.file.clone();
 
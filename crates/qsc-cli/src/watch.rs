@@ -1,10 +1,4 @@
-use std::{
-    fs::{self, canonicalize},
-    path::PathBuf,
-    sync::mpsc::channel,
-    thread::sleep,
-    time::Duration,
-};
+use std::{fs::canonicalize, path::PathBuf, sync::mpsc::channel, thread::sleep, time::Duration};
 
 use clap::Parser;
 use miette::IntoDiagnostic;
@@ -15,7 +9,7 @@ use target_lexicon::Triple;
 
 use qsc_codegen::jit::JitGenerator;
 
-use super::Command;
+use super::{report_warnings, Command};
 
 #[derive(Debug, Clone, Parser)]
 pub struct WatchCommand {
@@ -30,15 +24,11 @@ pub struct WatchCommand {
 impl WatchCommand {
     pub fn run(&self) -> Result<()> {
         let path = self.path.clone().join("main.qs");
-        let name = path.file_name().unwrap().to_str().unwrap();
-        let content = fs::read_to_string(path.clone()).into_diagnostic()?;
-
-        let compiler = Compiler::<JitGenerator>::compile(
-            name,
-            content,
-            Triple::host(),
-            self.libraries.clone(),
-        )?;
+
+        let mut compiler =
+            Compiler::<JitGenerator>::compile(path, Triple::host(), self.libraries.clone(), false)?;
+
+        report_warnings(std::mem::take(&mut compiler.warnings));
 
         let code = compiler.run()?;
 
@@ -1,5 +1,5 @@
 use cranelift_codegen::{
-    ir::{AbiParam, Function},
+    ir::{AbiParam, ArgumentPurpose, Function},
     isa::lookup,
     settings::{self, Configurable, Flags},
     Context,
@@ -10,11 +10,15 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     context::{CodegenContext, CompilerContext},
-    generator::{unify::BackendInternal, vars::func::FunctionCompiler, Backend},
+    generator::{
+        tuples::TupleCompiler, unify::BackendInternal, vars::func::FunctionCompiler, Backend,
+    },
 };
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
-use cranelift_module::{default_libcall_names, DataDescription, DataId, Linkage, Module};
+use cranelift_module::{default_libcall_names, DataDescription, DataId, Module};
 use qsc_ast::ast::{decl::func::FunctionNode, AbstractTree};
+
+use crate::linkage::{function_linkage, symbol};
 use qsc_object::{ObjectBuilder, ObjectModule, ObjectProduct};
 use target_lexicon::Triple;
 
@@ -24,7 +28,13 @@ pub struct AotGenerator {
 }
 
 impl AotGenerator {
-    pub fn new(triple: Triple, name: String, source: String, tree: AbstractTree) -> Result<Self> {
+    pub fn new(
+        triple: Triple,
+        name: String,
+        source: String,
+        tree: AbstractTree,
+        release: bool,
+    ) -> Result<Self> {
         let mut flags = settings::builder();
 
         flags
@@ -58,11 +68,14 @@ impl AotGenerator {
             module,
             functions: HashMap::new(),
             globals: HashMap::new(),
+            fn_values: HashMap::new(),
+            thunks: HashMap::new(),
             code: Arc::new(RwLock::new(HashMap::new())),
             fns: Vec::new(),
             vcode: Vec::new(),
             source: NamedSource::new(name, source),
             tree,
+            debug: !release,
         };
 
         Ok(Self {
@@ -90,6 +103,19 @@ impl AotGenerator {
         }
 
         let ptr = self.ctx.read().module.isa().pointer_type();
+        let ret = func.ret.clone().map(|v| v.as_str()).unwrap_or_default();
+
+        // Structs, enums, arrays and bigger tuples get returned through a
+        // pointer to the caller's storage.
+        if self.ctx.read().tree.returns_by_pointer(&ret) {
+            self.ctx
+                .write()
+                .ctx
+                .func
+                .signature
+                .params
+                .push(AbiParam::special(ptr, ArgumentPurpose::StructReturn));
+        }
 
         for arg in func.args.clone() {
             self.ctx
@@ -104,19 +130,17 @@ impl AotGenerator {
                 )));
         }
 
-        self.ctx
-            .write()
-            .ctx
-            .func
-            .signature
-            .returns
-            .push(AbiParam::new(Self::query_type_with_pointer(
-                ptr,
-                func.ret
-                    .clone()
-                    .map(|v| v.as_str())
-                    .unwrap_or("void".to_string()),
-            )));
+        let returns = Self::return_params(
+            &self.ctx.read().tree,
+            ptr,
+            &func
+                .ret
+                .clone()
+                .map(|v| v.as_str())
+                .unwrap_or("void".to_string()),
+        );
+
+        self.ctx.write().ctx.func.signature.returns.extend(returns);
 
         Ok(())
     }
@@ -140,9 +164,14 @@ impl AotGenerator {
             builder: &builder,
             locals: HashMap::new(),
             vars: HashMap::new(),
+            addressed: Vec::new(),
+            slots: HashMap::new(),
             values: HashMap::new(),
+            loops: Vec::new(),
+            next_var: 0,
             ret: func.ret.clone(),
             func: func.clone(),
+            inlined: Vec::new(),
         };
 
         Self::compile_fn(&self.ctx, ctx, func)?;
@@ -159,7 +188,7 @@ impl AotGenerator {
             .ctx
             .write()
             .module
-            .declare_function(&func.name, Linkage::Export, &sig)
+            .declare_function(&symbol(&func), function_linkage(&func), &sig)
             .into_diagnostic()?;
 
         {
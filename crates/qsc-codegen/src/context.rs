@@ -5,11 +5,12 @@ use std::{
 };
 
 use cranelift_codegen::{
-    ir::{Function, Value},
+    entity::EntityRef,
+    ir::{Block, Function, StackSlot, Value},
     CompiledCode, Context,
 };
 use cranelift_frontend::{FunctionBuilder, Variable};
-use cranelift_module::{DataDescription, DataId, Module};
+use cranelift_module::{DataDescription, DataId, FuncId, Module};
 
 use miette::NamedSource;
 use parking_lot::RwLock;
@@ -18,19 +19,56 @@ use qsc_ast::ast::{decl::func::FunctionNode, node::ty::TypeNode, AbstractTree};
 pub struct CodegenContext<'a, 'b> {
     pub locals: HashMap<String, DataId>,
     pub vars: HashMap<String, (Variable, Option<TypeNode>)>,
+
+    /// Locals that have their address taken somewhere, which live in a
+    /// stack slot instead of a variable.
+    pub addressed: Vec<String>,
+    pub slots: HashMap<String, (StackSlot, Option<TypeNode>)>,
+
     pub values: HashMap<String, (Value, TypeNode)>,
+    pub loops: Vec<(Block, Block)>,
+    pub next_var: usize,
     pub builder: &'b RwLock<FunctionBuilder<'a>>,
     pub ret: Option<TypeNode>,
     pub func: FunctionNode,
+
+    /// The functions that calls are being inlined into, innermost last,
+    /// with the block the inlined function returns to.
+    pub inlined: Vec<(FunctionNode, Block)>,
+}
+
+impl<'a, 'b> CodegenContext<'a, 'b> {
+    pub fn new_var(&mut self) -> Variable {
+        let var = Variable::new(self.next_var);
+
+        self.next_var += 1;
+
+        var
+    }
+
+    /// The name the processor knows the current function by, since the
+    /// entry point is renamed to `_start` when compiling to an object.
+    pub fn fn_name(&self) -> Option<String> {
+        Some(if self.func.name == "_start" {
+            "main".to_string()
+        } else {
+            self.func.name.clone()
+        })
+    }
 }
 
 #[derive(Debug)]
 pub struct DebugCodegenContext {
     pub locals: HashMap<String, DataId>,
     pub vars: HashMap<String, (Variable, Option<TypeNode>)>,
+    pub addressed: Vec<String>,
+    pub slots: HashMap<String, (StackSlot, Option<TypeNode>)>,
     pub values: HashMap<String, (Value, TypeNode)>,
+    pub loops: Vec<(Block, Block)>,
+    pub next_var: usize,
     pub ret: Option<TypeNode>,
     pub func: FunctionNode,
+    pub inlined: Vec<(FunctionNode, Block)>,
 }
 
 impl<'a, 'b> Into<DebugCodegenContext> for &CodegenContext<'a, 'b> {
@@ -38,9 +76,14 @@ impl<'a, 'b> Into<DebugCodegenContext> for &CodegenContext<'a, 'b> {
         DebugCodegenContext {
             locals: self.locals.clone(),
             vars: self.vars.clone(),
+            addressed: self.addressed.clone(),
+            slots: self.slots.clone(),
             values: self.values.clone(),
+            loops: self.loops.clone(),
+            next_var: self.next_var,
             ret: self.ret.clone(),
             func: self.func.clone(),
+            inlined: self.inlined.clone(),
         }
     }
 }
@@ -59,9 +102,21 @@ pub struct CompilerContext<M: Module> {
     pub module: M,
     pub functions: HashMap<String, FunctionNode>,
     pub globals: HashMap<String, DataId>,
+
+    /// The records made for functions used as values, which hold a pointer
+    /// to their code just like the environment of a closure does.
+    pub fn_values: HashMap<String, DataId>,
+
+    /// The thunks variadic C functions are called through, by the name of
+    /// the function and the number of floats they pass.
+    pub thunks: HashMap<(String, u8), FuncId>,
+
     pub fns: Vec<Function>,
     pub vcode: Vec<CompiledCode>,
     pub code: Arc<RwLock<HashMap<String, (String, *const u8, usize)>>>,
     pub source: NamedSource<String>,
     pub tree: AbstractTree,
+
+    /// Whether to emit runtime checks, like array bounds checks.
+    pub debug: bool,
 }
@@ -0,0 +1,211 @@
+use cranelift_codegen::ir::{condcodes::IntCC, types, InstBuilder, SourceLoc, TrapCode, Value};
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::RwLock;
+use qsc_ast::{
+    ast::{
+        expr::{
+            array::{ArrayExpr, IndexExpr},
+            ExpressionNode,
+        },
+        literal::string::StringNode,
+        node::ty::TypeNode,
+    },
+    span::StaticSpan,
+};
+use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError};
+
+use crate::{
+    alias::DeclareAliasedFunction,
+    context::{CodegenContext, CompilerContext},
+};
+
+use super::{call::CallCompiler, literal::LiteralCompiler, structs::StructCompiler, Backend};
+
+/// Arrays are aggregates just like structs, with their elements stored
+/// back to back.
+pub trait ArrayCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn compile_array_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: ArrayExpr,
+    ) -> Result<Value>;
+
+    fn compile_index_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: IndexExpr,
+    ) -> Result<(Value, String)>;
+
+    /// Check that an index is below `len`, printing where the index came
+    /// from to stderr and trapping otherwise.
+    fn compile_bounds_check(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        index: Value,
+        len: usize,
+        span: StaticSpan,
+    ) -> Result<()>;
+
+    fn compile_index(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: IndexExpr,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> ArrayCompiler<'a, 'b, M>
+    for T
+{
+    fn compile_array_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: ArrayExpr,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let array = ExpressionNode::Array(expr.clone()).get_type(&fn_name, &cctx.read().tree)?;
+        let (elem, _) = TypeNode::array_parts(&array).unwrap();
+        let (size, _) = Self::type_layout(cctx, elem);
+        let ty = Self::query_type(cctx, elem.to_string());
+        let addr = Self::alloc_aggregate(cctx, ctx, &array);
+
+        for (idx, node) in expr.values.into_iter().enumerate() {
+            let value = Self::compile(cctx, ctx, node.clone())?;
+            let value = Self::convert_int(cctx, ctx, &node, value, ty)?;
+            let elem_addr = ctx
+                .builder
+                .write()
+                .ins()
+                .iadd_imm(addr, (idx as u32 * size) as i64);
+
+            Self::store_value(cctx, ctx, elem, value, elem_addr);
+        }
+
+        Ok(addr)
+    }
+
+    fn compile_index_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: IndexExpr,
+    ) -> Result<(Value, String)> {
+        let fn_name = ctx.fn_name();
+
+        let ty = expr.value.data.get_type(&fn_name, &cctx.read().tree)?;
+
+        let Some((elem, len)) = TypeNode::array_parts(&ty) else {
+            return Err(CodegenError {
+                error: miette!("Type {} cannot be indexed!", ty),
+                location: expr.value.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into());
+        };
+
+        let ptr = Self::ptr(cctx);
+        let (size, _) = Self::type_layout(cctx, elem);
+        let base = Self::compile(cctx, ctx, expr.value)?;
+        let index = Self::compile(cctx, ctx, expr.index.clone())?;
+
+        // Indices are treated as unsigned, so a negative one ends up huge
+        // and fails the bounds check like any other bad index.
+        let index = if ctx.builder.read().func.dfg.value_type(index).bits() < ptr.bits() {
+            ctx.builder.write().ins().uextend(ptr, index)
+        } else {
+            Self::convert_int(cctx, ctx, &expr.index, index, ptr)?
+        };
+
+        if cctx.read().debug {
+            Self::compile_bounds_check(cctx, ctx, index, len, expr.index.span)?;
+        }
+
+        let mut bctx = ctx.builder.write();
+        let offset = bctx.ins().imul_imm(index, size as i64);
+        let addr = bctx.ins().iadd(base, offset);
+
+        Ok((addr, elem.to_string()))
+    }
+
+    fn compile_bounds_check(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        index: Value,
+        len: usize,
+        span: StaticSpan,
+    ) -> Result<()> {
+        let ok_block = {
+            let mut bctx = ctx.builder.write();
+            let fail_block = bctx.create_block();
+            let ok_block = bctx.create_block();
+            let oob = bctx
+                .ins()
+                .icmp_imm(IntCC::UnsignedGreaterThanOrEqual, index, len as i64);
+
+            bctx.ins().brif(oob, fail_block, &[], ok_block, &[]);
+            bctx.set_cold_block(fail_block);
+            bctx.switch_to_block(fail_block);
+            bctx.seal_block(fail_block);
+
+            ok_block
+        };
+
+        let (line, col) = span.line_col();
+        let message = format!(
+            "{}:{}:{}: index out of bounds: the length is {}\n",
+            cctx.read().source.name(),
+            line,
+            col,
+            len
+        );
+
+        let ptr = Self::ptr(cctx);
+        let size = message.len();
+
+        let message = Self::compile_string(
+            cctx,
+            ctx,
+            StringNode {
+                span: span.clone(),
+                value: message,
+            },
+        )?;
+
+        // Anything printed so far has to be flushed before the trap kills
+        // the process, and the message goes straight to stderr.
+        let (null, fd, size) = {
+            let mut bctx = ctx.builder.write();
+
+            (
+                bctx.ins().iconst(ptr, 0),
+                bctx.ins().iconst(types::I32, 2),
+                bctx.ins().iconst(ptr, size as i64),
+            )
+        };
+
+        Self::compile_import_call(cctx, ctx, "fflush", &[null], types::I32)?;
+        Self::compile_import_call(cctx, ctx, "write", &[fd, message, size], types::I32)?;
+
+        let mut bctx = ctx.builder.write();
+
+        // The trap also carries the index's position, for debuggers.
+        bctx.set_srcloc(SourceLoc::new(span.start as u32));
+        bctx.ins().trap(TrapCode::HeapOutOfBounds);
+        bctx.set_srcloc(SourceLoc::default());
+
+        bctx.switch_to_block(ok_block);
+        bctx.seal_block(ok_block);
+
+        Ok(())
+    }
+
+    fn compile_index(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: IndexExpr,
+    ) -> Result<Value> {
+        let (addr, ty) = Self::compile_index_addr(cctx, ctx, expr)?;
+
+        Ok(Self::load_value(cctx, ctx, &ty, addr))
+    }
+}
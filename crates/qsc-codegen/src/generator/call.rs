@@ -1,14 +1,28 @@
-use super::Backend;
+use std::collections::HashMap;
+
+use super::{
+    closure::ClosureCompiler, inline::InlineCompiler, structs::StructCompiler,
+    tuples::TupleCompiler, Backend,
+};
 use crate::{
     alias::DeclareAliasedFunction,
     context::{CodegenContext, CompilerContext},
+    linkage::symbol,
+};
+use cranelift_codegen::{
+    binemit::Reloc,
+    ir::{types, AbiParam, ArgumentPurpose, Function, InstBuilder, Signature, Type, Value},
+    isa::CallConv,
+    FinalizedMachReloc, FinalizedRelocTarget,
 };
-use cranelift_codegen::ir::{AbiParam, Function, InstBuilder, Value};
-use cranelift_module::{Linkage, Module};
+use cranelift_module::{FuncId, Linkage, Module};
 use miette::{IntoDiagnostic, Result};
 use parking_lot::{RwLock, RwLockWriteGuard};
-use qsc_ast::ast::stmt::call::CallNode;
-use qsc_core::util::random_string;
+use qsc_ast::ast::{
+    node::{data::NodeData, sym::SymbolNode, ty::TypeNode},
+    stmt::call::{CallArgument, CallNode},
+};
+use qsc_core::util::{mangle, random_string};
 
 pub trait CallCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
     fn compile_call(
@@ -16,6 +30,16 @@ pub trait CallCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
         ctx: &mut CodegenContext<'a, 'b>,
         call: CallNode,
     ) -> Result<Value>;
+
+    /// Call an imported C function that isn't declared in the source,
+    /// with a signature made from the types of the given arguments.
+    fn compile_import_call(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        func: &str,
+        args: &[Value],
+        ret: Type,
+    ) -> Result<Value>;
 }
 
 impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> CallCompiler<'a, 'b, M>
@@ -28,10 +52,37 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> CallComp
     ) -> Result<Value> {
         debug!("Trying to compile call: {:?}", call);
 
+        let fn_name = ctx.fn_name();
+
+        // Calls through a variable holding a function.
+        if ctx.vars.contains_key(&call.func)
+            || ctx.slots.contains_key(&call.func)
+            || cctx.read().tree.globals().contains_key(&call.func)
+        {
+            let ty = NodeData::Symbol(SymbolNode {
+                span: call.span.clone(),
+                value: call.func.clone(),
+            })
+            .get_type(&fn_name, &cctx.read().tree)?;
+
+            return Self::compile_indirect_call(cctx, ctx, call, &ty);
+        }
+
+        if let Some(func) = Self::inline_target(cctx, ctx, &call.func) {
+            return Self::compile_inline_call(cctx, ctx, call, func);
+        }
+
         let ptr = Self::ptr(cctx);
         let mut wctx = cctx.write();
         let mut sig = wctx.module.make_signature();
-        let mut func_name = call.func.to_string();
+        // Functions of the program can pick their own symbol with attributes.
+        let symbol = match wctx.tree.functions().get(&call.func) {
+            Some(func) => symbol(func),
+            None => mangle(&call.func),
+        };
+
+        let mut func_name = symbol.clone();
+        let ret_ty;
 
         if wctx.functions.contains_key(&call.func) {
             let func = wctx.functions.get(&call.func).unwrap();
@@ -61,46 +112,85 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> CallComp
                     .collect(),
             );
 
+            ret_ty = func
+                .ret
+                .clone()
+                .map(|v| v.as_str())
+                .unwrap_or(String::new());
+
             sig.returns
-                .push(AbiParam::new(Self::query_type_with_pointer(
-                    ptr,
-                    func.ret
-                        .clone()
-                        .map(|v| v.as_str())
-                        .unwrap_or(String::new()),
-                )));
+                .extend(Self::return_params(&wctx.tree, ptr, &ret_ty));
         } else {
-            let args = call
-                .args
-                .iter()
-                .map(|arg| {
-                    if let Ok(ident) = arg.value.data.as_symbol() {
-                        if ctx.vars.contains_key(&ident.value) {
-                            return ctx
-                                .vars
-                                .get(&ident.value)
-                                .unwrap()
-                                .1
-                                .clone()
-                                .map(|v| v.as_str())
-                                .unwrap_or("i32".to_string());
-                        }
+            let arg_type = |arg: &CallArgument| {
+                if let Ok(ident) = arg.value.data.as_symbol() {
+                    if ctx.vars.contains_key(&ident.value) {
+                        return ctx
+                            .vars
+                            .get(&ident.value)
+                            .unwrap()
+                            .1
+                            .clone()
+                            .map(|v| v.as_str())
+                            .unwrap_or("i32".to_string());
                     }
+                }
+
+                arg.value
+                    .data
+                    .get_type(&fn_name, &wctx.tree)
+                    .unwrap_or("ptr".to_string())
+            };
+
+            let externs = wctx.tree.externs();
+            let functions = wctx.tree.functions();
+
+            let (args, ret) = match externs.get(&call.func) {
+                // Arguments past the declared ones get C's default promotions.
+                Some(func) => (
+                    func.args
+                        .iter()
+                        .map(|p| p.type_.as_str())
+                        .chain(
+                            call.args
+                                .iter()
+                                .skip(func.args.len())
+                                .map(|arg| promote(arg_type(arg))),
+                        )
+                        .collect::<Vec<String>>(),
+                    func.ret.clone().map(|v| v.as_str()).unwrap_or_default(),
+                ),
 
-                    arg.value
-                        .data
-                        .get_type(&Some(ctx.func.name.to_string()), &wctx.tree)
-                        .unwrap_or("ptr".to_string())
-                })
-                .collect::<Vec<String>>();
+                // Functions that haven't been compiled yet.
+                None if functions.contains_key(&call.func) => {
+                    let func = &functions[&call.func];
 
-            func_name = format!("__qsc::alias::{}_{}", call.func, random_string(8));
+                    (
+                        func.args
+                            .iter()
+                            .map(|p| p.type_.as_str())
+                            .collect::<Vec<String>>(),
+                        func.ret.clone().map(|v| v.as_str()).unwrap_or_default(),
+                    )
+                }
+
+                None => (
+                    call.args.iter().map(arg_type).collect::<Vec<String>>(),
+                    "i32".to_string(),
+                ),
+            };
+
+            // Functions of the program keep their own name, so the call gets
+            // linked to them once they are defined.
+            if !functions.contains_key(&call.func) {
+                func_name = format!("__qsc::alias::{}_{}", call.func, random_string(8));
+            }
 
             debug!(
-                "Using imported function for call (name = {}, Linkage::Import): {}({}) -> i32",
+                "Using imported function for call (name = {}, Linkage::Import): {}({}) -> {}",
                 func_name,
                 call.func,
-                args.join(", ")
+                args.join(", "),
+                ret
             );
 
             sig.params.append(
@@ -111,32 +201,224 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> CallComp
             );
 
             sig.returns
-                .push(AbiParam::new(Self::query_type_with_pointer(
-                    ptr,
-                    "i32".to_string(),
-                )));
+                .extend(Self::return_params(&wctx.tree, ptr, &ret));
+
+            ret_ty = ret;
+        }
+
+        // Functions returning an aggregate take a pointer to the storage for
+        // it as a hidden first parameter, and hand it back as the result.
+        let sret = wctx
+            .tree
+            .functions()
+            .get(&call.func)
+            .and_then(|func| func.ret.clone())
+            .map(|ty| ty.as_str())
+            .filter(|ty| wctx.tree.returns_by_pointer(ty));
+
+        // Small tuples come back as one value for each element instead.
+        let split = wctx.tree.split_tuple(&ret_ty).map(|_| ret_ty.clone());
+
+        if sret.is_some() {
+            sig.params
+                .insert(0, AbiParam::special(ptr, ArgumentPurpose::StructReturn));
+            sig.returns = vec![AbiParam::new(ptr)];
         }
 
         debug!("Emitting call instruction...");
 
         let callee = wctx
             .module
-            .declare_aliased_function(&func_name, &call.func, Linkage::Import, &sig)
+            .declare_aliased_function(&func_name, &symbol, Linkage::Import, &sig)
             .into_diagnostic()?;
 
+        let variadic = wctx
+            .tree
+            .externs()
+            .get(&call.func)
+            .is_some_and(|func| func.variadic);
+
+        let callee = if variadic {
+            let CompilerContext { module, thunks, .. } = &mut *wctx;
+
+            variadic_thunk(module, thunks, &call.func, callee, &sig)?
+        } else {
+            callee
+        };
+
         let func_ref = unsafe { ((&mut wctx.ctx.func) as *mut Function).as_mut() }.unwrap();
         let local_callee = wctx.module.declare_func_in_func(callee, func_ref);
+
+        // Thunks are shared by calls passing different arguments, so each
+        // call uses its own signature.
+        if variadic {
+            func_ref.dfg.ext_funcs[local_callee].signature = func_ref.import_signature(sig.clone());
+        }
         let mut args = Vec::new();
 
+        // C functions take a plain pointer to the code of a function.
+        let code_params = wctx
+            .tree
+            .externs()
+            .get(&call.func)
+            .map(|func| {
+                func.args
+                    .iter()
+                    .map(|arg| TypeNode::function_parts(&arg.type_.as_str()).is_some())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
         RwLockWriteGuard::unlock_fair(wctx);
 
-        for arg in call.args {
-            args.push(Self::compile(cctx, ctx, arg.value)?);
+        if let Some(ty) = &sret {
+            args.push(Self::alloc_aggregate(cctx, ctx, ty));
+        }
+
+        for (idx, (arg, param)) in call
+            .args
+            .into_iter()
+            .zip(sig.params.into_iter().skip(args.len()))
+            .enumerate()
+        {
+            let mut value = Self::compile_owned(cctx, ctx, arg.value.clone())?;
+
+            if code_params.get(idx) == Some(&true) {
+                value = Self::compile_code_pointer(cctx, ctx, value);
+            }
+
+            if ctx.builder.read().func.dfg.value_type(value) == types::F32
+                && param.value_type == types::F64
+            {
+                value = ctx.builder.write().ins().fpromote(types::F64, value);
+            }
+
+            args.push(Self::convert_int(
+                cctx,
+                ctx,
+                &arg.value,
+                value,
+                param.value_type,
+            )?);
         }
 
         let call = ctx.builder.write().ins().call(local_callee, &args);
-        let result = ctx.builder.write().inst_results(call)[0];
+        let results = ctx.builder.write().inst_results(call).to_vec();
+
+        if let Some(ty) = split {
+            return Ok(Self::join_tuple_values(cctx, ctx, &ty, &results));
+        }
+
+        Ok(results[0])
+    }
+
+    fn compile_import_call(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        func: &str,
+        args: &[Value],
+        ret: Type,
+    ) -> Result<Value> {
+        let mut wctx = cctx.write();
+        let mut sig = wctx.module.make_signature();
+
+        for arg in args {
+            let ty = ctx.builder.read().func.dfg.value_type(*arg);
+
+            sig.params.push(AbiParam::new(ty));
+        }
+
+        sig.returns.push(AbiParam::new(ret));
+
+        let func_name = format!("__qsc::alias::{}_{}", func, random_string(8));
+
+        let callee = wctx
+            .module
+            .declare_aliased_function(&func_name, func, Linkage::Import, &sig)
+            .into_diagnostic()?;
+
+        let mut bctx = ctx.builder.write();
+        let local_callee = wctx.module.declare_func_in_func(callee, bctx.func);
+        let call = bctx.ins().call(local_callee, args);
+
+        Ok(bctx.inst_results(call)[0])
+    }
+}
+
+/// Variadic C functions on x86-64 expect `%al` to hold an upper bound on
+/// the number of vector registers the arguments are passed in, which
+/// Cranelift has no way to set. They get called through a thunk that sets
+/// it and then jumps to the real function instead.
+fn variadic_thunk<M: Module>(
+    module: &mut M,
+    thunks: &mut HashMap<(String, u8), FuncId>,
+    name: &str,
+    callee: FuncId,
+    sig: &Signature,
+) -> Result<FuncId> {
+    if module.isa().name() != "x64" || sig.call_conv != CallConv::SystemV {
+        return Ok(callee);
+    }
+
+    let floats = sig
+        .params
+        .iter()
+        .filter(|param| param.value_type.is_float())
+        .count()
+        .min(8) as u8;
+
+    if let Some(thunk) = thunks.get(&(name.to_string(), floats)) {
+        return Ok(*thunk);
+    }
+
+    // mov al, floats
+    let mut bytes = vec![0xb0, floats];
+
+    let (kind, addend) = if module.isa().flags().is_pic() {
+        // jmp [rip + callee@GOTPCREL]
+        bytes.extend([0xff, 0x25, 0, 0, 0, 0]);
+
+        (Reloc::X86GOTPCRel4, -4)
+    } else {
+        // movabs r11, callee; jmp r11
+        bytes.extend([0x49, 0xbb, 0, 0, 0, 0, 0, 0, 0, 0, 0x41, 0xff, 0xe3]);
+
+        (Reloc::Abs8, 0)
+    };
+
+    let thunk = module
+        .declare_function(
+            &format!("__qsc::varargs::{}_{}", name, floats),
+            Linkage::Local,
+            sig,
+        )
+        .into_diagnostic()?;
+
+    let mut func = Function::new();
+    let callee = module.declare_func_in_func(callee, &mut func);
+    let reloc = FinalizedMachReloc {
+        offset: 4,
+        kind,
+        target: FinalizedRelocTarget::ExternalName(func.dfg.ext_funcs[callee].name.clone()),
+        addend,
+    };
+
+    module
+        .define_function_bytes(thunk, &func, 16, &bytes, &[reloc])
+        .into_diagnostic()?;
+
+    thunks.insert((name.to_string(), floats), thunk);
+
+    Ok(thunk)
+}
 
-        Ok(result)
+/// C's default argument promotions, which apply to the arguments passed
+/// to the variadic part of an extern.
+fn promote(ty: String) -> String {
+    match ty.as_str() {
+        "f32" => "f64".to_string(),
+        "i8" | "i16" | "bool" => "i32".to_string(),
+        "u8" | "u16" => "u32".to_string(),
+        _ => ty,
     }
 }
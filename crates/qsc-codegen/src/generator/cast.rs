@@ -0,0 +1,88 @@
+use cranelift_codegen::ir::{types, InstBuilder, Value};
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::RwLock;
+use qsc_ast::ast::expr::cast::CastExpr;
+
+use crate::context::{CodegenContext, CompilerContext};
+
+use super::Backend;
+
+pub trait CastCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn compile_cast(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: CastExpr,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> CastCompiler<'a, 'b, M> for T {
+    fn compile_cast(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: CastExpr,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let target = expr.type_.as_str();
+        let to = Self::query_type(cctx, target.clone());
+        let value = Self::compile(cctx, ctx, expr.value.clone())?;
+        let from = ctx.builder.read().func.dfg.value_type(value);
+
+        // The source type decides how integers get extended, and the
+        // target type decides which float conversion is used.
+        let signed = expr.value.data.is_signed(&fn_name, &cctx.read().tree)?;
+        let unsigned_target = ["u8", "u16", "u32", "u64"].contains(&target.as_str());
+        let mut bctx = ctx.builder.write();
+
+        Ok(if from == to {
+            value
+        } else if from.is_int() && to.is_int() {
+            // Pointers are integers too, so this covers casting between
+            // them and plain integers.
+            if from.bits() > to.bits() {
+                bctx.ins().ireduce(to, value)
+            } else if signed {
+                bctx.ins().sextend(to, value)
+            } else {
+                bctx.ins().uextend(to, value)
+            }
+        } else if from.is_int() && to.is_float() {
+            if signed {
+                bctx.ins().fcvt_from_sint(to, value)
+            } else {
+                bctx.ins().fcvt_from_uint(to, value)
+            }
+        } else if from.is_float() && to.is_int() && to.bits() < 32 {
+            // Conversions only produce 32 or 64 bit integers, so narrower
+            // ones are clamped to the target's range and then truncated.
+            let bits = to.bits() as i64;
+
+            let value = if unsigned_target {
+                let value = bctx.ins().fcvt_to_uint_sat(types::I32, value);
+                let max = bctx.ins().iconst(types::I32, (1 << bits) - 1);
+
+                bctx.ins().umin(value, max)
+            } else {
+                let value = bctx.ins().fcvt_to_sint_sat(types::I32, value);
+                let min = bctx.ins().iconst(types::I32, -(1 << (bits - 1)));
+                let max = bctx.ins().iconst(types::I32, (1 << (bits - 1)) - 1);
+                let value = bctx.ins().smax(value, min);
+
+                bctx.ins().smin(value, max)
+            };
+
+            bctx.ins().ireduce(to, value)
+        } else if from.is_float() && to.is_int() {
+            if unsigned_target {
+                bctx.ins().fcvt_to_uint_sat(to, value)
+            } else {
+                bctx.ins().fcvt_to_sint_sat(to, value)
+            }
+        } else if from.bits() < to.bits() {
+            bctx.ins().fpromote(to, value)
+        } else {
+            bctx.ins().fdemote(to, value)
+        })
+    }
+}
@@ -0,0 +1,334 @@
+use cranelift_codegen::ir::{
+    types, AbiParam, ArgumentPurpose, Function, InstBuilder, MemFlags, Value,
+};
+use cranelift_module::{FuncId, Linkage, Module};
+use miette::{IntoDiagnostic, Result};
+use parking_lot::{RwLock, RwLockWriteGuard};
+use qsc_ast::ast::{
+    expr::closure::ClosureExpr,
+    node::{data::NodeData, sym::SymbolNode, ty::TypeNode, Node},
+    stmt::call::CallNode,
+};
+use qsc_core::util::mangle;
+
+use crate::{
+    alias::DeclareAliasedFunction,
+    context::{CodegenContext, CompilerContext},
+    linkage::{function_linkage, symbol},
+};
+
+use super::{
+    call::CallCompiler,
+    structs::StructCompiler,
+    tuples::TupleCompiler,
+    vars::{global::GlobalVariableCompiler, var::VariableCompiler},
+    Backend,
+};
+
+/// Functions are passed around as a pointer to a record that starts with
+/// the address of their code. For closures, the record is the environment
+/// holding the captured values, so calls through a function value pass it
+/// along as a hidden last argument. Plain functions just ignore it.
+///
+/// C functions that take a function pointer only get the address of the
+/// code, without the record. The processor makes sure those are only given
+/// functions and closures that capture nothing, which never read it.
+pub trait ClosureCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    /// Whether `name` refers to a function or an extern rather than a
+    /// variable, when used as a value.
+    fn is_function_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &CodegenContext<'a, 'b>,
+        name: &str,
+    ) -> bool;
+
+    /// Declare a function or an extern with the same signature it's
+    /// defined with, so its address can be taken.
+    fn declare_function_value(cctx: &RwLock<CompilerContext<M>>, name: &str) -> Result<FuncId>;
+
+    /// Get the record for a named function, creating it the first time the
+    /// function is used as a value.
+    fn compile_function_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        name: &str,
+    ) -> Result<Value>;
+
+    /// Build the environment of a closure on the heap, so the closure can
+    /// outlive the function that made it.
+    fn compile_closure(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: ClosureExpr,
+    ) -> Result<Value>;
+
+    /// Call the function a variable holds, whose type is `ty`.
+    fn compile_indirect_call(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        call: CallNode,
+        ty: &str,
+    ) -> Result<Value>;
+
+    /// Get the address of the code of a function value, for C functions
+    /// that take a plain function pointer. The record itself is dropped, so
+    /// this is only sound for values that don't need their environment.
+    fn compile_code_pointer(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        value: Value,
+    ) -> Value;
+}
+
+impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> ClosureCompiler<'a, 'b, M>
+    for T
+{
+    fn is_function_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &CodegenContext<'a, 'b>,
+        name: &str,
+    ) -> bool {
+        let tree = &cctx.read().tree;
+
+        let shadowed = ctx.vars.contains_key(name)
+            || ctx.slots.contains_key(name)
+            || ctx.values.contains_key(name)
+            || tree.globals().contains_key(name);
+
+        !shadowed && tree.function_type(name).is_some()
+    }
+
+    fn declare_function_value(cctx: &RwLock<CompilerContext<M>>, name: &str) -> Result<FuncId> {
+        let ptr = Self::ptr(cctx);
+        let tree = cctx.read().tree.clone();
+        let mut wctx = cctx.write();
+        let mut sig = wctx.module.make_signature();
+
+        let (args, ret, symbol, linkage) = match tree.functions().get(name) {
+            Some(func) => (
+                func.args.clone(),
+                func.ret.clone(),
+                symbol(func),
+                function_linkage(func),
+            ),
+
+            None => {
+                let func = tree.externs()[name].clone();
+
+                (func.args, func.ret, mangle(name), Linkage::Import)
+            }
+        };
+
+        let ret = ret.map(|v| v.as_str()).unwrap_or_default();
+
+        if tree.returns_by_pointer(&ret) {
+            sig.params
+                .push(AbiParam::special(ptr, ArgumentPurpose::StructReturn));
+        }
+
+        for arg in args {
+            sig.params.push(AbiParam::new(Self::query_type_with_pointer(
+                ptr,
+                arg.type_.as_str(),
+            )));
+        }
+
+        sig.returns.extend(Self::return_params(&tree, ptr, &ret));
+
+        wctx.module
+            .declare_function(&symbol, linkage, &sig)
+            .into_diagnostic()
+    }
+
+    fn compile_function_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        name: &str,
+    ) -> Result<Value> {
+        let existing = cctx.read().fn_values.get(name).copied();
+
+        let id = match existing {
+            Some(id) => id,
+
+            None => {
+                let ptr = Self::ptr(cctx);
+                let func = Self::declare_function_value(cctx, name)?;
+                let mut wctx = cctx.write();
+
+                wctx.data_desc
+                    .define(vec![0; ptr.bytes() as usize].into_boxed_slice());
+
+                let mut ddesc = wctx.data_desc.clone();
+                let func_ref = wctx.module.declare_func_in_data(func, &mut ddesc);
+
+                ddesc.write_function_addr(0, func_ref);
+                ddesc.set_align(ptr.bytes() as u64);
+
+                let id = wctx
+                    .module
+                    .declare_data(
+                        &format!("{}.fn", mangle(name)),
+                        Linkage::Local,
+                        false,
+                        false,
+                    )
+                    .into_diagnostic()?;
+
+                wctx.module.define_data(id, &ddesc).into_diagnostic()?;
+                ddesc.clear();
+                wctx.data_desc = ddesc;
+                wctx.fn_values.insert(name.to_string(), id);
+
+                RwLockWriteGuard::unlock_fair(wctx);
+                Self::post_define(cctx, id)?;
+
+                id
+            }
+        };
+
+        Ok(Self::compile_global_addr(cctx, ctx, id))
+    }
+
+    fn compile_closure(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: ClosureExpr,
+    ) -> Result<Value> {
+        let ptr = Self::ptr(cctx);
+        let layout = Self::struct_layout(cctx, &expr.env());
+        let size = ctx.builder.write().ins().iconst(ptr, layout.size as i64);
+        let env = Self::compile_import_call(cctx, ctx, "malloc", &[size], ptr)?;
+        let func = Self::declare_function_value(cctx, &expr.func)?;
+
+        let code = {
+            let mut wctx = cctx.write();
+            let func_ref = unsafe { ((&mut wctx.ctx.func) as *mut Function).as_mut() }.unwrap();
+            let local_func = wctx.module.declare_func_in_func(func, func_ref);
+
+            ctx.builder.write().ins().func_addr(ptr, local_func)
+        };
+
+        ctx.builder
+            .write()
+            .ins()
+            .store(MemFlags::trusted(), code, env, 0);
+
+        for field in layout.fields.iter().skip(1) {
+            let value = Self::compile_named_var(
+                cctx,
+                ctx,
+                SymbolNode {
+                    span: expr.span.clone(),
+                    value: field.name.clone(),
+                },
+            )?;
+
+            let addr = ctx.builder.write().ins().iadd_imm(env, field.offset as i64);
+
+            Self::store_value(cctx, ctx, &field.type_, value, addr);
+        }
+
+        Ok(env)
+    }
+
+    fn compile_indirect_call(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        call: CallNode,
+        ty: &str,
+    ) -> Result<Value> {
+        let ptr = Self::ptr(cctx);
+        let (params, ret) = TypeNode::function_parts(ty).unwrap();
+        let ret = ret.unwrap_or_default();
+        let sret = cctx.read().tree.returns_by_pointer(&ret);
+        let split = cctx.read().tree.split_tuple(&ret).is_some();
+
+        let record = Self::compile(
+            cctx,
+            ctx,
+            Node {
+                span: call.span.clone(),
+                data: Box::new(NodeData::Symbol(SymbolNode {
+                    span: call.span.clone(),
+                    value: call.func.clone(),
+                })),
+            },
+        )?;
+
+        let code = Self::compile_code_pointer(cctx, ctx, record);
+        let mut sig = cctx.read().module.make_signature();
+
+        if sret {
+            sig.params
+                .push(AbiParam::special(ptr, ArgumentPurpose::StructReturn));
+        }
+
+        for param in &params {
+            sig.params.push(AbiParam::new(Self::query_type_with_pointer(
+                ptr,
+                param.clone(),
+            )));
+        }
+
+        sig.params.push(AbiParam::new(ptr));
+        sig.returns
+            .extend(Self::return_params(&cctx.read().tree, ptr, &ret));
+
+        let mut args = Vec::new();
+
+        if sret {
+            args.push(Self::alloc_aggregate(cctx, ctx, &ret));
+        }
+
+        for (arg, param) in call
+            .args
+            .into_iter()
+            .zip(sig.params.iter().skip(args.len()))
+        {
+            let mut value = Self::compile_owned(cctx, ctx, arg.value.clone())?;
+
+            if ctx.builder.read().func.dfg.value_type(value) == types::F32
+                && param.value_type == types::F64
+            {
+                value = ctx.builder.write().ins().fpromote(types::F64, value);
+            }
+
+            args.push(Self::convert_int(
+                cctx,
+                ctx,
+                &arg.value,
+                value,
+                param.value_type,
+            )?);
+        }
+
+        args.push(record);
+
+        let results = {
+            let mut bctx = ctx.builder.write();
+            let sig_ref = bctx.import_signature(sig);
+            let call = bctx.ins().call_indirect(sig_ref, code, &args);
+
+            bctx.inst_results(call).to_vec()
+        };
+
+        if split {
+            return Ok(Self::join_tuple_values(cctx, ctx, &ret, &results));
+        }
+
+        Ok(results[0])
+    }
+
+    fn compile_code_pointer(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        value: Value,
+    ) -> Value {
+        let ptr = Self::ptr(cctx);
+
+        ctx.builder
+            .write()
+            .ins()
+            .load(ptr, MemFlags::trusted(), value, 0)
+    }
+}
@@ -41,18 +41,17 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> ConditionalCompiler<'a, 'b, M> fo
         RwLockWriteGuard::unlock_fair(builder);
 
         for node in &cond.block.data {
+            if Self::is_filled(ctx) {
+                break;
+            }
+
             Self::compile(cctx, ctx, node.clone())?;
         }
 
+        let filled = Self::is_filled(ctx);
         let mut builder = ctx.builder.write();
 
-        if !cond
-            .block
-            .data
-            .last()
-            .map(|v| v.data.as_stmt().map(|v| v.is_return()).unwrap_or(false))
-            .unwrap_or(false)
-        {
+        if !filled {
             builder.ins().jump(merge, &[then_ret]);
         }
 
@@ -65,13 +64,21 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> ConditionalCompiler<'a, 'b, M> fo
 
         if let Some(else_block) = cond.else_block {
             for node in &else_block.data {
+                if Self::is_filled(ctx) {
+                    break;
+                }
+
                 Self::compile(cctx, ctx, node.clone())?;
             }
         }
 
+        let filled = Self::is_filled(ctx);
         let mut builder = ctx.builder.write();
 
-        builder.ins().jump(merge, &[else_ret]);
+        if !filled {
+            builder.ins().jump(merge, &[else_ret]);
+        }
+
         builder.switch_to_block(merge);
         builder.seal_block(merge);
 
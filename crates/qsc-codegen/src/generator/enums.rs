@@ -0,0 +1,327 @@
+use cranelift_codegen::ir::{types, InstBuilder, MemFlags, TrapCode, Value};
+use cranelift_frontend::Switch;
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::{RwLock, RwLockWriteGuard};
+use qsc_ast::ast::{
+    expr::{
+        enumeration::{EnumExpr, MatchExpr, Pattern, VariantFieldExpr},
+        ExpressionNode,
+    },
+    node::{data::NodeData, ty::TypeNode},
+};
+
+use crate::context::{CodegenContext, CompilerContext};
+
+use super::{
+    structs::{StructCompiler, StructLayout},
+    Backend,
+};
+
+/// The size of an enum's tag, which comes before the fields of its variant.
+pub const TAG_SIZE: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumLayout {
+    pub size: u32,
+    pub align: u32,
+    pub variants: Vec<StructLayout>,
+}
+
+/// Enums are laid out as a tag (the index of the variant) followed by the
+/// fields of the active variant, so they're only as big as their largest
+/// variant.
+pub trait EnumCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn enum_layout(cctx: &RwLock<CompilerContext<M>>, name: &str) -> EnumLayout;
+
+    fn compile_enum_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: EnumExpr,
+    ) -> Result<Value>;
+
+    fn compile_variant_field_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        base: Value,
+        name: &str,
+        variant: &str,
+        index: usize,
+    ) -> (Value, String);
+
+    fn compile_variant_field(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: VariantFieldExpr,
+    ) -> Result<Value>;
+
+    fn compile_match(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: MatchExpr,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> EnumCompiler<'a, 'b, M> for T {
+    fn enum_layout(cctx: &RwLock<CompilerContext<M>>, name: &str) -> EnumLayout {
+        let item = cctx.read().tree.enums()[name].clone();
+        let mut variants = Vec::new();
+        let mut size = TAG_SIZE;
+        let mut align = TAG_SIZE;
+
+        for variant in item.variants {
+            let fields = variant
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| (idx.to_string(), field.as_str()))
+                .collect();
+
+            let layout = Self::fields_layout(cctx, fields, TAG_SIZE);
+
+            size = size.max(layout.size);
+            align = align.max(layout.align);
+            variants.push(layout);
+        }
+
+        EnumLayout {
+            size: size.next_multiple_of(align),
+            align,
+            variants,
+        }
+    }
+
+    fn compile_enum_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: EnumExpr,
+    ) -> Result<Value> {
+        let item = cctx.read().tree.enums()[&expr.name].clone();
+        let (tag, _) = item.variant(&expr.variant).unwrap();
+        let addr = Self::alloc_aggregate(cctx, ctx, &expr.name);
+
+        {
+            let mut bctx = ctx.builder.write();
+            let tag = bctx.ins().iconst(types::I32, tag as i64);
+
+            bctx.ins().store(MemFlags::trusted(), tag, addr, 0);
+        }
+
+        for (index, node) in expr.values.into_iter().enumerate() {
+            let (field_addr, type_) =
+                Self::compile_variant_field_addr(cctx, ctx, addr, &expr.name, &expr.variant, index);
+
+            let ty = Self::query_type(cctx, type_.clone());
+            let value = Self::compile(cctx, ctx, node.clone())?;
+            let value = Self::convert_int(cctx, ctx, &node, value, ty)?;
+
+            Self::store_value(cctx, ctx, &type_, value, field_addr);
+        }
+
+        Ok(addr)
+    }
+
+    fn compile_variant_field_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        base: Value,
+        name: &str,
+        variant: &str,
+        index: usize,
+    ) -> (Value, String) {
+        let (tag, _) = cctx.read().tree.enums()[name]
+            .variant(variant)
+            .map(|(tag, v)| (tag, v.clone()))
+            .unwrap();
+
+        let layout = Self::enum_layout(cctx, name);
+        let field = &layout.variants[tag].fields[index];
+        let addr = ctx
+            .builder
+            .write()
+            .ins()
+            .iadd_imm(base, field.offset as i64);
+
+        (addr, field.type_.clone())
+    }
+
+    fn compile_variant_field(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: VariantFieldExpr,
+    ) -> Result<Value> {
+        let base = Self::compile(cctx, ctx, expr.value)?;
+
+        let (addr, ty) = Self::compile_variant_field_addr(
+            cctx,
+            ctx,
+            base,
+            &expr.name,
+            &expr.variant,
+            expr.index,
+        );
+
+        Ok(Self::load_value(cctx, ctx, &ty, addr))
+    }
+
+    fn compile_match(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: MatchExpr,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let tree = cctx.read().tree.clone();
+        let name = expr.value.data.get_type(&fn_name, &tree)?;
+        let item = tree.enums()[&name].clone();
+
+        // Matches that are only used for their side effects don't have
+        // to produce a value.
+        let ty = ExpressionNode::Match(expr.clone())
+            .get_type(&fn_name, &tree)
+            .ok()
+            .filter(|ty| ty != "void")
+            .map(|ty| Self::query_type(cctx, ty));
+
+        // Anything after a wildcard can never be reached.
+        let arms = match expr
+            .arms
+            .iter()
+            .position(|arm| matches!(arm.pattern, Pattern::Wildcard(_)))
+        {
+            Some(idx) => expr.arms[..=idx].to_vec(),
+            None => expr.arms.clone(),
+        };
+
+        let value = Self::compile(cctx, ctx, expr.value)?;
+        let mut bctx = ctx.builder.write();
+        let tag = bctx.ins().load(types::I32, MemFlags::trusted(), value, 0);
+        let merge = bctx.create_block();
+
+        if let Some(ty) = ty {
+            bctx.append_block_param(merge, ty);
+        }
+
+        let blocks = arms.iter().map(|_| bctx.create_block()).collect::<Vec<_>>();
+        let mut switch = Switch::new();
+        let mut default = None;
+
+        for (arm, block) in arms.iter().zip(&blocks) {
+            match &arm.pattern {
+                Pattern::Variant(pattern) => {
+                    let (tag, _) = item.variant(&pattern.variant).unwrap();
+
+                    switch.set_entry(tag as u128, *block);
+                }
+
+                Pattern::Wildcard(_) => default = Some(*block),
+            }
+        }
+
+        // Without a wildcard, the processor has already made sure that
+        // every variant is covered.
+        let unreachable = bctx.create_block();
+
+        switch.emit(&mut bctx, tag, default.unwrap_or(unreachable));
+
+        bctx.switch_to_block(unreachable);
+        bctx.seal_block(unreachable);
+        bctx.ins().trap(TrapCode::UnreachableCodeReached);
+
+        RwLockWriteGuard::unlock_fair(bctx);
+
+        for (arm, block) in arms.into_iter().zip(blocks) {
+            {
+                let mut bctx = ctx.builder.write();
+
+                bctx.switch_to_block(block);
+                bctx.seal_block(block);
+            }
+
+            if let Pattern::Variant(pattern) = &arm.pattern {
+                for (index, binding) in pattern.bindings.iter().enumerate() {
+                    let Some(binding) = binding else {
+                        continue;
+                    };
+
+                    let (addr, type_) = Self::compile_variant_field_addr(
+                        cctx,
+                        ctx,
+                        value,
+                        &pattern.name,
+                        &pattern.variant,
+                        index,
+                    );
+
+                    let mut val = Self::load_value(cctx, ctx, &type_, addr);
+
+                    if Self::is_aggregate(cctx, &type_) {
+                        val = Self::alloc_aggregate(cctx, ctx, &type_);
+
+                        Self::copy_aggregate(cctx, ctx, &type_, addr, val);
+                    }
+
+                    let ty = Self::query_type(cctx, type_.clone());
+                    let var = ctx.new_var();
+                    let mut bctx = ctx.builder.write();
+
+                    bctx.declare_var(var, ty);
+                    bctx.def_var(var, val);
+
+                    ctx.vars.insert(
+                        binding.value.clone(),
+                        (
+                            var,
+                            Some(TypeNode {
+                                span: binding.span.clone(),
+                                name: type_,
+                                generics: Vec::new(),
+                            }),
+                        ),
+                    );
+                }
+            }
+
+            let nodes = match *arm.body.data.clone() {
+                NodeData::Block(block) => block.data,
+                _ => vec![arm.body],
+            };
+
+            let mut res = None;
+
+            for node in nodes {
+                if Self::is_filled(ctx) {
+                    break;
+                }
+
+                res = Some((Self::compile(cctx, ctx, node.clone())?, node));
+            }
+
+            if Self::is_filled(ctx) {
+                continue;
+            }
+
+            match (ty, res) {
+                (Some(ty), Some((res, node))) => {
+                    let res = Self::convert_int(cctx, ctx, &node, res, ty)?;
+
+                    ctx.builder.write().ins().jump(merge, &[res]);
+                }
+
+                _ => {
+                    ctx.builder.write().ins().jump(merge, &[]);
+                }
+            }
+        }
+
+        let mut bctx = ctx.builder.write();
+
+        bctx.switch_to_block(merge);
+        bctx.seal_block(merge);
+
+        Ok(match ty {
+            Some(_) => bctx.block_params(merge)[0],
+            None => Value::from_u32(0),
+        })
+    }
+}
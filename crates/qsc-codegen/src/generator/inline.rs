@@ -0,0 +1,141 @@
+use std::mem;
+
+use cranelift_codegen::ir::Value;
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::RwLock;
+use qsc_ast::ast::{decl::func::FunctionNode, node::attr::Attribute, stmt::call::CallNode};
+
+use crate::{
+    alias::DeclareAliasedFunction,
+    context::{CodegenContext, CompilerContext},
+};
+
+use super::{ret::ReturnCompiler, structs::StructCompiler, vars::var::VariableCompiler, Backend};
+
+pub trait InlineCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    /// The function a call should be inlined from, if it is marked with
+    /// `@Inline`. Functions returning a struct or enum are always called,
+    /// and so are the ones already being inlined, so recursion ends.
+    fn inline_target(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &CodegenContext<'a, 'b>,
+        name: &str,
+    ) -> Option<FunctionNode>;
+
+    /// Compile the body of `func` in place of a call to it.
+    fn compile_inline_call(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        call: CallNode,
+        func: FunctionNode,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> InlineCompiler<'a, 'b, M>
+    for T
+{
+    fn inline_target(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &CodegenContext<'a, 'b>,
+        name: &str,
+    ) -> Option<FunctionNode> {
+        let func = cctx.read().tree.functions().get(name).cloned()?;
+        let ret = func.ret.clone().map(|v| v.as_str()).unwrap_or_default();
+
+        let active = ctx.func.name == name || ctx.inlined.iter().any(|(func, _)| func.name == name);
+
+        if Attribute::find(&func.attrs, "Inline").is_none()
+            || Self::is_aggregate(cctx, &ret)
+            || active
+        {
+            return None;
+        }
+
+        Some(func)
+    }
+
+    fn compile_inline_call(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        call: CallNode,
+        func: FunctionNode,
+    ) -> Result<Value> {
+        debug!("Inlining call to function: {}", func.name);
+
+        let mut args = Vec::new();
+
+        for (arg, param) in call.args.iter().zip(&func.args) {
+            let value = Self::compile_owned(cctx, ctx, arg.value.clone())?;
+            let ty = Self::query_type(cctx, param.type_.as_str());
+
+            args.push(Self::convert_int(cctx, ctx, &arg.value, value, ty)?);
+        }
+
+        let ret = Self::query_type(
+            cctx,
+            func.ret.clone().map(|v| v.as_str()).unwrap_or_default(),
+        );
+
+        let exit = ctx.builder.write().create_block();
+
+        ctx.builder.write().append_block_param(exit, ret);
+
+        // The inlined function gets a scope of its own, which is put back
+        // once it is compiled.
+        let vars = mem::take(&mut ctx.vars);
+        let slots = mem::take(&mut ctx.slots);
+        let values = mem::take(&mut ctx.values);
+        let addressed = mem::take(&mut ctx.addressed);
+        let loops = mem::take(&mut ctx.loops);
+        let caller_ret = mem::replace(&mut ctx.ret, func.ret.clone());
+        let caller = mem::replace(&mut ctx.func, func.clone());
+
+        ctx.inlined.push((caller, exit));
+
+        func.content.walk(&mut |node| {
+            let value = node
+                .data
+                .as_expr()
+                .and_then(|expr| expr.as_ref())
+                .and_then(|expr| expr.value.data.as_symbol());
+
+            if let Ok(sym) = value {
+                ctx.addressed.push(sym.value);
+            }
+        });
+
+        for (arg, value) in func.args.iter().zip(args) {
+            Self::compile_value_var(cctx, ctx, arg.clone().into(), value)?;
+        }
+
+        for node in func.content.data.clone() {
+            if Self::is_filled(ctx) {
+                break;
+            }
+
+            Self::compile(cctx, ctx, node)?;
+        }
+
+        if !Self::is_filled(ctx) {
+            Self::compile_void_return(ctx);
+        }
+
+        let (caller, _) = ctx.inlined.pop().unwrap();
+
+        ctx.vars = vars;
+        ctx.slots = slots;
+        ctx.values = values;
+        ctx.addressed = addressed;
+        ctx.loops = loops;
+        ctx.ret = caller_ret;
+        ctx.func = caller;
+
+        let mut bctx = ctx.builder.write();
+
+        bctx.switch_to_block(exit);
+        bctx.seal_block(exit);
+
+        Ok(bctx.block_params(exit)[0])
+    }
+}
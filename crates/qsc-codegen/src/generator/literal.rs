@@ -14,10 +14,20 @@ use super::Backend;
 
 pub trait LiteralCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
     fn compile_bool(ctx: &mut CodegenContext<'a, 'b>, value: BoolNode) -> Value;
-    fn compile_int(ctx: &mut CodegenContext<'a, 'b>, value: IntNode) -> Value;
-    fn compile_float(ctx: &mut CodegenContext<'a, 'b>, value: FloatNode) -> Value;
     fn compile_char(ctx: &mut CodegenContext<'a, 'b>, value: CharNode) -> Value;
 
+    fn compile_int(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        value: IntNode,
+    ) -> Value;
+
+    fn compile_float(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        value: FloatNode,
+    ) -> Value;
+
     fn compile_string(
         cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
@@ -39,8 +49,8 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> LiteralCompiler<'a, 'b, M> for T
     ) -> Result<Value> {
         Ok(match expr {
             LiteralNode::Bool(b) => Self::compile_bool(ctx, b),
-            LiteralNode::Int(i) => Self::compile_int(ctx, i),
-            LiteralNode::Float(f) => Self::compile_float(ctx, f),
+            LiteralNode::Int(i) => Self::compile_int(cctx, ctx, i),
+            LiteralNode::Float(f) => Self::compile_float(cctx, ctx, f),
             LiteralNode::String(s) => Self::compile_string(cctx, ctx, s)?,
             LiteralNode::Char(c) => Self::compile_char(ctx, c),
         })
@@ -53,12 +63,35 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> LiteralCompiler<'a, 'b, M> for T
             .iconst(types::I8.as_truthy(), if value.value { 1 } else { 0 })
     }
 
-    fn compile_int(ctx: &mut CodegenContext<'a, 'b>, value: IntNode) -> Value {
-        ctx.builder.write().ins().iconst(types::I32, value.value)
+    fn compile_int(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        value: IntNode,
+    ) -> Value {
+        let ty = Self::query_type(cctx, LiteralNode::Int(value.clone()).get_type().unwrap());
+
+        // Constants are given as their raw bits, so negative numbers
+        // have to be cut down to the width of the type.
+        let bits = match ty.bits() {
+            64 => value.value,
+            bits => value.value & ((1 << bits) - 1),
+        };
+
+        ctx.builder.write().ins().iconst(ty, bits)
     }
 
-    fn compile_float(ctx: &mut CodegenContext<'a, 'b>, value: FloatNode) -> Value {
-        ctx.builder.write().ins().f64const(value.value)
+    fn compile_float(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        value: FloatNode,
+    ) -> Value {
+        let ty = Self::query_type(cctx, LiteralNode::Float(value.clone()).get_type().unwrap());
+
+        if ty == types::F32 {
+            ctx.builder.write().ins().f32const(value.value as f32)
+        } else {
+            ctx.builder.write().ins().f64const(value.value)
+        }
     }
 
     fn compile_string(
@@ -0,0 +1,220 @@
+use cranelift_codegen::ir::{condcodes::IntCC, InstBuilder, Value};
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::{RwLock, RwLockWriteGuard};
+use qsc_ast::ast::stmt::loops::{BreakNode, ContinueNode, ForNode, WhileNode};
+use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError};
+
+use crate::context::{CodegenContext, CompilerContext};
+
+use super::{vars::var::VariableCompiler, Backend};
+
+pub trait LoopCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn compile_while(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: WhileNode,
+    ) -> Result<Value>;
+
+    fn compile_for(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: ForNode,
+    ) -> Result<Value>;
+
+    fn compile_break(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: BreakNode,
+    ) -> Result<Value>;
+
+    fn compile_continue(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: ContinueNode,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> LoopCompiler<'a, 'b, M> for T {
+    fn compile_while(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: WhileNode,
+    ) -> Result<Value> {
+        let mut builder = ctx.builder.write();
+
+        let header = builder.create_block();
+        let body = builder.create_block();
+        let exit = builder.create_block();
+
+        builder.ins().jump(header, &[]);
+
+        // The header can't be sealed until the back-edge from the body exists.
+        builder.switch_to_block(header);
+
+        RwLockWriteGuard::unlock_fair(builder);
+
+        let cond_value = Self::compile(cctx, ctx, node.condition)?;
+        let mut builder = ctx.builder.write();
+
+        builder.ins().brif(cond_value, body, &[], exit, &[]);
+        builder.switch_to_block(body);
+        builder.seal_block(body);
+
+        RwLockWriteGuard::unlock_fair(builder);
+
+        ctx.loops.push((header, exit));
+
+        for node in node.block.data {
+            if Self::is_filled(ctx) {
+                break;
+            }
+
+            Self::compile(cctx, ctx, node)?;
+        }
+
+        ctx.loops.pop();
+
+        let filled = Self::is_filled(ctx);
+        let mut builder = ctx.builder.write();
+
+        if !filled {
+            builder.ins().jump(header, &[]);
+        }
+
+        builder.seal_block(header);
+        builder.switch_to_block(exit);
+        builder.seal_block(exit);
+
+        Ok(Value::from_u32(0))
+    }
+
+    fn compile_for(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: ForNode,
+    ) -> Result<Value> {
+        // The processor has settled on the type both bounds share by now.
+        let signed = node
+            .type_
+            .as_ref()
+            .is_none_or(|ty| ["i8", "i16", "i32", "i64"].contains(&ty.as_str().as_str()));
+
+        // The bounds are only evaluated once, before entering the loop.
+        let start = Self::compile(cctx, ctx, node.range.start.clone())?;
+        let end = Self::compile(cctx, ctx, node.range.end.clone())?;
+        let var = Self::declare_var(cctx, ctx, node.variable())?;
+        let mut builder = ctx.builder.write();
+
+        builder.def_var(var, start);
+
+        let header = builder.create_block();
+        let body = builder.create_block();
+        let latch = builder.create_block();
+        let exit = builder.create_block();
+
+        builder.ins().jump(header, &[]);
+        builder.switch_to_block(header);
+
+        let cc = match (node.range.inclusive, signed) {
+            (true, true) => IntCC::SignedLessThanOrEqual,
+            (true, false) => IntCC::UnsignedLessThanOrEqual,
+            (false, true) => IntCC::SignedLessThan,
+            (false, false) => IntCC::UnsignedLessThan,
+        };
+
+        let current = builder.use_var(var);
+        let cond_value = builder.ins().icmp(cc, current, end);
+
+        builder.ins().brif(cond_value, body, &[], exit, &[]);
+        builder.switch_to_block(body);
+        builder.seal_block(body);
+
+        RwLockWriteGuard::unlock_fair(builder);
+
+        // `continue` jumps to the latch, so the increment stays the only back-edge.
+        ctx.loops.push((latch, exit));
+
+        for node in node.block.data {
+            if Self::is_filled(ctx) {
+                break;
+            }
+
+            Self::compile(cctx, ctx, node)?;
+        }
+
+        ctx.loops.pop();
+
+        let filled = Self::is_filled(ctx);
+        let mut builder = ctx.builder.write();
+
+        if !filled {
+            builder.ins().jump(latch, &[]);
+        }
+
+        builder.switch_to_block(latch);
+        builder.seal_block(latch);
+
+        let current = builder.use_var(var);
+
+        // Inclusive ranges stop at the end itself, since incrementing past
+        // it would overflow when it's the largest value of its type.
+        if node.range.inclusive {
+            let step = builder.create_block();
+            let last = builder.ins().icmp(IntCC::Equal, current, end);
+
+            builder.ins().brif(last, exit, &[], step, &[]);
+            builder.switch_to_block(step);
+            builder.seal_block(step);
+        }
+
+        let next = builder.ins().iadd_imm(current, 1);
+
+        builder.def_var(var, next);
+        builder.ins().jump(header, &[]);
+
+        builder.seal_block(header);
+        builder.switch_to_block(exit);
+        builder.seal_block(exit);
+
+        Ok(Value::from_u32(0))
+    }
+
+    fn compile_break(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: BreakNode,
+    ) -> Result<Value> {
+        let Some((_, exit)) = ctx.loops.last().cloned() else {
+            return Err(CodegenError {
+                error: miette!("Cannot break outside of a loop!"),
+                location: node.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into());
+        };
+
+        ctx.builder.write().ins().jump(exit, &[]);
+
+        Ok(Value::from_u32(0))
+    }
+
+    fn compile_continue(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: ContinueNode,
+    ) -> Result<Value> {
+        let Some((header, _)) = ctx.loops.last().cloned() else {
+            return Err(CodegenError {
+                error: miette!("Cannot continue outside of a loop!"),
+                location: node.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into());
+        };
+
+        ctx.builder.write().ins().jump(header, &[]);
+
+        Ok(Value::from_u32(0))
+    }
+}
@@ -4,34 +4,53 @@ use miette::Result;
 use parking_lot::RwLock;
 use qsc_ast::ast::{
     decl::DeclarationNode,
-    expr::{unary::UnaryExpr, ExpressionNode},
+    expr::ExpressionNode,
     node::{data::NodeData, Node},
     stmt::StatementNode,
 };
+use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError};
 
 use crate::alias::DeclareAliasedFunction;
 
 use self::{
+    arrays::ArrayCompiler,
     call::CallCompiler,
+    cast::CastCompiler,
+    closure::ClosureCompiler,
     cond::ConditionalCompiler,
+    enums::EnumCompiler,
     literal::LiteralCompiler,
+    loops::LoopCompiler,
     ops::OperationCompiler,
+    pointers::PointerCompiler,
     ret::ReturnCompiler,
+    structs::StructCompiler,
+    tuples::TupleCompiler,
     unify::BackendInternal,
     vars::{func::FunctionCompiler, var::VariableCompiler},
 };
 
 use super::context::{CodegenContext, CompilerContext};
 
+pub mod arrays;
 pub mod call;
+pub mod cast;
+pub mod closure;
 pub mod cond;
+pub mod enums;
+pub mod inline;
 pub mod literal;
+pub mod loops;
 pub mod ops;
+pub mod pointers;
 pub mod ret;
+pub mod structs;
+pub mod tuples;
 pub mod unify;
 pub mod vars;
 
 pub const RETURN_VAR: &str = "__func_return__";
+pub const STRUCT_RETURN_VAR: &str = "__struct_return__";
 
 pub trait Backend<'a, 'b, M: Module>: BackendInternal<M> {
     fn query_type(cctx: &RwLock<CompilerContext<M>>, ty: String) -> Type;
@@ -39,6 +58,15 @@ pub trait Backend<'a, 'b, M: Module>: BackendInternal<M> {
     fn ptr(cctx: &RwLock<CompilerContext<M>>) -> Type;
     fn null(ctx: &mut CodegenContext<'a, 'b>) -> Value;
     fn nullptr(cctx: &RwLock<CompilerContext<M>>, ctx: &mut CodegenContext<'a, 'b>) -> Value;
+    fn is_filled(ctx: &CodegenContext<'a, 'b>) -> bool;
+
+    fn convert_int(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: &Node,
+        value: Value,
+        ty: Type,
+    ) -> Result<Value>;
 
     fn compile(
         cctx: &RwLock<CompilerContext<M>>,
@@ -78,7 +106,7 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: BackendInternal<M>> Backend<
 
     fn null(ctx: &mut CodegenContext<'a, 'b>) -> Value {
         // one null byte
-        ctx.builder.write().ins().null(types::I8)
+        ctx.builder.write().ins().iconst(types::I8, 0)
     }
 
     fn nullptr(cctx: &RwLock<CompilerContext<M>>, ctx: &mut CodegenContext<'a, 'b>) -> Value {
@@ -87,6 +115,50 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: BackendInternal<M>> Backend<
         ctx.builder.write().ins().null(ptr)
     }
 
+    fn is_filled(ctx: &CodegenContext<'a, 'b>) -> bool {
+        let bctx = ctx.builder.read();
+
+        // A block is filled once its last instruction is a terminator,
+        // after which nothing else can be emitted into it.
+        bctx.current_block()
+            .and_then(|block| bctx.func.layout.last_inst(block))
+            .map(|inst| bctx.func.dfg.insts[inst].opcode().is_terminator())
+            .unwrap_or(false)
+    }
+
+    fn convert_int(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: &Node,
+        value: Value,
+        ty: Type,
+    ) -> Result<Value> {
+        let from = ctx.builder.read().func.dfg.value_type(value);
+
+        if !from.is_int() || !ty.is_int() || from == ty {
+            return Ok(value);
+        }
+
+        let fn_name = ctx.fn_name();
+
+        // Pointers are integers to Cranelift too, but they can't be resized.
+        if !node.data.is_int(&fn_name, &cctx.read().tree)? {
+            return Ok(value);
+        }
+
+        // The source value's type decides how it gets extended.
+        let signed = node.data.is_signed(&fn_name, &cctx.read().tree)?;
+        let mut bctx = ctx.builder.write();
+
+        Ok(if from.bits() > ty.bits() {
+            bctx.ins().ireduce(ty, value)
+        } else if signed {
+            bctx.ins().sextend(ty, value)
+        } else {
+            bctx.ins().uextend(ty, value)
+        })
+    }
+
     fn get_global(
         cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
@@ -106,42 +178,112 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: BackendInternal<M>> Backend<
 
         let res = match *node.data {
             NodeData::Literal(literal) => Self::compile_literal(cctx, ctx, literal),
-            NodeData::Symbol(symbol) => Self::compile_named_var(cctx, ctx, symbol),
+            NodeData::Symbol(symbol) => {
+                if Self::is_function_value(cctx, ctx, &symbol.value) {
+                    Self::compile_function_value(cctx, ctx, &symbol.value)
+                } else {
+                    Self::compile_named_var(cctx, ctx, symbol)
+                }
+            }
+
             NodeData::Type(_) | NodeData::EOI => Ok(Self::null(ctx)),
 
             NodeData::Expr(expr) => match expr {
-                ExpressionNode::Unary(UnaryExpr {
-                    negative,
-                    value,
-                    span: _,
-                }) => Ok(if negative {
-                    let val = Self::compile(cctx, ctx, value)?;
-
-                    ctx.builder.write().ins().ineg(val)
-                } else {
-                    Self::compile(cctx, ctx, value)?
-                }),
-
+                ExpressionNode::Unary(op) => Self::compile_unary_expr(cctx, ctx, op),
                 ExpressionNode::Binary(op) => Self::compile_binary_expr(cctx, ctx, op),
+                ExpressionNode::Struct(expr) => Self::compile_struct_expr(cctx, ctx, expr),
+                ExpressionNode::Field(expr) => Self::compile_field(cctx, ctx, expr),
+                ExpressionNode::Enum(expr) => Self::compile_enum_expr(cctx, ctx, expr),
+                ExpressionNode::Match(expr) => Self::compile_match(cctx, ctx, expr),
+                ExpressionNode::VariantField(expr) => Self::compile_variant_field(cctx, ctx, expr),
+                ExpressionNode::Array(expr) => Self::compile_array_expr(cctx, ctx, expr),
+                ExpressionNode::Tuple(expr) => Self::compile_tuple_expr(cctx, ctx, expr),
+                ExpressionNode::Index(expr) => Self::compile_index(cctx, ctx, expr),
+                ExpressionNode::Ref(expr) => Self::compile_ref(cctx, ctx, expr),
+                ExpressionNode::Deref(expr) => Self::compile_deref(cctx, ctx, expr),
+                ExpressionNode::Cast(expr) => Self::compile_cast(cctx, ctx, expr),
+                ExpressionNode::Closure(expr) => Self::compile_closure(cctx, ctx, expr),
+
+                // The processor turns these into plain calls.
+                ExpressionNode::Method(expr) => Err(CodegenError {
+                    error: miette!("Unprocessed method call: {}", expr.method.value),
+                    location: expr.span.into_source_span(),
+                    src: cctx.read().source.clone(),
+                }
+                .into()),
+
+                ExpressionNode::Comptime(expr) => Err(CodegenError {
+                    error: miette!("Unevaluated comptime expression"),
+                    location: expr.span.into_source_span(),
+                    src: cctx.read().source.clone(),
+                }
+                .into()),
+
+                ExpressionNode::MacroCall(expr) => match expr.expansion {
+                    Some(expansion) => Self::compile(
+                        cctx,
+                        ctx,
+                        Node {
+                            span: expansion.span.clone(),
+                            data: Box::new(NodeData::Block(expansion)),
+                        },
+                    ),
+
+                    None => Err(CodegenError {
+                        error: miette!("Unexpanded macro: {}", expr.name),
+                        location: expr.span.into_source_span(),
+                        src: cctx.read().source.clone(),
+                    }
+                    .into()),
+                },
+
+                ExpressionNode::Spread(expr) => Err(CodegenError {
+                    error: miette!("Unexpanded spread: {}...", expr.name),
+                    location: expr.span.into_source_span(),
+                    src: cctx.read().source.clone(),
+                }
+                .into()),
             },
 
             NodeData::Statement(stmt) => match stmt {
                 StatementNode::Call(call) => Self::compile_call(cctx, ctx, call),
                 StatementNode::Return(ret) => Self::compile_return(cctx, ctx, ret),
                 StatementNode::Condition(cond) => Self::compile_conditional(cctx, ctx, cond),
+                StatementNode::While(node) => Self::compile_while(cctx, ctx, node),
+                StatementNode::For(node) => Self::compile_for(cctx, ctx, node),
+                StatementNode::Break(node) => Self::compile_break(cctx, ctx, node),
+                StatementNode::Continue(node) => Self::compile_continue(cctx, ctx, node),
+
+                StatementNode::Repeat(node) => Err(CodegenError {
+                    error: miette!("Unexpanded repetition over {}...", node.list.value),
+                    location: node.span.into_source_span(),
+                    src: cctx.read().source.clone(),
+                }
+                .into()),
             },
 
             NodeData::Declaration(decl) => match decl {
                 DeclarationNode::Variable(var) => Self::compile_var(cctx, ctx, var),
                 DeclarationNode::Function(func) => Self::compile_fn(cctx, ctx, &func),
-                DeclarationNode::Global(_global) => unimplemented!(),
+                DeclarationNode::Global(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Struct(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Enum(_) => Ok(Self::null(ctx)),
                 DeclarationNode::Extern(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Module(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Use(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Impl(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Trait(_) => Ok(Self::null(ctx)),
+                DeclarationNode::Macro(_) => Ok(Self::null(ctx)),
             },
 
             NodeData::Block(block) => {
                 let mut res = Self::null(ctx);
 
                 for node in block.data {
+                    if Self::is_filled(ctx) {
+                        break;
+                    }
+
                     res = Self::compile(cctx, ctx, node)?;
                 }
 
@@ -1,16 +1,29 @@
 use cranelift_codegen::ir::{
     condcodes::{FloatCC, IntCC},
-    InstBuilder, Value,
+    types, InstBuilder, MemFlags, Value,
 };
+use cranelift_frontend::FunctionBuilder;
 use cranelift_module::Module;
 use miette::Result;
-use parking_lot::RwLock;
-use qsc_core::{conv::IntoSourceSpan, error::compiler::CompilerError};
+use parking_lot::{RwLock, RwLockWriteGuard};
+use qsc_core::{
+    conv::IntoSourceSpan,
+    error::{codegen::CodegenError, compiler::CompilerError},
+};
 
-use crate::context::{CodegenContext, CompilerContext};
-use qsc_ast::ast::expr::{binary::BinaryExpr, operator::Operator};
+use crate::{
+    alias::DeclareAliasedFunction,
+    context::{CodegenContext, CompilerContext},
+};
+use qsc_ast::ast::{
+    expr::{binary::BinaryExpr, operator::Operator, unary::UnaryExpr},
+    node::ty::TypeNode,
+};
 
-use super::Backend;
+use super::{
+    arrays::ArrayCompiler, pointers::PointerCompiler, structs::StructCompiler,
+    vars::global::GlobalVariableCompiler, Backend,
+};
 
 pub trait OperationCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
     fn compile_binary_expr(
@@ -18,49 +31,160 @@ pub trait OperationCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
         ctx: &mut CodegenContext<'a, 'b>,
         expr: BinaryExpr,
     ) -> Result<Value>;
+
+    /// Apply `operator` to operands that are compiled already, whose types
+    /// are the ones of the sides of `expr`.
+    fn compile_operation(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: &BinaryExpr,
+        operator: Operator,
+        left: Value,
+        right: Value,
+    ) -> Result<Value>;
+
+    fn int_cond(operator: Operator, signed: bool) -> Option<IntCC>;
+
+    fn compile_float_op(
+        bctx: &mut FunctionBuilder,
+        operator: Operator,
+        left: Value,
+        right: Value,
+    ) -> Option<Value>;
+
+    fn compile_logical(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: BinaryExpr,
+    ) -> Result<Value>;
+
+    fn compile_assign(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: BinaryExpr,
+    ) -> Result<Value>;
+
+    /// Get the value a compound assignment stores, from the current value
+    /// of its place, which `load` reads, and the assigned `value`. Plain
+    /// assignments just store `value`, without reading the place.
+    fn compile_compound(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: &BinaryExpr,
+        value: Value,
+        load: impl FnOnce(&mut CodegenContext<'a, 'b>) -> Value,
+    ) -> Result<Value>;
+
+    fn compile_unary_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: UnaryExpr,
+    ) -> Result<Value>;
 }
 
-impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> OperationCompiler<'a, 'b, M> for T {
+impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> OperationCompiler<'a, 'b, M>
+    for T
+{
     fn compile_binary_expr(
         cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
         expr: BinaryExpr,
     ) -> Result<Value> {
+        if expr.operator.is_assign() {
+            return Self::compile_assign(cctx, ctx, expr);
+        }
+
+        if matches!(expr.operator, Operator::And | Operator::Or) {
+            return Self::compile_logical(cctx, ctx, expr);
+        }
+
         let left = Self::compile(cctx, ctx, expr.lhs.clone())?;
         let right = Self::compile(cctx, ctx, expr.rhs.clone())?;
-        let tree = cctx.read().tree.clone();
 
-        let fn_name = Some(if ctx.func.name == "_start" {
-            "main".to_string()
+        Self::compile_operation(cctx, ctx, &expr, expr.operator, left, right)
+    }
+
+    fn compile_operation(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: &BinaryExpr,
+        operator: Operator,
+        left: Value,
+        right: Value,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let lhs = expr.lhs.data.get_type(&fn_name, &cctx.read().tree)?;
+
+        if let Some(pointee) = TypeNode::pointee(&lhs) {
+            return Self::compile_pointer_op(
+                cctx,
+                ctx,
+                expr,
+                operator,
+                pointee.to_string(),
+                left,
+                right,
+            );
+        }
+
+        let (lty, rty) = {
+            let bctx = ctx.builder.read();
+
+            (
+                bctx.func.dfg.value_type(left),
+                bctx.func.dfg.value_type(right),
+            )
+        };
+
+        // Integers of different widths get widened to the larger one.
+        let (left, right) = if lty.bits() < rty.bits() {
+            (Self::convert_int(cctx, ctx, &expr.lhs, left, rty)?, right)
         } else {
-            ctx.func.name.clone()
-        });
+            (left, Self::convert_int(cctx, ctx, &expr.rhs, right, lty)?)
+        };
+
+        let tree = cctx.read().tree.clone();
+
+        let unsupported = |ty: &str| -> miette::Report {
+            CompilerError {
+                location: expr.span.into_source_span(),
+                src: tree.src.clone().into(),
+                error: miette!("The {:?} operator is not supported for {}!", operator, ty),
+            }
+            .into()
+        };
 
         let mut bctx = ctx.builder.write();
 
         if expr.lhs.data.is_int(&fn_name, &tree)? {
             if expr.rhs.data.is_int(&fn_name, &tree)? {
-                match expr.operator {
+                let signed = expr.lhs.data.is_signed(&fn_name, &tree)?;
+
+                if let Some(cond) = Self::int_cond(operator, signed) {
+                    return Ok(bctx.ins().icmp(cond, left, right));
+                }
+
+                match operator {
                     Operator::Add => Ok(bctx.ins().iadd(left, right)),
                     Operator::Subtract => Ok(bctx.ins().isub(left, right)),
                     Operator::Multiply => Ok(bctx.ins().imul(left, right)),
-                    Operator::Divide => Ok(bctx.ins().fdiv(left, right)),
-                    Operator::Equal => Ok(bctx.ins().icmp(IntCC::Equal, left, right)),
-                    Operator::NotEqual => Ok(bctx.ins().icmp(IntCC::NotEqual, left, right)),
+                    Operator::Divide if signed => Ok(bctx.ins().sdiv(left, right)),
+                    Operator::Divide => Ok(bctx.ins().udiv(left, right)),
+                    Operator::Modulo if signed => Ok(bctx.ins().srem(left, right)),
+                    Operator::Modulo => Ok(bctx.ins().urem(left, right)),
+                    Operator::BitwiseAnd => Ok(bctx.ins().band(left, right)),
+                    Operator::BitwiseOr => Ok(bctx.ins().bor(left, right)),
+                    Operator::BitwiseXor => Ok(bctx.ins().bxor(left, right)),
+                    Operator::ShiftLeft => Ok(bctx.ins().ishl(left, right)),
+                    Operator::ShiftRight if signed => Ok(bctx.ins().sshr(left, right)),
+                    Operator::ShiftRight => Ok(bctx.ins().ushr(left, right)),
 
-                    _ => todo!("This operation is not implemented yet!"),
+                    _ => Err(unsupported("integers")),
                 }
             } else if expr.rhs.data.is_float(&fn_name, &tree)? {
-                match expr.operator {
-                    Operator::Add => Ok(bctx.ins().fadd(left, right)),
-                    Operator::Subtract => Ok(bctx.ins().fsub(left, right)),
-                    Operator::Multiply => Ok(bctx.ins().fmul(left, right)),
-                    Operator::Divide => Ok(bctx.ins().fdiv(left, right)),
-                    Operator::Equal => Ok(bctx.ins().fcmp(FloatCC::Equal, left, right)),
-                    Operator::NotEqual => Ok(bctx.ins().fcmp(FloatCC::NotEqual, left, right)),
-
-                    _ => todo!("This operation is not implemented yet!"),
-                }
+                Self::compile_float_op(&mut bctx, operator, left, right)
+                    .ok_or_else(|| unsupported("floats"))
             } else {
                 Err(CompilerError {
                     location: expr.span.into_source_span(),
@@ -72,13 +196,28 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> OperationCompiler<'a, 'b, M> for
                 }
                 .into())
             }
+        } else if expr.lhs.data.is_float(&fn_name, &tree)? {
+            if expr.rhs.data.is_float(&fn_name, &tree)? {
+                Self::compile_float_op(&mut bctx, operator, left, right)
+                    .ok_or_else(|| unsupported("floats"))
+            } else {
+                Err(CompilerError {
+                    location: expr.span.into_source_span(),
+                    src: tree.src.clone().into(),
+                    error: miette!(
+                        "A float cannot be converted to a {}!",
+                        expr.rhs.data.get_type(&fn_name, &tree)?
+                    ),
+                }
+                .into())
+            }
         } else if expr.lhs.data.is_str(&fn_name, &tree)? {
             if expr.rhs.data.is_str(&fn_name, &tree)? {
-                match expr.operator {
+                match operator {
                     Operator::Equal => Ok(bctx.ins().icmp(IntCC::Equal, left, right)),
                     Operator::NotEqual => Ok(bctx.ins().icmp(IntCC::NotEqual, left, right)),
 
-                    _ => todo!("This operation is not implemented yet!"),
+                    _ => Err(unsupported("strings")),
                 }
             } else {
                 Err(CompilerError {
@@ -93,11 +232,9 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> OperationCompiler<'a, 'b, M> for
             }
         } else if expr.lhs.data.is_char(&fn_name, &tree)? {
             if expr.rhs.data.is_char(&fn_name, &tree)? {
-                match expr.operator {
-                    Operator::Equal => Ok(bctx.ins().icmp(IntCC::Equal, left, right)),
-                    Operator::NotEqual => Ok(bctx.ins().icmp(IntCC::NotEqual, left, right)),
-
-                    _ => todo!("This operation is not implemented yet!"),
+                match Self::int_cond(operator, false) {
+                    Some(cond) => Ok(bctx.ins().icmp(cond, left, right)),
+                    None => Err(unsupported("chars")),
                 }
             } else {
                 Err(CompilerError {
@@ -112,11 +249,14 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> OperationCompiler<'a, 'b, M> for
             }
         } else if expr.lhs.data.is_bool(&fn_name, &tree)? {
             if expr.rhs.data.is_bool(&fn_name, &tree)? {
-                match expr.operator {
+                match operator {
                     Operator::Equal => Ok(bctx.ins().icmp(IntCC::Equal, left, right)),
                     Operator::NotEqual => Ok(bctx.ins().icmp(IntCC::NotEqual, left, right)),
+                    Operator::BitwiseAnd => Ok(bctx.ins().band(left, right)),
+                    Operator::BitwiseOr => Ok(bctx.ins().bor(left, right)),
+                    Operator::BitwiseXor => Ok(bctx.ins().bxor(left, right)),
 
-                    _ => todo!("This operation is not supported for booleans yet!"),
+                    _ => Err(unsupported("booleans")),
                 }
             } else {
                 Err(CompilerError {
@@ -138,4 +278,270 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> OperationCompiler<'a, 'b, M> for
             .into())
         }
     }
+
+    fn int_cond(operator: Operator, signed: bool) -> Option<IntCC> {
+        let cond = match operator {
+            Operator::Equal => IntCC::Equal,
+            Operator::NotEqual => IntCC::NotEqual,
+            Operator::Greater => IntCC::SignedGreaterThan,
+            Operator::Less => IntCC::SignedLessThan,
+            Operator::GreaterEqual => IntCC::SignedGreaterThanOrEqual,
+            Operator::LessEqual => IntCC::SignedLessThanOrEqual,
+
+            _ => return None,
+        };
+
+        Some(if signed { cond } else { cond.unsigned() })
+    }
+
+    fn compile_float_op(
+        bctx: &mut FunctionBuilder,
+        operator: Operator,
+        left: Value,
+        right: Value,
+    ) -> Option<Value> {
+        Some(match operator {
+            Operator::Add => bctx.ins().fadd(left, right),
+            Operator::Subtract => bctx.ins().fsub(left, right),
+            Operator::Multiply => bctx.ins().fmul(left, right),
+            Operator::Divide => bctx.ins().fdiv(left, right),
+
+            // Cranelift has no float remainder, so this is `fmod` spelled out:
+            // `left - trunc(left / right) * right`. `trunc` itself would be a
+            // libcall without SSE4.1, so it's done with an integer round-trip
+            // instead. Quotients of 2^52 and above are integral already.
+            Operator::Modulo => {
+                let ty = bctx.func.dfg.value_type(left);
+                let quot = bctx.ins().fdiv(left, right);
+                let int = bctx.ins().fcvt_to_sint_sat(types::I64, quot);
+                let int = bctx.ins().fcvt_from_sint(ty, int);
+                let abs = bctx.ins().fabs(quot);
+
+                let limit = if ty == types::F32 {
+                    bctx.ins().f32const(4503599627370496.0)
+                } else {
+                    bctx.ins().f64const(4503599627370496.0)
+                };
+
+                let big = bctx.ins().fcmp(FloatCC::GreaterThanOrEqual, abs, limit);
+                let quot = bctx.ins().select(big, quot, int);
+                let prod = bctx.ins().fmul(quot, right);
+
+                bctx.ins().fsub(left, prod)
+            }
+
+            Operator::Equal => bctx.ins().fcmp(FloatCC::Equal, left, right),
+            Operator::NotEqual => bctx.ins().fcmp(FloatCC::NotEqual, left, right),
+            Operator::Greater => bctx.ins().fcmp(FloatCC::GreaterThan, left, right),
+            Operator::Less => bctx.ins().fcmp(FloatCC::LessThan, left, right),
+            Operator::GreaterEqual => bctx.ins().fcmp(FloatCC::GreaterThanOrEqual, left, right),
+            Operator::LessEqual => bctx.ins().fcmp(FloatCC::LessThanOrEqual, left, right),
+
+            _ => return None,
+        })
+    }
+
+    fn compile_logical(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: BinaryExpr,
+    ) -> Result<Value> {
+        let ty = Self::query_type(cctx, "bool".to_string());
+        let left = Self::compile(cctx, ctx, expr.lhs)?;
+        let mut builder = ctx.builder.write();
+
+        let rhs = builder.create_block();
+        let merge = builder.create_block();
+
+        builder.append_block_param(merge, ty);
+
+        // Only evaluate the right-hand side if the left one didn't
+        // already decide the result.
+        if expr.operator == Operator::And {
+            builder.ins().brif(left, rhs, &[], merge, &[left]);
+        } else {
+            builder.ins().brif(left, merge, &[left], rhs, &[]);
+        }
+
+        builder.switch_to_block(rhs);
+        builder.seal_block(rhs);
+
+        RwLockWriteGuard::unlock_fair(builder);
+
+        let right = Self::compile(cctx, ctx, expr.rhs)?;
+        let mut builder = ctx.builder.write();
+
+        builder.ins().jump(merge, &[right]);
+        builder.switch_to_block(merge);
+        builder.seal_block(merge);
+
+        Ok(builder.block_params(merge)[0])
+    }
+
+    fn compile_assign(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: BinaryExpr,
+    ) -> Result<Value> {
+        let value = match expr.operator.base_op() {
+            Some(_) => Self::compile(cctx, ctx, expr.rhs.clone())?,
+            None => Self::compile_owned(cctx, ctx, expr.rhs.clone())?,
+        };
+
+        if let Ok(field) = expr.lhs.data.as_expr().and_then(|v| v.as_field()) {
+            let (addr, type_) = Self::compile_field_addr(cctx, ctx, field)?;
+            let ty = Self::query_type(cctx, type_.clone());
+            let value = Self::compile_compound(cctx, ctx, &expr, value, |ctx| {
+                Self::load_value(cctx, ctx, &type_, addr)
+            })?;
+            let value = Self::convert_int(cctx, ctx, &expr.rhs, value, ty)?;
+
+            Self::store_value(cctx, ctx, &type_, value, addr);
+
+            return Ok(value);
+        }
+
+        if let Ok(deref) = expr.lhs.data.as_expr().and_then(|v| v.as_deref()) {
+            let fn_name = ctx.fn_name();
+
+            let type_ = expr.lhs.data.get_type(&fn_name, &cctx.read().tree)?;
+            let ty = Self::query_type(cctx, type_.clone());
+            let addr = Self::compile(cctx, ctx, deref.value)?;
+            let value = Self::compile_compound(cctx, ctx, &expr, value, |ctx| {
+                Self::load_value(cctx, ctx, &type_, addr)
+            })?;
+            let value = Self::convert_int(cctx, ctx, &expr.rhs, value, ty)?;
+
+            Self::store_value(cctx, ctx, &type_, value, addr);
+
+            return Ok(value);
+        }
+
+        if let Ok(index) = expr.lhs.data.as_expr().and_then(|v| v.as_index()) {
+            let (addr, type_) = Self::compile_index_addr(cctx, ctx, index)?;
+            let ty = Self::query_type(cctx, type_.clone());
+            let value = Self::compile_compound(cctx, ctx, &expr, value, |ctx| {
+                Self::load_value(cctx, ctx, &type_, addr)
+            })?;
+            let value = Self::convert_int(cctx, ctx, &expr.rhs, value, ty)?;
+
+            Self::store_value(cctx, ctx, &type_, value, addr);
+
+            return Ok(value);
+        }
+
+        let sym = expr.lhs.data.as_symbol()?;
+        let global = cctx.read().globals.get(&sym.value).copied();
+
+        if let Some((slot, type_)) = ctx.slots.get(&sym.value).cloned() {
+            let ty = Self::query_type(cctx, type_.map(|v| v.as_str()).unwrap_or_default());
+            let value = Self::compile_compound(cctx, ctx, &expr, value, |ctx| {
+                ctx.builder.write().ins().stack_load(ty, slot, 0)
+            })?;
+            let value = Self::convert_int(cctx, ctx, &expr.rhs, value, ty)?;
+
+            ctx.builder.write().ins().stack_store(value, slot, 0);
+
+            Ok(value)
+        } else if let Some((var, type_)) = ctx.vars.get(&sym.value).cloned() {
+            let type_ = type_.map(|v| v.as_str()).unwrap_or_default();
+
+            // Something may still point at an addressed struct or array, so
+            // it gets overwritten in place instead of rebound.
+            if ctx.addressed.contains(&sym.value) && Self::is_aggregate(cctx, &type_) {
+                let addr = ctx.builder.write().use_var(var);
+
+                Self::copy_aggregate(cctx, ctx, &type_, value, addr);
+
+                return Ok(addr);
+            }
+
+            let ty = Self::query_type(cctx, type_);
+            let value = Self::compile_compound(cctx, ctx, &expr, value, |ctx| {
+                ctx.builder.write().use_var(var)
+            })?;
+            let value = Self::convert_int(cctx, ctx, &expr.rhs, value, ty)?;
+
+            ctx.builder.write().def_var(var, value);
+
+            Ok(value)
+        } else if let Some(id) = global {
+            let type_ = cctx.read().tree.globals()[&sym.value].type_.as_str();
+            let addr = Self::compile_global_addr(cctx, ctx, id);
+
+            if Self::is_aggregate(cctx, &type_) {
+                Self::copy_aggregate(cctx, ctx, &type_, value, addr);
+
+                return Ok(addr);
+            }
+
+            let ty = Self::query_type(cctx, type_);
+            let value = Self::compile_compound(cctx, ctx, &expr, value, |ctx| {
+                ctx.builder
+                    .write()
+                    .ins()
+                    .load(ty, MemFlags::trusted(), addr, 0)
+            })?;
+            let value = Self::convert_int(cctx, ctx, &expr.rhs, value, ty)?;
+
+            ctx.builder
+                .write()
+                .ins()
+                .store(MemFlags::trusted(), value, addr, 0);
+
+            Ok(value)
+        } else {
+            Err(CodegenError {
+                error: miette!("Variable {} not found", sym.value),
+                location: sym.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into())
+        }
+    }
+
+    fn compile_compound(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: &BinaryExpr,
+        value: Value,
+        load: impl FnOnce(&mut CodegenContext<'a, 'b>) -> Value,
+    ) -> Result<Value> {
+        let Some(operator) = expr.operator.base_op() else {
+            return Ok(value);
+        };
+
+        let current = load(ctx);
+
+        Self::compile_operation(cctx, ctx, expr, operator, current, value)
+    }
+
+    fn compile_unary_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: UnaryExpr,
+    ) -> Result<Value> {
+        let value = Self::compile(cctx, ctx, expr.value.clone())?;
+        let tree = cctx.read().tree.clone();
+
+        let fn_name = ctx.fn_name();
+
+        let is_float = expr.value.data.is_float(&fn_name, &tree)?;
+        let mut bctx = ctx.builder.write();
+
+        match expr.operator {
+            Operator::Add => Ok(value),
+            Operator::Subtract if is_float => Ok(bctx.ins().fneg(value)),
+            Operator::Subtract => Ok(bctx.ins().ineg(value)),
+            Operator::Not => Ok(bctx.ins().icmp_imm(IntCC::Equal, value, 0)),
+            Operator::BitwiseNot => Ok(bctx.ins().bnot(value)),
+
+            operator => Err(CodegenError {
+                error: miette!("{:?} is not a unary operator!", operator),
+                location: expr.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into()),
+        }
+    }
 }
@@ -0,0 +1,184 @@
+use cranelift_codegen::ir::{types, InstBuilder, Value};
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::RwLock;
+use qsc_ast::ast::{
+    expr::{
+        binary::BinaryExpr,
+        operator::Operator,
+        pointer::{DerefExpr, RefExpr},
+        ExpressionNode,
+    },
+    node::data::NodeData,
+};
+use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError};
+
+use crate::{
+    alias::DeclareAliasedFunction,
+    context::{CodegenContext, CompilerContext},
+};
+
+use super::{
+    arrays::ArrayCompiler, ops::OperationCompiler, structs::StructCompiler,
+    vars::global::GlobalVariableCompiler, Backend,
+};
+
+/// Typed pointers (`*T`, or `ptr<T>`) are plain addresses at runtime, the
+/// pointee type only decides how big a load or a step is.
+pub trait PointerCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn compile_ref(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: RefExpr,
+    ) -> Result<Value>;
+
+    fn compile_deref(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: DerefExpr,
+    ) -> Result<Value>;
+
+    /// Compile arithmetic and comparisons with a pointer on the left.
+    /// Offsets are scaled by the size of the pointee.
+    fn compile_pointer_op(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: &BinaryExpr,
+        operator: Operator,
+        pointee: String,
+        left: Value,
+        right: Value,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> PointerCompiler<'a, 'b, M>
+    for T
+{
+    fn compile_ref(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: RefExpr,
+    ) -> Result<Value> {
+        let ptr = Self::ptr(cctx);
+
+        match *expr.value.data {
+            NodeData::Expr(ExpressionNode::Field(field)) => {
+                Ok(Self::compile_field_addr(cctx, ctx, field)?.0)
+            }
+
+            NodeData::Expr(ExpressionNode::Index(index)) => {
+                Ok(Self::compile_index_addr(cctx, ctx, index)?.0)
+            }
+
+            NodeData::Expr(ExpressionNode::Deref(deref)) => Self::compile(cctx, ctx, deref.value),
+
+            NodeData::Symbol(sym) => {
+                if let Some((slot, _)) = ctx.slots.get(&sym.value) {
+                    return Ok(ctx.builder.write().ins().stack_addr(ptr, *slot, 0));
+                }
+
+                if let Some((var, type_)) = ctx.vars.get(&sym.value).cloned() {
+                    let type_ = type_.map(|v| v.as_str()).unwrap_or_default();
+
+                    // Structs and arrays are pointers to their storage anyway.
+                    if Self::is_aggregate(cctx, &type_) {
+                        return Ok(ctx.builder.write().use_var(var));
+                    }
+                }
+
+                let global = cctx.read().globals.get(&sym.value).copied();
+
+                if let Some(id) = global {
+                    return Ok(Self::compile_global_addr(cctx, ctx, id));
+                }
+
+                Err(CodegenError {
+                    error: miette!("Cannot take the address of {}!", sym.value),
+                    location: sym.span.into_source_span(),
+                    src: cctx.read().source.clone(),
+                }
+                .into())
+            }
+
+            _ => Err(CodegenError {
+                error: miette!("Cannot take the address of a temporary value!"),
+                location: expr.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into()),
+        }
+    }
+
+    fn compile_deref(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: DerefExpr,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let ty = ExpressionNode::Deref(expr.clone()).get_type(&fn_name, &cctx.read().tree)?;
+        let addr = Self::compile(cctx, ctx, expr.value)?;
+
+        Ok(Self::load_value(cctx, ctx, &ty, addr))
+    }
+
+    fn compile_pointer_op(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: &BinaryExpr,
+        operator: Operator,
+        pointee: String,
+        left: Value,
+        right: Value,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let tree = cctx.read().tree.clone();
+        let ptr = Self::ptr(cctx);
+        let (size, _) = Self::type_layout(cctx, &pointee);
+        if expr.rhs.data.is_int(&fn_name, &tree)? {
+            let right = Self::convert_int(cctx, ctx, &expr.rhs, right, ptr)?;
+            let mut bctx = ctx.builder.write();
+            let offset = bctx.ins().imul_imm(right, size as i64);
+
+            return match operator {
+                Operator::Add => Ok(bctx.ins().iadd(left, offset)),
+                Operator::Subtract => Ok(bctx.ins().isub(left, offset)),
+
+                operator => Err(CodegenError {
+                    error: miette!("The {:?} operator is not supported for pointers!", operator),
+                    location: expr.span.into_source_span(),
+                    src: cctx.read().source.clone(),
+                }
+                .into()),
+            };
+        }
+
+        let mut bctx = ctx.builder.write();
+
+        if let Some(cond) = Self::int_cond(operator, false) {
+            return Ok(bctx.ins().icmp(cond, left, right));
+        }
+
+        match operator {
+            // The distance between two pointers, in elements.
+            Operator::Subtract => {
+                let bytes = bctx.ins().isub(left, right);
+                let elems = bctx.ins().sdiv_imm(bytes, size as i64);
+
+                Ok(if ptr.bits() < 64 {
+                    bctx.ins().sextend(types::I64, elems)
+                } else {
+                    elems
+                })
+            }
+
+            operator => Err(CodegenError {
+                error: miette!("The {:?} operator is not supported for pointers!", operator),
+                location: expr.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into()),
+        }
+    }
+}
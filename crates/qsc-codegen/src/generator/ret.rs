@@ -1,8 +1,4 @@
-use cranelift_codegen::{
-    entity::EntityRef,
-    ir::{InstBuilder, Value},
-};
-use cranelift_frontend::Variable;
+use cranelift_codegen::ir::{types, InstBuilder, Value};
 use cranelift_module::Module;
 use miette::Result;
 use parking_lot::RwLock;
@@ -16,7 +12,10 @@ use qsc_ast::ast::stmt::{
     ret::ReturnNode,
 };
 
-use super::{Backend, CallCompiler, RETURN_VAR};
+use super::{
+    structs::StructCompiler, tuples::TupleCompiler, Backend, CallCompiler, RETURN_VAR,
+    STRUCT_RETURN_VAR,
+};
 
 pub trait ReturnCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
     fn compile_return(
@@ -24,6 +23,11 @@ pub trait ReturnCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
         ctx: &mut CodegenContext<'a, 'b>,
         node: ReturnNode,
     ) -> Result<Value>;
+
+    /// Return from a function without a value. Void functions still
+    /// return a word in their signature, which is left as zero.
+    /// Inside of an inlined function, this jumps back to the caller.
+    fn compile_void_return(ctx: &mut CodegenContext<'a, 'b>);
 }
 
 impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> ReturnCompiler<'a, 'b, M>
@@ -52,31 +56,94 @@ impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> ReturnCo
                 )?;
             }
 
-            let val = Self::compile(cctx, ctx, value)?;
+            let val = Self::compile(cctx, ctx, value.clone())?;
 
             let ty = Self::query_type(
                 cctx,
                 ctx.ret.clone().map(|v| v.as_str()).unwrap_or(String::new()),
             );
 
-            let ref_ = Variable::new(ctx.vars.len());
-            let mut bctx = ctx.builder.write();
+            let val = Self::convert_int(cctx, ctx, &value, val, ty)?;
+
+            let val = match ctx.values.get(STRUCT_RETURN_VAR).cloned() {
+                Some((sret, type_)) => {
+                    Self::copy_aggregate(cctx, ctx, &type_.as_str(), val, sret);
 
-            bctx.declare_var(ref_, ty);
-            bctx.def_var(ref_, val);
+                    sret
+                }
+
+                None => val,
+            };
+
+            let ref_ = ctx.new_var();
+
+            {
+                let mut bctx = ctx.builder.write();
+
+                bctx.declare_var(ref_, ty);
+                bctx.def_var(ref_, val);
+            }
 
             ctx.vars
                 .insert(RETURN_VAR.to_string(), (ref_, ctx.ret.clone()));
 
-            let val = bctx.use_var(ref_);
+            let val = ctx.builder.write().use_var(ref_);
+            let ret = ctx.ret.clone().map(|v| v.as_str()).unwrap_or_default();
 
-            bctx.ins().return_(&[val]);
+            // Small tuples are returned as one value for each element.
+            let values = if cctx.read().tree.split_tuple(&ret).is_some() {
+                Self::split_tuple_values(cctx, ctx, &ret, val)
+            } else {
+                vec![val]
+            };
+
+            let mut bctx = ctx.builder.write();
+
+            // Inlined functions return by jumping back into their caller.
+            match ctx.inlined.last() {
+                Some((_, exit)) => bctx.ins().jump(*exit, &values),
+                None => bctx.ins().return_(&values),
+            };
 
             Ok(val)
         } else {
-            ctx.builder.write().ins().return_(&[]);
+            Self::compile_void_return(ctx);
 
-            Ok(Self::null(ctx))
+            Ok(Value::from_u32(0))
         }
     }
+
+    fn compile_void_return(ctx: &mut CodegenContext<'a, 'b>) {
+        let mut bctx = ctx.builder.write();
+
+        let returns = match ctx.inlined.last() {
+            Some((_, exit)) => bctx
+                .block_params(*exit)
+                .iter()
+                .map(|param| bctx.func.dfg.value_type(*param))
+                .collect::<Vec<_>>(),
+
+            None => bctx
+                .func
+                .signature
+                .returns
+                .iter()
+                .map(|ret| ret.value_type)
+                .collect(),
+        };
+
+        let values = returns
+            .into_iter()
+            .map(|ty| match ty {
+                types::F32 => bctx.ins().f32const(0.0),
+                types::F64 => bctx.ins().f64const(0.0),
+                ty => bctx.ins().iconst(ty, 0),
+            })
+            .collect::<Vec<_>>();
+
+        match ctx.inlined.last() {
+            Some((_, exit)) => bctx.ins().jump(*exit, &values),
+            None => bctx.ins().return_(&values),
+        };
+    }
 }
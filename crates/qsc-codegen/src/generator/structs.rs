@@ -0,0 +1,365 @@
+use cranelift_codegen::ir::{InstBuilder, MemFlags, StackSlotData, StackSlotKind, Value};
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::RwLock;
+use qsc_ast::ast::{
+    expr::{
+        structure::{FieldExpr, StructExpr},
+        ExpressionNode,
+    },
+    node::{ty::TypeNode, Node},
+};
+use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError};
+
+use crate::context::{CodegenContext, CompilerContext};
+
+use super::{enums::EnumCompiler, tuples::TupleCompiler, Backend};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub size: u32,
+    pub align: u32,
+    pub fields: Vec<FieldLayout>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: String,
+    pub type_: String,
+    pub offset: u32,
+}
+
+/// Structs (and enums) are always handled through a pointer to their
+/// storage, which is a stack slot for locals. Copies are made whenever one
+/// is bound to a variable or passed to a function, so each binding owns its
+/// data.
+pub trait StructCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn is_aggregate(cctx: &RwLock<CompilerContext<M>>, ty: &str) -> bool;
+
+    /// Get the size and alignment of a type, in bytes.
+    fn type_layout(cctx: &RwLock<CompilerContext<M>>, ty: &str) -> (u32, u32);
+
+    fn struct_layout(cctx: &RwLock<CompilerContext<M>>, name: &str) -> StructLayout;
+
+    /// Lay out a list of `(name, type)` fields, starting at `start`.
+    fn fields_layout(
+        cctx: &RwLock<CompilerContext<M>>,
+        fields: Vec<(String, String)>,
+        start: u32,
+    ) -> StructLayout;
+
+    fn alloc_aggregate(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+    ) -> Value;
+
+    fn copy_aggregate(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        src: Value,
+        dest: Value,
+    );
+
+    fn store_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        value: Value,
+        addr: Value,
+    );
+
+    fn load_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        addr: Value,
+    ) -> Value;
+
+    /// Compile a value that is about to be bound somewhere, copying it
+    /// first if it is a struct that something else still refers to.
+    fn compile_owned(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: Node,
+    ) -> Result<Value>;
+
+    fn compile_struct_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: StructExpr,
+    ) -> Result<Value>;
+
+    fn compile_field_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: FieldExpr,
+    ) -> Result<(Value, String)>;
+
+    fn compile_field(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: FieldExpr,
+    ) -> Result<Value>;
+}
+
+impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> StructCompiler<'a, 'b, M> for T {
+    fn is_aggregate(cctx: &RwLock<CompilerContext<M>>, ty: &str) -> bool {
+        cctx.read().tree.is_aggregate(ty)
+    }
+
+    fn type_layout(cctx: &RwLock<CompilerContext<M>>, ty: &str) -> (u32, u32) {
+        if cctx.read().tree.structs().contains_key(ty) {
+            let layout = Self::struct_layout(cctx, ty);
+
+            (layout.size, layout.align)
+        } else if cctx.read().tree.enums().contains_key(ty) {
+            let layout = Self::enum_layout(cctx, ty);
+
+            (layout.size, layout.align)
+        } else if let Some((elem, len)) = TypeNode::array_parts(ty) {
+            let (size, align) = Self::type_layout(cctx, elem);
+
+            (size * len as u32, align)
+        } else if TypeNode::tuple_parts(ty).is_some() {
+            let layout = Self::tuple_layout(cctx, ty);
+
+            (layout.size, layout.align)
+        } else {
+            let bytes = Self::query_type(cctx, ty.to_string()).bytes();
+
+            (bytes, bytes)
+        }
+    }
+
+    fn struct_layout(cctx: &RwLock<CompilerContext<M>>, name: &str) -> StructLayout {
+        let item = cctx.read().tree.structs()[name].clone();
+
+        let fields = item
+            .fields
+            .into_iter()
+            .map(|field| (field.name, field.type_.as_str()))
+            .collect();
+
+        Self::fields_layout(cctx, fields, 0)
+    }
+
+    fn fields_layout(
+        cctx: &RwLock<CompilerContext<M>>,
+        fields: Vec<(String, String)>,
+        start: u32,
+    ) -> StructLayout {
+        let mut layout = Vec::new();
+        let mut offset = start;
+        let mut align = 1;
+
+        // Same rules as C: every field is aligned to its own alignment,
+        // and the whole struct is padded to a multiple of the largest one.
+        for (name, type_) in fields {
+            let (field_size, field_align) = Self::type_layout(cctx, &type_);
+
+            offset = offset.next_multiple_of(field_align);
+            align = align.max(field_align);
+
+            layout.push(FieldLayout {
+                name,
+                type_,
+                offset,
+            });
+
+            offset += field_size;
+        }
+
+        StructLayout {
+            size: offset.next_multiple_of(align),
+            align,
+            fields: layout,
+        }
+    }
+
+    fn alloc_aggregate(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+    ) -> Value {
+        let ptr = Self::ptr(cctx);
+        let (size, align) = Self::type_layout(cctx, ty);
+        let mut bctx = ctx.builder.write();
+
+        let slot = bctx.create_sized_stack_slot(StackSlotData::new(
+            StackSlotKind::ExplicitSlot,
+            size,
+            align.trailing_zeros() as u8,
+        ));
+
+        bctx.ins().stack_addr(ptr, slot, 0)
+    }
+
+    fn copy_aggregate(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        src: Value,
+        dest: Value,
+    ) {
+        let (size, align) = Self::type_layout(cctx, ty);
+        let config = cctx.read().module.target_config();
+        let align = align.min(u8::MAX as u32) as u8;
+
+        ctx.builder.write().emit_small_memory_copy(
+            config,
+            dest,
+            src,
+            size as u64,
+            align,
+            align,
+            true,
+            MemFlags::trusted(),
+        );
+    }
+
+    fn store_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        value: Value,
+        addr: Value,
+    ) {
+        if Self::is_aggregate(cctx, ty) {
+            Self::copy_aggregate(cctx, ctx, ty, value, addr);
+        } else {
+            ctx.builder
+                .write()
+                .ins()
+                .store(MemFlags::trusted(), value, addr, 0);
+        }
+    }
+
+    fn load_value(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        addr: Value,
+    ) -> Value {
+        // Nested structs are used in place, like any other struct value.
+        if Self::is_aggregate(cctx, ty) {
+            return addr;
+        }
+
+        let ty = Self::query_type(cctx, ty.to_string());
+
+        ctx.builder
+            .write()
+            .ins()
+            .load(ty, MemFlags::trusted(), addr, 0)
+    }
+
+    fn compile_owned(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        node: Node,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let ty = node.data.get_type(&fn_name, &cctx.read().tree)?;
+
+        // Literals and call results are fresh already, so they don't need
+        // to be copied.
+        let fresh = node.data.is_stmt()
+            || node.data.as_expr().is_ok_and(|expr| {
+                matches!(
+                    expr,
+                    ExpressionNode::Struct(_)
+                        | ExpressionNode::Enum(_)
+                        | ExpressionNode::Array(_)
+                        | ExpressionNode::Tuple(_)
+                )
+            });
+
+        let value = Self::compile(cctx, ctx, node)?;
+
+        if fresh || !Self::is_aggregate(cctx, &ty) {
+            return Ok(value);
+        }
+
+        let copy = Self::alloc_aggregate(cctx, ctx, &ty);
+
+        Self::copy_aggregate(cctx, ctx, &ty, value, copy);
+
+        Ok(copy)
+    }
+
+    fn compile_struct_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: StructExpr,
+    ) -> Result<Value> {
+        let layout = Self::struct_layout(cctx, &expr.name);
+        let addr = Self::alloc_aggregate(cctx, ctx, &expr.name);
+
+        for field in expr.fields {
+            let info = layout.fields.iter().find(|v| v.name == field.name).unwrap();
+
+            let ty = Self::query_type(cctx, info.type_.clone());
+            let value = Self::compile(cctx, ctx, field.value.clone())?;
+            let value = Self::convert_int(cctx, ctx, &field.value, value, ty)?;
+            let field_addr = ctx.builder.write().ins().iadd_imm(addr, info.offset as i64);
+
+            Self::store_value(cctx, ctx, &info.type_, value, field_addr);
+        }
+
+        Ok(addr)
+    }
+
+    fn compile_field_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: FieldExpr,
+    ) -> Result<(Value, String)> {
+        let fn_name = ctx.fn_name();
+
+        let ty = expr.value.data.get_type(&fn_name, &cctx.read().tree)?;
+
+        // Fields are reached through pointers too, and since structs are
+        // used by their address, the pointer is the base already.
+        let ty = TypeNode::pointee(&ty).unwrap_or(&ty).to_string();
+
+        let layout = if TypeNode::tuple_parts(&ty).is_some() {
+            Self::tuple_layout(cctx, &ty)
+        } else if cctx.read().tree.structs().contains_key(&ty) {
+            Self::struct_layout(cctx, &ty)
+        } else {
+            return Err(CodegenError {
+                error: miette!("Type {} has no fields!", ty),
+                location: expr.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into());
+        };
+
+        let Some(info) = layout.fields.iter().find(|v| v.name == expr.field.value) else {
+            return Err(CodegenError {
+                error: miette!("Type {} has no field named {}!", ty, expr.field.value),
+                location: expr.field.span.into_source_span(),
+                src: cctx.read().source.clone(),
+            }
+            .into());
+        };
+
+        let base = Self::compile(cctx, ctx, expr.value)?;
+        let addr = ctx.builder.write().ins().iadd_imm(base, info.offset as i64);
+
+        Ok((addr, info.type_.clone()))
+    }
+
+    fn compile_field(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: FieldExpr,
+    ) -> Result<Value> {
+        let (addr, ty) = Self::compile_field_addr(cctx, ctx, expr)?;
+
+        Ok(Self::load_value(cctx, ctx, &ty, addr))
+    }
+}
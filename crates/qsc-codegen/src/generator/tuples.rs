@@ -0,0 +1,132 @@
+use cranelift_codegen::ir::{AbiParam, InstBuilder, Type, Value};
+use cranelift_module::Module;
+use miette::Result;
+use parking_lot::RwLock;
+use qsc_ast::ast::{
+    expr::{tuple::TupleExpr, ExpressionNode},
+    node::ty::TypeNode,
+    AbstractTree,
+};
+
+use crate::context::{CodegenContext, CompilerContext};
+
+use super::{
+    structs::{StructCompiler, StructLayout},
+    Backend,
+};
+
+/// Tuples are laid out like structs with fields named `0`, `1` and so on.
+/// Small ones are returned from functions as one value for each element
+/// instead of through memory, see [`AbstractTree::split_tuple`].
+pub trait TupleCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
+    fn tuple_layout(cctx: &RwLock<CompilerContext<M>>, ty: &str) -> StructLayout;
+
+    /// Get the values a function returning `ret` gives back.
+    fn return_params(tree: &AbstractTree, ptr: Type, ret: &str) -> Vec<AbiParam>;
+
+    fn compile_tuple_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: TupleExpr,
+    ) -> Result<Value>;
+
+    /// Load every element of a tuple, to return them.
+    fn split_tuple_values(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        addr: Value,
+    ) -> Vec<Value>;
+
+    /// Store the elements a call returned into new storage for their tuple.
+    fn join_tuple_values(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        values: &[Value],
+    ) -> Value;
+}
+
+impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> TupleCompiler<'a, 'b, M> for T {
+    fn tuple_layout(cctx: &RwLock<CompilerContext<M>>, ty: &str) -> StructLayout {
+        let fields = TypeNode::tuple_parts(ty)
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, ty)| (idx.to_string(), ty))
+            .collect();
+
+        Self::fields_layout(cctx, fields, 0)
+    }
+
+    fn return_params(tree: &AbstractTree, ptr: Type, ret: &str) -> Vec<AbiParam> {
+        match tree.split_tuple(ret) {
+            Some(elems) => elems
+                .into_iter()
+                .map(|ty| AbiParam::new(Self::query_type_with_pointer(ptr, ty)))
+                .collect(),
+
+            None => vec![AbiParam::new(Self::query_type_with_pointer(
+                ptr,
+                ret.to_string(),
+            ))],
+        }
+    }
+
+    fn compile_tuple_expr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        expr: TupleExpr,
+    ) -> Result<Value> {
+        let fn_name = ctx.fn_name();
+
+        let ty = ExpressionNode::Tuple(expr.clone()).get_type(&fn_name, &cctx.read().tree)?;
+        let layout = Self::tuple_layout(cctx, &ty);
+        let addr = Self::alloc_aggregate(cctx, ctx, &ty);
+
+        for (value, info) in expr.values.into_iter().zip(layout.fields) {
+            let ty = Self::query_type(cctx, info.type_.clone());
+            let val = Self::compile(cctx, ctx, value.clone())?;
+            let val = Self::convert_int(cctx, ctx, &value, val, ty)?;
+            let field_addr = ctx.builder.write().ins().iadd_imm(addr, info.offset as i64);
+
+            Self::store_value(cctx, ctx, &info.type_, val, field_addr);
+        }
+
+        Ok(addr)
+    }
+
+    fn split_tuple_values(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        addr: Value,
+    ) -> Vec<Value> {
+        Self::tuple_layout(cctx, ty)
+            .fields
+            .into_iter()
+            .map(|info| {
+                let field_addr = ctx.builder.write().ins().iadd_imm(addr, info.offset as i64);
+
+                Self::load_value(cctx, ctx, &info.type_, field_addr)
+            })
+            .collect()
+    }
+
+    fn join_tuple_values(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        ty: &str,
+        values: &[Value],
+    ) -> Value {
+        let addr = Self::alloc_aggregate(cctx, ctx, ty);
+
+        for (value, info) in values.iter().zip(Self::tuple_layout(cctx, ty).fields) {
+            let field_addr = ctx.builder.write().ins().iadd_imm(addr, info.offset as i64);
+
+            Self::store_value(cctx, ctx, &info.type_, *value, field_addr);
+        }
+
+        addr
+    }
+}
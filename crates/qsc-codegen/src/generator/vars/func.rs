@@ -1,11 +1,12 @@
-use cranelift_codegen::ir::{InstBuilder, Value};
+use cranelift_codegen::ir::Value;
 use cranelift_module::Module;
 use miette::Result;
 use parking_lot::RwLock;
 
 use crate::{
+    alias::DeclareAliasedFunction,
     context::{CodegenContext, CompilerContext},
-    generator::{Backend, RETURN_VAR},
+    generator::{ret::ReturnCompiler, Backend, RETURN_VAR, STRUCT_RETURN_VAR},
 };
 
 use qsc_ast::ast::decl::func::FunctionNode;
@@ -20,7 +21,9 @@ pub trait FunctionCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
     ) -> Result<Value>;
 }
 
-impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> FunctionCompiler<'a, 'b, M> for T {
+impl<'a, 'b, M: Module + DeclareAliasedFunction, T: Backend<'a, 'b, M>> FunctionCompiler<'a, 'b, M>
+    for T
+{
     fn compile_fn(
         cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
@@ -41,11 +44,35 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> FunctionCompiler<'a, 'b, M> for T
 
         debug!("Declaring argument variables for function: {}", func.name);
 
+        let ret = func.ret.clone().map(|v| v.as_str()).unwrap_or_default();
+        let offset = cctx.read().tree.returns_by_pointer(&ret) as usize;
+
+        if offset == 1 {
+            let sret = ctx.builder.write().block_params(entry)[0];
+
+            ctx.values.insert(
+                STRUCT_RETURN_VAR.to_string(),
+                (sret, func.ret.clone().unwrap()),
+            );
+        }
+
+        // Locals that get their address taken have to be stored in memory.
+        func.content.walk(&mut |node| {
+            let value = node
+                .data
+                .as_expr()
+                .and_then(|expr| expr.as_ref())
+                .and_then(|expr| expr.value.data.as_symbol());
+
+            if let Ok(sym) = value {
+                ctx.addressed.push(sym.value);
+            }
+        });
+
         for (idx, arg) in func.args.iter().enumerate() {
-            let val = ctx.builder.write().block_params(entry)[idx];
-            let var = Self::declare_var(cctx, ctx, arg.clone().into())?;
+            let val = ctx.builder.write().block_params(entry)[idx + offset];
 
-            ctx.builder.write().def_var(var, val);
+            Self::compile_value_var(cctx, ctx, arg.clone().into(), val)?;
         }
 
         debug!("Compiling nodes for function: {}", func.name);
@@ -57,7 +84,7 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> FunctionCompiler<'a, 'b, M> for T
         debug!("Compiled all nodes for function: {}", func.name);
 
         if !ctx.vars.contains_key(RETURN_VAR) {
-            ctx.builder.write().ins().return_(&[]);
+            Self::compile_void_return(ctx);
         }
 
         debug!("Compiled function: {}", func.name);
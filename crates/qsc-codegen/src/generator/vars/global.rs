@@ -1,42 +1,252 @@
 use crate::{
     context::{CodegenContext, CompilerContext},
-    generator::Backend,
+    generator::{structs::StructCompiler, tuples::TupleCompiler, Backend},
+    linkage::linkage,
 };
-use cranelift_module::{DataId, Module};
-use miette::Result;
-use qsc_ast::ast::decl::global::GlobalVariable;
+use cranelift_codegen::ir::{types, Endianness, InstBuilder, Value};
+use cranelift_module::{DataId, Linkage, Module};
+use miette::{IntoDiagnostic, Result};
+use parking_lot::{RwLock, RwLockWriteGuard};
+use qsc_ast::ast::{
+    decl::global::GlobalVariable,
+    expr::{operator::Operator, ExpressionNode},
+    literal::LiteralNode,
+    node::{data::NodeData, ty::TypeNode, Node},
+};
+use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError, util::mangle};
 
 pub trait GlobalVariableCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
-    fn compile_global(
-        cctx: &mut CompilerContext<M>,
+    fn compile_global(cctx: &RwLock<CompilerContext<M>>, var: GlobalVariable) -> Result<DataId>;
+
+    /// Write `value` out as the bytes of a `ty` at `offset` into `bytes`.
+    /// Strings are left as null pointers, and collected in `strings` with
+    /// where they go.
+    fn global_data(
+        cctx: &RwLock<CompilerContext<M>>,
+        ty: &str,
+        value: &Node,
+        offset: u32,
+        bytes: &mut [u8],
+        strings: &mut Vec<(u32, String)>,
+    ) -> Result<()>;
+
+    fn compile_global_addr(
+        cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
-        var: GlobalVariable,
-    ) -> Result<DataId>;
+        id: DataId,
+    ) -> Value;
 }
 
 impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> GlobalVariableCompiler<'a, 'b, M> for T {
-    fn compile_global(
-        _cctx: &mut CompilerContext<M>,
-        _ctx: &mut CodegenContext<'a, 'b>,
-        _var: GlobalVariable,
-    ) -> Result<DataId> {
-        todo!();
+    fn compile_global(cctx: &RwLock<CompilerContext<M>>, var: GlobalVariable) -> Result<DataId> {
+        let ty = var.type_.as_str();
+        let (size, align) = Self::type_layout(cctx, &ty);
+        let mut bytes = vec![0; size as usize];
+        let mut strings = Vec::new();
+
+        Self::global_data(cctx, &ty, &var.value, 0, &mut bytes, &mut strings)?;
+
+        let mut wctx = cctx.write();
+        let mut pointers = Vec::new();
+
+        // Strings live in their own data objects, and the global itself
+        // only holds pointers to them.
+        for (idx, (offset, string)) in strings.into_iter().enumerate() {
+            wctx.data_desc
+                .define(format!("{}\0", string).into_bytes().into_boxed_slice());
+
+            let name = match idx {
+                0 => format!("{}.str", mangle(&var.name)),
+                _ => format!("{}.str{}", mangle(&var.name), idx),
+            };
+
+            let str_id = wctx
+                .module
+                .declare_data(&name, Linkage::Local, false, false)
+                .into_diagnostic()?;
+
+            let mut ddesc = wctx.data_desc.clone();
+
+            wctx.module.define_data(str_id, &ddesc).into_diagnostic()?;
+            ddesc.clear();
+            wctx.data_desc = ddesc;
+
+            pointers.push((offset, str_id));
+        }
+
+        let mut ddesc = wctx.data_desc.clone();
+
+        // Not `define_zeroinit`, which would put pointers in `.bss` where
+        // they can't be relocated.
+        ddesc.define(bytes.into_boxed_slice());
+        ddesc.set_align(align as u64);
+
+        for (offset, str_id) in pointers {
+            let str_ref = wctx.module.declare_data_in_data(str_id, &mut ddesc);
+
+            ddesc.write_data_addr(offset, str_ref, 0);
+        }
+
+        let id = wctx
+            .module
+            .declare_data(
+                &mangle(&var.name),
+                linkage(&var.name, var.vis),
+                var.mutable,
+                false,
+            )
+            .map_err(|err| CodegenError {
+                error: miette!("Cannot declare global {}: {}", var.name, err),
+                location: var.span.into_source_span(),
+                src: wctx.source.clone(),
+            })?;
+
+        wctx.module.define_data(id, &ddesc).into_diagnostic()?;
+        ddesc.clear();
+        wctx.data_desc = ddesc;
+        wctx.globals.insert(var.name.clone(), id);
+
+        RwLockWriteGuard::unlock_fair(wctx);
+        Self::post_define(cctx, id)?;
+
+        Ok(id)
+    }
+
+    fn global_data(
+        cctx: &RwLock<CompilerContext<M>>,
+        ty: &str,
+        value: &Node,
+        offset: u32,
+        bytes: &mut [u8],
+        strings: &mut Vec<(u32, String)>,
+    ) -> Result<()> {
+        match value.data.as_ref() {
+            NodeData::Expr(ExpressionNode::Array(expr)) => {
+                let elem = TypeNode::array_parts(ty)
+                    .map(|(elem, _)| elem)
+                    .unwrap_or_default();
+                let (size, _) = Self::type_layout(cctx, elem);
+
+                for (idx, value) in expr.values.iter().enumerate() {
+                    let offset = offset + size * idx as u32;
+
+                    Self::global_data(cctx, elem, value, offset, bytes, strings)?;
+                }
 
-        // let ty = Self::query_type(cctx, var.type_.as_str());
+                return Ok(());
+            }
 
-        // cctx.data_desc.define(Self::compile_literal(cctx, ctx, var.value.data.as_literal()?)?);
+            NodeData::Expr(ExpressionNode::Tuple(expr)) => {
+                for (value, info) in expr.values.iter().zip(Self::tuple_layout(cctx, ty).fields) {
+                    let offset = offset + info.offset;
 
-        // let id = cctx
-        //     .module
-        //     .declare_data(&var.name, Linkage::Export, true, false)?;
+                    Self::global_data(cctx, &info.type_, value, offset, bytes, strings)?;
+                }
 
-        // cctx.module.define_data(id, cctx.data_desc)?;
-        // cctx.data_desc.clear();
+                return Ok(());
+            }
 
-        // Self::post_define(cctx, id)?;
+            NodeData::Expr(ExpressionNode::Struct(expr)) => {
+                let layout = Self::struct_layout(cctx, ty);
 
-        // cctx.globals.insert(&var.name, id);
+                for field in &expr.fields {
+                    let Some(info) = layout.fields.iter().find(|v| v.name == field.name) else {
+                        continue;
+                    };
+
+                    let offset = offset + info.offset;
+
+                    Self::global_data(cctx, &info.type_, &field.value, offset, bytes, strings)?;
+                }
+
+                return Ok(());
+            }
+
+            _ => {}
+        }
+
+        let scalar = Self::query_type(cctx, ty.to_string());
+        let little = cctx.read().module.isa().endianness() == Endianness::Little;
+
+        // The processor only lets literals (with an optional unary
+        // operator in front of them) through here.
+        let (operator, literal) = match value.data.as_expr() {
+            Ok(expr) => {
+                let unary = expr.as_unary()?;
+
+                (Some(unary.operator), unary.value.data.as_literal()?)
+            }
+
+            Err(_) => (None, value.data.as_literal()?),
+        };
+
+        let data = match literal {
+            LiteralNode::Int(int) => {
+                let value = match operator {
+                    Some(Operator::Subtract) => int.value.wrapping_neg(),
+                    Some(Operator::BitwiseNot) => !int.value,
+                    _ => int.value,
+                };
+
+                let size = scalar.bytes() as usize;
+
+                if little {
+                    value.to_le_bytes()[..size].to_vec()
+                } else {
+                    value.to_be_bytes()[8 - size..].to_vec()
+                }
+            }
+
+            LiteralNode::Float(float) => {
+                let value = match operator {
+                    Some(Operator::Subtract) => -float.value,
+                    _ => float.value,
+                };
+
+                match (scalar == types::F32, little) {
+                    (true, true) => (value as f32).to_le_bytes().to_vec(),
+                    (true, false) => (value as f32).to_be_bytes().to_vec(),
+                    (false, true) => value.to_le_bytes().to_vec(),
+                    (false, false) => value.to_be_bytes().to_vec(),
+                }
+            }
+
+            LiteralNode::Bool(bool) => {
+                vec![(bool.value != (operator == Some(Operator::Not))) as u8]
+            }
+
+            LiteralNode::Char(char) => {
+                if little {
+                    (char.value as u32).to_le_bytes().to_vec()
+                } else {
+                    (char.value as u32).to_be_bytes().to_vec()
+                }
+            }
+
+            // Filled in with a relocation once the string has its own
+            // data object.
+            LiteralNode::String(string) => {
+                strings.push((offset, string.value));
+
+                Vec::new()
+            }
+        };
+
+        let start = offset as usize;
+
+        bytes[start..start + data.len()].copy_from_slice(&data);
+
+        Ok(())
+    }
+
+    fn compile_global_addr(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        id: DataId,
+    ) -> Value {
+        let ptr = Self::ptr(cctx);
+        let local_id = Self::get_global(cctx, ctx, id);
 
-        // Ok(id)
+        ctx.builder.write().ins().global_value(ptr, local_id)
     }
 }
@@ -1,10 +1,7 @@
-use cranelift_codegen::{
-    entity::EntityRef,
-    ir::{InstBuilder, Value},
-};
+use cranelift_codegen::ir::{InstBuilder, MemFlags, StackSlotData, StackSlotKind, Value};
 use cranelift_frontend::Variable;
-use cranelift_module::{DataId, Linkage, Module};
-use miette::{IntoDiagnostic, Result};
+use cranelift_module::{DataId, Module};
+use miette::Result;
 
 use parking_lot::RwLock;
 use qsc_ast::ast::{decl::var::VariableNode, node::sym::SymbolNode};
@@ -12,7 +9,7 @@ use qsc_core::{conv::IntoSourceSpan, error::codegen::CodegenError};
 
 use crate::{
     context::{CodegenContext, CompilerContext},
-    generator::Backend,
+    generator::{structs::StructCompiler, vars::global::GlobalVariableCompiler, Backend},
 };
 
 pub trait VariableCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
@@ -50,6 +47,14 @@ pub trait VariableCompiler<'a, 'b, M: Module>: Backend<'a, 'b, M> {
         value: Value,
     ) -> Result<Self::O>;
 
+    /// Store a local in a stack slot, so it can have its address taken.
+    fn compile_slot_var(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        var: VariableNode,
+        value: Value,
+    ) -> Result<Self::O>;
+
     fn compile_named_var(
         cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
@@ -67,7 +72,7 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> VariableCompiler<'a, 'b, M> for T
     ) -> Result<Self::O> {
         match var.clone().value {
             Some(value) => {
-                let val = Self::compile(cctx, ctx, value)?;
+                let val = Self::compile_owned(cctx, ctx, value)?;
 
                 Self::compile_value_var(cctx, ctx, var, val)
             }
@@ -88,7 +93,7 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> VariableCompiler<'a, 'b, M> for T
                 .map(|v| v.as_str())
                 .unwrap_or(String::new()),
         );
-        let ref_ = Variable::new(ctx.vars.len());
+        let ref_ = ctx.new_var();
 
         ctx.builder.write().declare_var(ref_, ty);
         ctx.vars.insert(var.name, (ref_, var.type_));
@@ -109,9 +114,14 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> VariableCompiler<'a, 'b, M> for T
                 .unwrap_or(String::new()),
         );
 
+        let null = ctx.builder.write().ins().null(ty);
+
+        if ctx.addressed.contains(&var.name) {
+            return Self::compile_slot_var(cctx, ctx, var, null);
+        }
+
         let mut bctx = ctx.builder.write();
-        let null = bctx.ins().null(ty);
-        let ref_ = Variable::new(ctx.vars.len());
+        let ref_ = ctx.new_var();
 
         bctx.declare_var(ref_, ty);
         bctx.def_var(ref_, null);
@@ -146,7 +156,7 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> VariableCompiler<'a, 'b, M> for T
                 .map(|v| v.as_str())
                 .unwrap_or(String::new()),
         );
-        let ref_ = Variable::new(ctx.vars.len());
+        let ref_ = ctx.new_var();
         let mut bctx = ctx.builder.write();
 
         bctx.declare_var(ref_, ty);
@@ -170,7 +180,19 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> VariableCompiler<'a, 'b, M> for T
                 .unwrap_or(String::new()),
         );
 
-        let ref_ = Variable::new(ctx.vars.len());
+        let val = match &var.value {
+            Some(node) => Self::convert_int(cctx, ctx, node, val, ty)?,
+            None => val,
+        };
+
+        let type_ = var.type_.clone().map(|v| v.as_str()).unwrap_or_default();
+
+        // Structs and arrays live in memory already.
+        if ctx.addressed.contains(&var.name) && !Self::is_aggregate(cctx, &type_) {
+            return Self::compile_slot_var(cctx, ctx, var, val);
+        }
+
+        let ref_ = ctx.new_var();
         let mut bctx = ctx.builder.write();
 
         bctx.declare_var(ref_, ty);
@@ -180,38 +202,78 @@ impl<'a, 'b, M: Module, T: Backend<'a, 'b, M>> VariableCompiler<'a, 'b, M> for T
         Ok(bctx.use_var(ref_))
     }
 
+    fn compile_slot_var(
+        cctx: &RwLock<CompilerContext<M>>,
+        ctx: &mut CodegenContext<'a, 'b>,
+        var: VariableNode,
+        value: Value,
+    ) -> Result<Self::O> {
+        let type_ = var.type_.clone().map(|v| v.as_str()).unwrap_or_default();
+        let (size, align) = Self::type_layout(cctx, &type_);
+        let mut bctx = ctx.builder.write();
+
+        let slot = bctx.create_sized_stack_slot(StackSlotData::new(
+            StackSlotKind::ExplicitSlot,
+            size,
+            align.trailing_zeros() as u8,
+        ));
+
+        bctx.ins().stack_store(value, slot, 0);
+        ctx.slots.insert(var.name, (slot, var.type_));
+
+        Ok(value)
+    }
+
     fn compile_named_var(
         cctx: &RwLock<CompilerContext<M>>,
         ctx: &mut CodegenContext<'a, 'b>,
         ident: SymbolNode,
     ) -> Result<Self::O> {
-        let ptr = Self::ptr(cctx);
-        let mut wctx = cctx.write();
-        let mut bctx = ctx.builder.write();
+        if let Some((slot, type_)) = ctx.slots.get(&ident.value).cloned() {
+            let ty = Self::query_type(cctx, type_.map(|v| v.as_str()).unwrap_or_default());
+
+            return Ok(ctx.builder.write().ins().stack_load(ty, slot, 0));
+        }
 
-        if ctx.vars.contains_key(&ident.value) {
-            let (ref_, _) = *ctx.vars.get(&ident.value).unwrap();
+        if let Some((ref_, _)) = ctx.vars.get(&ident.value) {
+            return Ok(ctx.builder.write().use_var(*ref_));
+        }
+
+        let global = cctx.read().tree.globals().get(&ident.value).cloned();
 
-            Ok(bctx.use_var(ref_))
-        } else if wctx.globals.contains_key(&ident.value) {
-            let sym = wctx
-                .module
-                .declare_data(&ident.value, Linkage::Export, true, false)
-                .into_diagnostic()?;
+        if let Some(global) = global {
+            let type_ = global.type_.as_str();
+            let id = cctx.read().globals.get(&ident.value).copied();
 
-            let local_id = wctx.module.declare_data_in_func(sym, bctx.func);
-            let val = bctx.ins().symbol_value(ptr, local_id);
+            // Structs and arrays are pointers to their storage anyway.
+            if let Some(id) = id.filter(|_| Self::is_aggregate(cctx, &type_)) {
+                return Ok(Self::compile_global_addr(cctx, ctx, id));
+            }
+
+            if global.constant {
+                return Self::compile(cctx, ctx, global.value);
+            }
 
-            Ok(val)
-        } else if ctx.values.contains_key(&ident.value) {
-            let (val, _) = *ctx.values.get(&ident.value).unwrap();
+            let ty = Self::query_type(cctx, type_);
+
+            if let Some(id) = id {
+                let addr = Self::compile_global_addr(cctx, ctx, id);
+
+                return Ok(ctx
+                    .builder
+                    .write()
+                    .ins()
+                    .load(ty, MemFlags::trusted(), addr, 0));
+            }
+        }
 
-            Ok(val)
+        if let Some((val, _)) = ctx.values.get(&ident.value) {
+            Ok(*val)
         } else {
             Err(CodegenError {
                 error: miette!("Variable {} not found", ident.value),
                 location: ident.span.into_source_span(),
-                src: wctx.source.clone(),
+                src: cctx.read().source.clone(),
             }
             .into())
         }
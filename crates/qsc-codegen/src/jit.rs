@@ -1,16 +1,18 @@
 use std::{collections::HashMap, sync::Arc};
 
 use cranelift_codegen::{
-    ir::{AbiParam, Function},
+    ir::{AbiParam, ArgumentPurpose, Function},
     isa::lookup,
     settings::{self, Configurable, Flags},
     Context,
 };
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
-use cranelift_module::{default_libcall_names, DataDescription, DataId, Linkage, Module};
+use cranelift_module::{default_libcall_names, DataDescription, DataId, FuncOrDataId, Module};
 use miette::{IntoDiagnostic, NamedSource, Result};
 use parking_lot::RwLock;
 use qsc_ast::ast::{decl::func::FunctionNode, AbstractTree};
+
+use crate::linkage::{function_linkage, symbol};
 use qsc_jit::{JITBuilder, JITModule};
 use target_lexicon::Triple;
 
@@ -18,7 +20,9 @@ use crate::lookup::lookup_symbol;
 
 use super::{
     context::{CodegenContext, CompilerContext},
-    generator::{unify::BackendInternal, vars::func::FunctionCompiler, Backend},
+    generator::{
+        tuples::TupleCompiler, unify::BackendInternal, vars::func::FunctionCompiler, Backend,
+    },
 };
 
 pub struct JitGenerator {
@@ -33,6 +37,7 @@ impl JitGenerator {
         source: String,
         tree: AbstractTree,
         libs: Vec<String>,
+        release: bool,
     ) -> Result<Self> {
         let mut flags = settings::builder();
 
@@ -64,11 +69,14 @@ impl JitGenerator {
             module,
             functions: HashMap::new(),
             globals: HashMap::new(),
+            fn_values: HashMap::new(),
+            thunks: HashMap::new(),
             code: map,
             fns: Vec::new(),
             vcode: Vec::new(),
             source: NamedSource::new(name, source),
             tree,
+            debug: !release,
         };
 
         Ok(Self {
@@ -89,6 +97,19 @@ impl JitGenerator {
         debug!("Compiling function: {}", func.name);
 
         let ptr = self.ctx.read().module.isa().pointer_type();
+        let ret = func.ret.clone().map(|v| v.as_str()).unwrap_or_default();
+
+        // Structs, enums, arrays and bigger tuples get returned through a
+        // pointer to the caller's storage.
+        if self.ctx.read().tree.returns_by_pointer(&ret) {
+            self.ctx
+                .write()
+                .ctx
+                .func
+                .signature
+                .params
+                .push(AbiParam::special(ptr, ArgumentPurpose::StructReturn));
+        }
 
         for arg in func.args.clone() {
             self.ctx
@@ -103,19 +124,17 @@ impl JitGenerator {
                 )));
         }
 
-        self.ctx
-            .write()
-            .ctx
-            .func
-            .signature
-            .returns
-            .push(AbiParam::new(Self::query_type_with_pointer(
-                ptr,
-                func.ret
-                    .clone()
-                    .map(|v| v.as_str())
-                    .unwrap_or("void".to_string()),
-            )));
+        let returns = Self::return_params(
+            &self.ctx.read().tree,
+            ptr,
+            &func
+                .ret
+                .clone()
+                .map(|v| v.as_str())
+                .unwrap_or("void".to_string()),
+        );
+
+        self.ctx.write().ctx.func.signature.returns.extend(returns);
 
         Ok(())
     }
@@ -139,9 +158,14 @@ impl JitGenerator {
             builder: &builder,
             locals: HashMap::new(),
             vars: HashMap::new(),
+            addressed: Vec::new(),
+            slots: HashMap::new(),
             values: HashMap::new(),
+            loops: Vec::new(),
+            next_var: 0,
             ret: func.ret.clone(),
             func: func.clone(),
+            inlined: Vec::new(),
         };
 
         Self::compile_fn(&self.ctx, ctx, func)?;
@@ -158,7 +182,7 @@ impl JitGenerator {
             .ctx
             .write()
             .module
-            .declare_function(&func.name, Linkage::Export, &sig)
+            .declare_function(&symbol(&func), function_linkage(&func), &sig)
             .into_diagnostic()?;
 
         {
@@ -188,21 +212,31 @@ impl JitGenerator {
             ctx.module.clear_context(ctx_ref);
         }
 
-        self.ctx
-            .write()
-            .module
-            .finalize_definitions()
-            .into_diagnostic()?;
+        debug!("Compiled function: {}", func.name);
 
-        let (code, size) = self.ctx.read().module.get_finalized_function(id);
+        Ok(())
+    }
 
-        self.ctx
-            .write()
-            .code
-            .write()
-            .insert(func.name.to_string(), (func.name.to_string(), code, size));
+    /// Finalize everything once all of the functions are defined, since
+    /// functions can call the ones that come after them.
+    pub fn finalize_all(&mut self) -> Result<()> {
+        let mut wctx = self.ctx.write();
 
-        debug!("Compiled function: {}", func.name);
+        wctx.module.finalize_definitions().into_diagnostic()?;
+
+        for (name, func) in &wctx.functions {
+            let symbol = symbol(func);
+
+            let Some(FuncOrDataId::Func(id)) = wctx.module.get_name(&symbol) else {
+                continue;
+            };
+
+            let (code, size) = wctx.module.get_finalized_function(id);
+
+            wctx.code
+                .write()
+                .insert(symbol, (name.to_string(), code, size));
+        }
 
         Ok(())
     }
@@ -235,17 +269,8 @@ impl JitGenerator {
 }
 
 impl BackendInternal<JITModule> for JitGenerator {
-    fn post_define(cctx: &RwLock<CompilerContext<JITModule>>, id: DataId) -> Result<()> {
-        let mut wctx = cctx.write();
-
-        wctx.module.finalize_definitions().into_diagnostic()?;
-
-        let (code, size) = wctx.module.get_finalized_data(id);
-
-        wctx.code
-            .write()
-            .insert(String::new(), (String::new(), code, size));
-
+    // Data is finalized along with the functions in `finalize_all`.
+    fn post_define(_cctx: &RwLock<CompilerContext<JITModule>>, _id: DataId) -> Result<()> {
         Ok(())
     }
 
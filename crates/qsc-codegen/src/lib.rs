@@ -9,6 +9,7 @@ pub mod aot;
 pub mod context;
 pub mod generator;
 pub mod jit;
+pub mod linkage;
 pub mod lookup;
 pub mod simple;
 pub mod unify;
@@ -0,0 +1,45 @@
+use cranelift_module::Linkage;
+use qsc_ast::ast::{
+    decl::func::FunctionNode,
+    node::{attr::Attribute, vis::Visibility},
+};
+use qsc_core::util::mangle;
+
+/// Public declarations are exported from the object, and everything else
+/// stays local to it. The entrypoint is always exported.
+pub fn linkage(name: &str, vis: Visibility) -> Linkage {
+    if vis == Visibility::Public || name == "main" || name == "_start" {
+        Linkage::Export
+    } else {
+        Linkage::Local
+    }
+}
+
+/// Like [`linkage`], but functions that pick their own symbol with
+/// `@Export` or `@NoMangle` are always exported.
+pub fn function_linkage(func: &FunctionNode) -> Linkage {
+    if Attribute::find(&func.attrs, "Export").is_some()
+        || Attribute::find(&func.attrs, "NoMangle").is_some()
+    {
+        Linkage::Export
+    } else {
+        linkage(&func.name, func.vis)
+    }
+}
+
+/// The name of the symbol a function is defined as. `@Export("name")`
+/// sets it, `@NoMangle` uses the function's name without its path, and
+/// everything else gets mangled.
+pub fn symbol(func: &FunctionNode) -> String {
+    if let Some(name) = Attribute::find(&func.attrs, "Export").and_then(|attr| attr.string()) {
+        return name;
+    }
+
+    if Attribute::find(&func.attrs, "NoMangle").is_some() {
+        let name = func.name.rsplit("::").next().unwrap();
+
+        return name.to_string();
+    }
+
+    mangle(&func.name)
+}
@@ -17,9 +17,10 @@ impl<T: CodegenBackend> SimpleCompiler<T> {
         source: &String,
         tree: AbstractTree,
         libs: Vec<String>,
+        release: bool,
     ) -> Result<Self, BackendError> {
         Ok(Self {
-            backend: T::new(triple, name, source.clone(), tree, libs)?,
+            backend: T::new(triple, name, source.clone(), tree, libs, release)?,
         })
     }
 
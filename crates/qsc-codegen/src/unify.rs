@@ -1,6 +1,8 @@
 use std::slice;
 
-use super::{aot::AotGenerator, jit::JitGenerator};
+use super::{
+    aot::AotGenerator, generator::vars::global::GlobalVariableCompiler, jit::JitGenerator,
+};
 use cranelift_codegen::write_function;
 use cranelift_module::Module;
 use miette::{IntoDiagnostic, Result};
@@ -15,6 +17,7 @@ pub trait CodegenBackend {
         source: String,
         tree: AbstractTree,
         libs: Vec<String>,
+        release: bool,
     ) -> Result<Self>
     where
         Self: Sized;
@@ -37,13 +40,23 @@ impl CodegenBackend for AotGenerator {
         source: String,
         tree: AbstractTree,
         _libs: Vec<String>,
+        release: bool,
     ) -> Result<Self> {
-        Ok(Self::new(triple, name, source, tree)?)
+        Ok(Self::new(triple, name, source, tree, release)?)
     }
 
     fn compile(&mut self) -> Result<()> {
         let data = self.ctx.read().tree.clone();
 
+        // Globals have to exist before any function can refer to them.
+        // Constants are compiled where they're used, except for structs
+        // and arrays, which are only stored once.
+        for global in data.globals().into_values() {
+            if !global.constant || data.is_aggregate(global.type_.as_str()) {
+                Self::compile_global(&self.ctx, global)?;
+            }
+        }
+
         for node in data.data {
             if let Ok(decl) = node.data.as_decl() {
                 if let Ok(func) = decl.as_function() {
@@ -144,13 +157,23 @@ impl CodegenBackend for JitGenerator {
         source: String,
         tree: AbstractTree,
         libs: Vec<String>,
+        release: bool,
     ) -> Result<Self> {
-        Ok(Self::new(triple, name, source, tree, libs)?)
+        Ok(Self::new(triple, name, source, tree, libs, release)?)
     }
 
     fn compile(&mut self) -> Result<()> {
         let data = self.ctx.read().tree.clone();
 
+        // Globals have to exist before any function can refer to them.
+        // Constants are compiled where they're used, except for structs
+        // and arrays, which are only stored once.
+        for global in data.globals().into_values() {
+            if !global.constant || data.is_aggregate(global.type_.as_str()) {
+                Self::compile_global(&self.ctx, global)?;
+            }
+        }
+
         for node in data.data {
             if let Ok(decl) = node.data.as_decl() {
                 if let Ok(func) = decl.as_function() {
@@ -159,7 +182,7 @@ impl CodegenBackend for JitGenerator {
             }
         }
 
-        Ok(())
+        self.finalize_all()
     }
 
     fn is_jit(&self) -> bool {
@@ -5,11 +5,12 @@ use miette::IntoDiagnostic;
 use parking_lot::RwLock;
 use qsc_ast::ast::AbstractTree;
 use qsc_codegen::{simple::SimpleCompiler, unify::CodegenBackend};
-use qsc_core::error::Result;
+use qsc_core::error::{processor::ProcessorWarning, Result};
 use qsc_lexer::lexer::Lexer;
 use qsc_object::ObjectProduct;
 use qsc_processor::Processor;
 use ron::ser::PrettyConfig;
+use std::path::Path;
 use target_lexicon::Triple;
 
 pub struct Compiler<B: CodegenBackend> {
@@ -18,33 +19,58 @@ pub struct Compiler<B: CodegenBackend> {
 
     pub name: String,
     pub source: String,
+
+    /// The libraries to link with, including the ones asked for with
+    /// `@Link`.
+    pub libs: Vec<String>,
+
+    /// Warnings found while processing the program, like uses of
+    /// `@Deprecated` items, for the caller to report.
+    pub warnings: Vec<ProcessorWarning>,
 }
 
 impl<B: CodegenBackend> Compiler<B> {
+    /// Compile the program whose root file is at `path`. The files of the
+    /// modules it declares are found relative to it.
     pub fn compile(
-        name: impl AsRef<str>,
-        source: impl AsRef<str>,
+        path: impl AsRef<Path>,
         triple: Triple,
-        libs: Vec<String>,
+        mut libs: Vec<String>,
+        release: bool,
     ) -> Result<Self> {
         debug!("[Stage 1/3] Running lexer...");
 
-        let mut lexer = Lexer::new(&name, &source);
-        let ast = lexer.lex()?;
+        let ast = Lexer::lex_file(&path)?;
+        let name = path
+            .as_ref()
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let source = ast.source.clone();
 
         debug!("[Stage 2/3] Running processor...");
 
         let mut proc = Processor::new(ast);
         let ast = proc.process()?;
+        let warnings = proc.warnings;
+
+        for lib in ast.links() {
+            if !libs.contains(&lib) {
+                libs.push(lib);
+            }
+        }
 
         debug!("[Stage 3/3] Compiling...");
 
         let mut backend = SimpleCompiler::<B>::new(
             triple,
-            name.as_ref().to_string(),
-            &source.as_ref().to_string(),
+            name.clone(),
+            &source,
             ast.clone(),
-            libs,
+            libs.clone(),
+            release,
         )?;
 
         backend.compile()?;
@@ -53,16 +79,17 @@ impl<B: CodegenBackend> Compiler<B> {
             ast: RwLock::new(ast),
             backend: RwLock::new(backend),
 
-            name: name.as_ref().to_string(),
-            source: source.as_ref().to_string(),
+            name,
+            source,
+            libs,
+            warnings,
         })
     }
 
-    pub fn dump_pst(name: impl AsRef<str>, source: impl AsRef<str>) -> Result<String> {
+    pub fn dump_pst(path: impl AsRef<Path>) -> Result<String> {
         debug!("[Stage 1/2] Running lexer...");
 
-        let mut lexer = Lexer::new(name, source);
-        let ast = lexer.lex()?;
+        let ast = Lexer::lex_file(path)?;
 
         debug!("[Stage 2/2] Running processor...");
 
@@ -74,11 +101,10 @@ impl<B: CodegenBackend> Compiler<B> {
             .map_err(|v| v.into())
     }
 
-    pub fn dump_ast(name: impl AsRef<str>, source: impl AsRef<str>) -> Result<String> {
+    pub fn dump_ast(path: impl AsRef<Path>) -> Result<String> {
         debug!("[1/1] Running lexer...");
 
-        let mut lexer = Lexer::new(name, source);
-        let ast = lexer.lex()?;
+        let ast = Lexer::lex_file(path)?;
 
         ron::ser::to_string_pretty(&ast, PrettyConfig::default())
             .into_diagnostic()
@@ -9,8 +9,12 @@ use miette::{Diagnostic, Report};
 use thiserror::Error;
 
 use self::{
-    backend::BackendError, codegen::CodegenError, compiler::CompilerError, lexer::LexerError,
-    lexical::LexicalError, processor::ProcessorError,
+    backend::BackendError,
+    codegen::CodegenError,
+    compiler::CompilerError,
+    lexer::LexerError,
+    lexical::LexicalError,
+    processor::{DuplicateError, MacroError, ProcessorError},
 };
 
 pub type Result<T, E = CompileError> = std::result::Result<T, E>;
@@ -38,6 +42,14 @@ pub enum CompileError {
     #[diagnostic(transparent)]
     Processor(#[from] ProcessorError),
 
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Duplicate(#[from] DuplicateError),
+
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Macro(#[from] Box<MacroError>),
+
     #[error(transparent)]
     #[diagnostic(transparent)]
     Codegen(#[from] CodegenError),
@@ -1,6 +1,8 @@
 use miette::{Diagnostic, NamedSource, Report, SourceSpan};
 use thiserror::Error;
 
+use super::CompileError;
+
 #[derive(Debug, Error, Diagnostic)]
 #[error("Processor Error")]
 #[diagnostic(code(qsc_processor::error), url(docsrs))]
@@ -14,3 +16,56 @@ pub struct ProcessorError {
     #[help]
     pub error: Report,
 }
+
+/// A name that is declared twice in the same module, pointing at both of
+/// the declarations.
+#[derive(Debug, Error, Diagnostic)]
+#[error("Processor Error")]
+#[diagnostic(code(qsc_processor::duplicate), url(docsrs))]
+pub struct DuplicateError {
+    #[source_code]
+    pub src: NamedSource<String>,
+
+    #[label("first declared here")]
+    pub first: SourceSpan,
+
+    #[label("declared again here")]
+    pub second: SourceSpan,
+
+    #[help]
+    pub error: Report,
+}
+
+/// Something that compiles, but probably isn't what was meant, like a call
+/// to a deprecated function.
+#[derive(Debug, Error, Diagnostic)]
+#[error("Processor Warning")]
+#[diagnostic(code(qsc_processor::warning), severity(Warning), url(docsrs))]
+pub struct ProcessorWarning {
+    #[source_code]
+    pub src: NamedSource<String>,
+
+    #[label("here")]
+    pub location: SourceSpan,
+
+    #[help]
+    pub error: Report,
+}
+
+/// An error in code that came from expanding a macro. It points at the
+/// invocation, and the error itself points at the code in the macro.
+#[derive(Debug, Error, Diagnostic)]
+#[error("Error in expansion of macro {name}")]
+#[diagnostic(code(qsc_processor::macro_error), url(docsrs))]
+pub struct MacroError {
+    pub name: String,
+
+    #[source_code]
+    pub src: NamedSource<String>,
+
+    #[label("expanded from here")]
+    pub location: SourceSpan,
+
+    #[related]
+    pub errors: Vec<CompileError>,
+}
@@ -70,3 +70,176 @@ impl AsCharVec for String {
         self.chars().collect()
     }
 }
+
+/// Mangle a path like `util::helper` or an instance of a generic
+/// function like `max<i32>` into a symbol name the linker can handle,
+/// using the Itanium scheme. Plain names are left as they are, so `main`
+/// and C functions keep their own names.
+pub fn mangle(name: &str) -> String {
+    let (path, args) = match name.split_once('<') {
+        Some((path, args)) => (path, args.strip_suffix('>')),
+        None => (name, None),
+    };
+
+    if args.is_none() && !path.contains("::") {
+        return name.to_string();
+    }
+
+    let mut out = String::from("_ZN");
+
+    for seg in path.split("::") {
+        out += &format!("{}{}", seg.len(), seg);
+    }
+
+    if let Some(args) = args {
+        out.push('I');
+
+        for arg in split_generics(args) {
+            out += &mangle_type(arg);
+        }
+
+        out.push('E');
+    }
+
+    out.push('E');
+    out
+}
+
+/// Mangle a type used as a generic argument.
+pub fn mangle_type(ty: &str) -> String {
+    let builtin = match ty {
+        "i8" => "a",
+        "i16" => "s",
+        "i32" => "i",
+        "i64" => "l",
+        "u8" => "h",
+        "u16" => "t",
+        "u32" => "j",
+        "u64" => "m",
+        "f32" => "f",
+        "f64" => "d",
+        "bool" => "b",
+        "char" => "Di",
+        "str" => "Pc",
+        "ptr" => "Pv",
+        _ => "",
+    };
+
+    if !builtin.is_empty() {
+        return builtin.to_string();
+    }
+
+    if let Some((elem, len)) = ty
+        .strip_prefix('[')
+        .and_then(|ty| ty.strip_suffix(']'))
+        .and_then(|ty| ty.rsplit_once("; "))
+    {
+        return format!("A{}_{}", len, mangle_type(elem));
+    }
+
+    if let Some(pointee) = ty.strip_prefix("ptr<").and_then(|ty| ty.strip_suffix('>')) {
+        return format!("P{}", mangle_type(pointee));
+    }
+
+    if let Some((params, ret)) = split_function_type(ty) {
+        let params = if params.is_empty() {
+            "v".to_string()
+        } else {
+            params.into_iter().map(mangle_type).collect()
+        };
+
+        return format!(
+            "PF{}{}E",
+            ret.map(mangle_type).unwrap_or("v".into()),
+            params
+        );
+    }
+
+    // Tuples are mangled like C++'s `std::tuple`.
+    if let Some(elems) = ty.strip_prefix('(').and_then(|ty| ty.strip_suffix(')')) {
+        let elems = split_generics(elems)
+            .into_iter()
+            .map(mangle_type)
+            .collect::<String>();
+
+        return format!("St5tupleIJ{}EE", elems);
+    }
+
+    if ty.contains("::") {
+        return mangle(ty).trim_start_matches("_Z").to_string();
+    }
+
+    format!("{}{}", ty.len(), ty)
+}
+
+/// Split a list of types like `i32, ptr<u8>` at the commas that aren't
+/// nested inside of another type.
+pub fn split_generics(args: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut prev = ' ';
+
+    for (idx, ch) in args.char_indices() {
+        match ch {
+            '<' | '[' | '(' => depth += 1,
+
+            // The `>` in `->` doesn't close anything.
+            '>' if prev == '-' => {}
+
+            '>' | ']' | ')' => depth -= 1,
+
+            ',' if depth == 0 => {
+                res.push(args[start..idx].trim());
+                start = idx + 1;
+            }
+
+            _ => {}
+        }
+
+        prev = ch;
+    }
+
+    if !args[start..].trim().is_empty() {
+        res.push(args[start..].trim());
+    }
+
+    res
+}
+
+/// Split a function type like `fn(i32, i32) -> i32` into its parameter
+/// types and its return type.
+pub fn split_function_type(ty: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let rest = ty.strip_prefix("fn(")?;
+    let mut depth = 1;
+    let mut prev = ' ';
+    let mut end = None;
+
+    for (idx, ch) in rest.char_indices() {
+        match ch {
+            '<' | '[' | '(' => depth += 1,
+            '>' if prev == '-' => {}
+            '>' | ']' | ')' => depth -= 1,
+            _ => {}
+        }
+
+        prev = ch;
+
+        if depth == 0 {
+            end = Some(idx);
+            break;
+        }
+    }
+
+    let end = end?;
+    let params = split_generics(&rest[..end]);
+    let ret = rest[end + 1..].trim();
+
+    let ret = match ret.strip_prefix("->") {
+        Some(ret) => Some(ret.trim()),
+        None if ret.is_empty() => None,
+        None => return None,
+    };
+
+    Some((params, ret))
+}
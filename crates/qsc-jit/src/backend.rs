@@ -260,7 +260,7 @@ impl JITModule {
 
         if path.exists() {
             unsafe {
-                self.lib_handles.push(dlopen(lib.as_ptr(), DLOPEN_FLAG));
+                self.lib_handles.push(dlopen(lib.as_ptr() as _, DLOPEN_FLAG));
             }
         }
     }
@@ -272,7 +272,7 @@ impl JITModule {
         if let Ok(item) = res {
             for file in item {
                 unsafe {
-                    let handle = dlopen(file.as_os_str().to_str().unwrap().as_ptr(), DLOPEN_FLAG);
+                    let handle = dlopen(file.as_os_str().to_str().unwrap().as_ptr() as _, DLOPEN_FLAG);
 
                     self.lib_handles.push(handle);
                 }
@@ -0,0 +1,20 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::expr::array::ArrayExpr;
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn array_lit(&self, pair: Pair<'i, Rule>) -> Result<ArrayExpr> {
+        let values = pair
+            .clone()
+            .into_inner()
+            .map(|pair| self.parse(pair))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ArrayExpr {
+            span: pair.as_span().into(),
+            values,
+        })
+    }
+}
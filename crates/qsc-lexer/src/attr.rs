@@ -0,0 +1,36 @@
+use pest::iterators::Pairs;
+use qsc_ast::ast::node::attr::Attribute;
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    /// Take the attributes off the front of a declaration.
+    pub fn attrs(&self, inner: &mut Pairs<'i, Rule>) -> Result<Vec<Attribute>> {
+        let mut attrs = Vec::new();
+
+        while inner.peek().map(|v| v.as_rule()) == Some(Rule::attr) {
+            let pair = inner.next().unwrap();
+            let mut parts = pair.clone().into_inner();
+            let name = parts.next().unwrap().as_str().trim().to_string();
+
+            let args = parts
+                .next()
+                .map(|args| {
+                    args.into_inner()
+                        .map(|arg| self.parse(arg))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+
+            attrs.push(Attribute {
+                span: pair.as_span().into(),
+                name,
+                args,
+            });
+        }
+
+        Ok(attrs)
+    }
+}
@@ -13,9 +13,10 @@ impl<'i> Lexer {
             .next()
             .map(|pair| {
                 pair.into_inner()
-                    .map(|pair| self.call_arg(pair).unwrap())
-                    .collect::<Vec<_>>()
+                    .map(|pair| self.call_arg(pair))
+                    .collect::<Result<Vec<_>>>()
             })
+            .transpose()?
             .unwrap_or_default();
 
         Ok(CallNode {
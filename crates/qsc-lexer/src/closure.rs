@@ -0,0 +1,83 @@
+use pest::iterators::Pair;
+use qsc_ast::{
+    ast::{
+        expr::closure::{ClosureArgument, ClosureExpr},
+        node::{block::Block, data::NodeData, Node},
+        stmt::{ret::ReturnNode, StatementNode},
+    },
+    span::StaticSpan,
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn closure(&self, pair: Pair<'i, Rule>) -> Result<ClosureExpr> {
+        let mut inner = pair.clone().into_inner();
+        let mut args = Vec::new();
+
+        while inner.peek().map(|v| v.as_rule()) == Some(Rule::closure_param) {
+            args.push(self.closure_param(inner.next().unwrap())?);
+        }
+
+        let ret = if inner.peek().map(|v| v.as_rule()) == Some(Rule::r#type) {
+            Some(self.ty(inner.next().unwrap())?)
+        } else {
+            None
+        };
+
+        let body_pair = inner.next().unwrap();
+
+        // `|x| x + 1` is short for `|x| { return x + 1; }`.
+        let body = if body_pair.as_rule() == Rule::block {
+            Block {
+                span: body_pair.as_span().into(),
+                data: body_pair
+                    .into_inner()
+                    .map(|pair| self.parse(pair))
+                    .collect::<Result<_>>()?,
+            }
+        } else {
+            let span: StaticSpan = body_pair.as_span().into();
+
+            Block {
+                span: span.clone(),
+                data: vec![Node {
+                    span: span.clone(),
+                    data: Box::new(NodeData::Statement(StatementNode::Return(ReturnNode {
+                        span,
+                        value: Some(self.parse(body_pair)?),
+                    }))),
+                }],
+            }
+        };
+
+        Ok(ClosureExpr {
+            span: pair.as_span().into(),
+            args,
+            ret,
+            body,
+            func: String::new(),
+            captures: Vec::new(),
+        })
+    }
+
+    pub fn closure_param(&self, pair: Pair<'i, Rule>) -> Result<ClosureArgument> {
+        let mut inner = pair.clone().into_inner();
+        let mutable = inner.peek().unwrap().as_str().trim() == "mut";
+
+        if mutable {
+            inner.next();
+        }
+
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let type_ = inner.next().map(|pair| self.ty(pair)).transpose()?;
+
+        Ok(ClosureArgument {
+            span: pair.as_span().into(),
+            mutable,
+            name,
+            type_,
+        })
+    }
+}
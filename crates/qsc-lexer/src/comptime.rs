@@ -0,0 +1,19 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::expr::comptime::ComptimeExpr;
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn comptime(&self, pair: Pair<'i, Rule>) -> Result<ComptimeExpr> {
+        let mut inner = pair.clone().into_inner();
+
+        // Skip the `comptime` keyword.
+        inner.next();
+
+        Ok(ComptimeExpr {
+            span: pair.as_span().into(),
+            value: self.parse(inner.next().unwrap())?,
+        })
+    }
+}
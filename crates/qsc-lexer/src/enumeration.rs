@@ -0,0 +1,123 @@
+use pest::iterators::{Pair, Pairs};
+use qsc_ast::ast::{
+    decl::enumeration::{EnumNode, EnumVariant},
+    expr::enumeration::{EnumExpr, MatchArm, MatchExpr, Pattern, VariantPattern},
+    node::sym::SymbolNode,
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn enum_(&self, pair: Pair<'i, Rule>) -> Result<EnumNode> {
+        let mut inner = pair.clone().into_inner();
+        let attrs = self.attrs(&mut inner)?;
+        let vis = self.vis(&mut inner);
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let mut variants = Vec::new();
+
+        for variant in inner {
+            let span = variant.as_span().into();
+            let mut inner = variant.into_inner();
+            let name = inner.next().unwrap().as_str().trim().to_string();
+            let fields = inner
+                .map(|pair| self.ty(pair))
+                .collect::<Result<Vec<_>>>()?;
+
+            variants.push(EnumVariant { span, name, fields });
+        }
+
+        Ok(EnumNode {
+            span: pair.as_span().into(),
+            name,
+            variants,
+            vis,
+            attrs,
+        })
+    }
+
+    /// Values are added by the processor, when a call turns out to be
+    /// an enum literal.
+    pub fn enum_lit(&self, pair: Pair<'i, Rule>) -> Result<EnumExpr> {
+        let (name, variant) = self.variant_path(pair.clone().into_inner());
+
+        Ok(EnumExpr {
+            span: pair.as_span().into(),
+            name,
+            variant,
+            values: Vec::new(),
+        })
+    }
+
+    /// Split a path like `util::Shape::Circle` into the enum's path and
+    /// the variant's name.
+    pub fn variant_path(&self, inner: Pairs<'i, Rule>) -> (String, String) {
+        let mut parts = inner
+            .take_while(|pair| pair.as_rule() == Rule::ident)
+            .map(|pair| pair.as_str().trim().to_string())
+            .collect::<Vec<_>>();
+
+        let variant = parts.pop().unwrap();
+
+        (parts.join("::"), variant)
+    }
+
+    pub fn match_expr(&self, pair: Pair<'i, Rule>) -> Result<MatchExpr> {
+        let mut inner = pair.clone().into_inner();
+        let value = self.parse(inner.next().unwrap())?;
+        let mut arms = Vec::new();
+
+        for arm in inner {
+            let span = arm.as_span().into();
+            let mut inner = arm.into_inner();
+            let pattern = self.pattern(inner.next().unwrap())?;
+            let body = self.parse(inner.next().unwrap())?;
+
+            arms.push(MatchArm {
+                span,
+                pattern,
+                body,
+            });
+        }
+
+        Ok(MatchExpr {
+            span: pair.as_span().into(),
+            value,
+            arms,
+        })
+    }
+
+    pub fn pattern(&self, pair: Pair<'i, Rule>) -> Result<Pattern> {
+        let pair = pair.into_inner().next().unwrap();
+
+        if pair.as_rule() == Rule::wildcard {
+            return Ok(Pattern::Wildcard(pair.as_span().into()));
+        }
+
+        let inner = pair.clone().into_inner();
+        let (name, variant) = self.variant_path(inner.clone());
+
+        let bindings = inner
+            .filter(|pair| pair.as_rule() == Rule::binding)
+            .map(|binding| {
+                let binding = binding.into_inner().next().unwrap();
+
+                match binding.as_rule() {
+                    Rule::wildcard => None,
+
+                    _ => Some(SymbolNode {
+                        span: binding.as_span().into(),
+                        value: binding.as_str().trim().to_string(),
+                    }),
+                }
+            })
+            .collect();
+
+        Ok(Pattern::Variant(VariantPattern {
+            span: pair.as_span().into(),
+            name,
+            variant,
+            bindings,
+        }))
+    }
+}
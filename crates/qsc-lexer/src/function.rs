@@ -1,21 +1,44 @@
 use crate::{lexer::Lexer, parser::Rule};
-use pest::iterators::Pair;
+use pest::iterators::{Pair, Pairs};
 use qsc_ast::ast::{
-    decl::{external::ExternFunctionNode, func::FunctionNode},
-    node::{block::Block, vis::Visibility},
+    decl::{
+        external::ExternFunctionNode,
+        func::{FunctionArgument, FunctionNode},
+    },
+    node::block::Block,
 };
 use qsc_core::error::Result;
 
 impl<'i> Lexer {
     pub fn function(&self, pair: Pair<'i, Rule>) -> Result<FunctionNode> {
         let mut inner = pair.clone().into_inner();
+        let attrs = self.attrs(&mut inner)?;
+        let vis = self.vis(&mut inner);
+        let constant = inner.peek().map(|v| v.as_rule()) == Some(Rule::const_kw);
+
+        if constant {
+            inner.next();
+        }
+
         let name = inner.next().unwrap().as_str().trim().to_string();
 
-        let args = if inner.peek().map(|v| v.as_rule()) == Some(Rule::params) {
-            self.params(inner.next().unwrap())?
-        } else {
-            Vec::new()
-        };
+        let mut generics = Vec::new();
+        let mut bounds = Vec::new();
+
+        if inner.peek().map(|v| v.as_rule()) == Some(Rule::generic_params) {
+            for param in inner.next().unwrap().into_inner() {
+                let mut parts = param.into_inner();
+                let name = parts.next().unwrap().as_str().trim().to_string();
+
+                if let Some(list) = parts.next() {
+                    bounds.extend(self.bounds(&name, list));
+                }
+
+                generics.push(name);
+            }
+        }
+
+        let args = self.method_args(&mut inner)?;
 
         let ret = if inner.peek().map(|v| v.as_rule()) == Some(Rule::r#type) {
             Some(self.ty(inner.next().unwrap())?)
@@ -23,28 +46,59 @@ impl<'i> Lexer {
             None
         };
 
+        if inner.peek().map(|v| v.as_rule()) == Some(Rule::where_clause) {
+            for bound in inner.next().unwrap().into_inner() {
+                let mut parts = bound.into_inner();
+                let name = parts.next().unwrap().as_str().trim().to_string();
+
+                bounds.extend(self.bounds(&name, parts.next().unwrap()));
+            }
+        }
+
         let body_pair = inner.next().unwrap();
 
         let body = Block {
             span: body_pair.as_span().into(),
             data: body_pair
                 .into_inner()
-                .map(|pair| self.parse(pair).unwrap())
-                .collect(),
+                .map(|pair| self.parse(pair))
+                .collect::<Result<_>>()?,
         };
 
         Ok(FunctionNode {
             span: pair.as_span().into(),
             name,
+            generics,
+            bounds,
             args,
             content: body,
             ret,
-            vis: Visibility::Public,
+            vis,
+            attrs,
+            constant,
         })
     }
 
+    /// Parse the arguments of a function, starting with its receiver if it
+    /// has one.
+    pub fn method_args(&self, inner: &mut Pairs<'i, Rule>) -> Result<Vec<FunctionArgument>> {
+        let mut args = Vec::new();
+
+        if inner.peek().map(|v| v.as_rule()) == Some(Rule::receiver) {
+            args.push(self.receiver(inner.next().unwrap()));
+        }
+
+        if inner.peek().map(|v| v.as_rule()) == Some(Rule::params) {
+            args.extend(self.params(inner.next().unwrap())?);
+        }
+
+        Ok(args)
+    }
+
     pub fn extern_(&self, pair: Pair<'i, Rule>) -> Result<ExternFunctionNode> {
         let mut inner = pair.clone().into_inner();
+        let attrs = self.attrs(&mut inner)?;
+        let vis = self.vis(&mut inner);
         let name = inner.next().unwrap().as_str().trim().to_string();
 
         let args = if inner.peek().map(|v| v.as_rule()) == Some(Rule::params) {
@@ -53,6 +107,12 @@ impl<'i> Lexer {
             Vec::new()
         };
 
+        let variadic = inner.peek().map(|v| v.as_rule()) == Some(Rule::variadic);
+
+        if variadic {
+            inner.next();
+        }
+
         let ret = if inner.peek().map(|v| v.as_rule()) == Some(Rule::r#type) {
             Some(self.ty(inner.next().unwrap())?)
         } else {
@@ -64,7 +124,9 @@ impl<'i> Lexer {
             name,
             args,
             ret,
-            vis: Visibility::Public,
+            vis,
+            attrs,
+            variadic,
         })
     }
 }
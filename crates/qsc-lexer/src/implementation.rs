@@ -0,0 +1,71 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::{
+    decl::{
+        func::FunctionArgument,
+        implementation::{ImplNode, SELF_TYPE},
+    },
+    node::ty::TypeNode,
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn impl_(&self, pair: Pair<'i, Rule>) -> Result<ImplNode> {
+        let mut inner = pair.clone().into_inner();
+        let first = inner.next().unwrap().as_str().trim().to_string();
+
+        // `impl Trait for Type` names the trait first.
+        let (name, trait_) = if inner.peek().map(|v| v.as_rule()) == Some(Rule::path) {
+            (
+                inner.next().unwrap().as_str().trim().to_string(),
+                Some(first),
+            )
+        } else {
+            (first, None)
+        };
+
+        let functions = inner
+            .map(|pair| self.function(pair))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ImplNode {
+            span: pair.as_span().into(),
+            name,
+            trait_,
+            functions,
+        })
+    }
+
+    /// Turn a method's receiver into its first argument, which is `self`
+    /// itself, or a pointer to it for `&self` and `&mut self`.
+    pub fn receiver(&self, pair: Pair<'i, Rule>) -> FunctionArgument {
+        let mut by_ref = false;
+        let mut mutable = false;
+
+        for pair in pair.clone().into_inner() {
+            match pair.as_rule() {
+                Rule::receiver_ref => by_ref = true,
+                Rule::r#mut => mutable = true,
+                _ => {}
+            }
+        }
+
+        let name = if by_ref {
+            TypeNode::pointer(SELF_TYPE)
+        } else {
+            SELF_TYPE.to_string()
+        };
+
+        FunctionArgument {
+            span: pair.as_span().into(),
+            mutable,
+            name: "self".to_string(),
+            type_: TypeNode {
+                span: pair.as_span().into(),
+                name,
+                generics: Vec::new(),
+            },
+        }
+    }
+}
@@ -3,11 +3,8 @@ use pest::{iterators::Pair, Parser};
 
 use qsc_ast::ast::{
     decl::DeclarationNode,
-    expr::{unary::UnaryExpr, ExpressionNode},
-    literal::{
-        boolean::BoolNode, char::CharNode, float::FloatNode, int::IntNode, string::StringNode,
-        LiteralNode,
-    },
+    expr::ExpressionNode,
+    literal::{boolean::BoolNode, LiteralNode},
     node::{block::Block, data::NodeData, sym::SymbolNode, Node},
     stmt::{ret::ReturnNode, StatementNode},
     AbstractTree,
@@ -68,7 +65,15 @@ impl<'i> Lexer {
             }
 
             Rule::r#extern => NodeData::Declaration(DeclarationNode::Extern(self.extern_(pair)?)),
-            Rule::binary_op => NodeData::Expr(ExpressionNode::Binary(self.binary_op(pair)?)),
+            Rule::global => NodeData::Declaration(DeclarationNode::Global(self.global(pair)?)),
+            Rule::r#struct => NodeData::Declaration(DeclarationNode::Struct(self.struct_(pair)?)),
+            Rule::r#enum => NodeData::Declaration(DeclarationNode::Enum(self.enum_(pair)?)),
+            Rule::module => NodeData::Declaration(DeclarationNode::Module(self.module(pair)?)),
+            Rule::r#use => NodeData::Declaration(DeclarationNode::Use(self.use_(pair)?)),
+            Rule::impl_block => NodeData::Declaration(DeclarationNode::Impl(self.impl_(pair)?)),
+            Rule::r#trait => NodeData::Declaration(DeclarationNode::Trait(self.trait_(pair)?)),
+            Rule::comptime => NodeData::Expr(ExpressionNode::Comptime(self.comptime(pair)?)),
+            Rule::macro_def => NodeData::Declaration(DeclarationNode::Macro(self.macro_def(pair)?)),
             Rule::call => NodeData::Statement(StatementNode::Call(self.call(pair)?)),
             Rule::var => NodeData::Declaration(DeclarationNode::Variable(self.var(pair)?)),
             Rule::r#type => NodeData::Type(self.ty(pair)?),
@@ -78,39 +83,15 @@ impl<'i> Lexer {
                 value: pair.as_str().trim().to_string(),
             }),
 
-            Rule::unary_op => NodeData::Expr(ExpressionNode::Unary(UnaryExpr {
-                span: pair.as_span().into(),
-                negative: pair.as_str().trim().starts_with("-"),
-                value: self.parse(pair.into_inner().last().unwrap())?,
-            })),
-
             // Redirects
             Rule::literal => self.parse_data(pair.into_inner().next().unwrap())?,
 
             // Primitives (literals)
-            Rule::char => NodeData::Literal(LiteralNode::Char(CharNode {
-                span: pair.as_span().into(),
-                value: self
-                    .interp_literal(pair.as_str().trim().trim_matches('\''))
-                    .chars()
-                    .nth(0)
-                    .unwrap(),
-            })),
-
-            Rule::string => NodeData::Literal(LiteralNode::String(StringNode {
-                span: pair.as_span().into(),
-                value: self.interp_literal(pair.as_str().trim().trim_matches('"')),
-            })),
-
-            Rule::float => NodeData::Literal(LiteralNode::Float(FloatNode {
-                span: pair.as_span().into(),
-                value: pair.as_str().trim().parse().unwrap(),
-            })),
+            Rule::char => self.char(pair)?,
+            Rule::string => self.string(pair)?,
 
-            Rule::int => NodeData::Literal(LiteralNode::Int(IntNode {
-                span: pair.as_span().into(),
-                value: pair.as_str().trim().parse().unwrap(),
-            })),
+            Rule::float => NodeData::Literal(LiteralNode::Float(self.float(pair)?)),
+            Rule::int => NodeData::Literal(LiteralNode::Int(self.int(pair)?)),
 
             Rule::bool => NodeData::Literal(LiteralNode::Bool(BoolNode {
                 span: pair.as_span().into(),
@@ -123,6 +104,23 @@ impl<'i> Lexer {
 
                 match pair.as_rule() {
                     Rule::call => NodeData::Statement(StatementNode::Call(self.call(pair)?)),
+                    Rule::struct_lit => {
+                        NodeData::Expr(ExpressionNode::Struct(self.struct_lit(pair)?))
+                    }
+                    Rule::enum_lit => NodeData::Expr(ExpressionNode::Enum(self.enum_lit(pair)?)),
+                    Rule::array_lit => NodeData::Expr(ExpressionNode::Array(self.array_lit(pair)?)),
+                    Rule::tuple_lit => NodeData::Expr(ExpressionNode::Tuple(self.tuple_lit(pair)?)),
+                    Rule::match_expr => {
+                        NodeData::Expr(ExpressionNode::Match(self.match_expr(pair)?))
+                    }
+                    Rule::closure => NodeData::Expr(ExpressionNode::Closure(self.closure(pair)?)),
+                    Rule::comptime => {
+                        NodeData::Expr(ExpressionNode::Comptime(self.comptime(pair)?))
+                    }
+                    Rule::macro_call => {
+                        NodeData::Expr(ExpressionNode::MacroCall(self.macro_call(pair)?))
+                    }
+                    Rule::spread => NodeData::Expr(ExpressionNode::Spread(self.spread(pair)?)),
                     Rule::literal => self.parse_data(pair)?,
                     Rule::ident => self.parse_data(pair)?,
 
@@ -137,25 +135,7 @@ impl<'i> Lexer {
                 }
             }
 
-            Rule::expr => {
-                let pair = pair.into_inner().next().unwrap();
-
-                match pair.as_rule() {
-                    Rule::term => self.parse_data(pair)?,
-                    Rule::binary_op => {
-                        NodeData::Expr(ExpressionNode::Binary(self.binary_op(pair)?))
-                    }
-
-                    _ => {
-                        return Err(LexerError {
-                            src: self.err_src.clone(),
-                            location: pair.as_span().into_source_span(),
-                            error: miette!("Unsupported pair: {:?}", pair),
-                        }
-                        .into())
-                    }
-                }
-            }
+            Rule::expr => *self.expr(pair)?.data,
 
             Rule::number => {
                 let pair = pair.into_inner().next().unwrap();
@@ -197,10 +177,16 @@ impl<'i> Lexer {
 
                 match pair.as_rule() {
                     Rule::ret => self.parse_data(pair)?,
+                    Rule::destructure => NodeData::Block(self.destructure(pair)?),
                     Rule::var => self.parse_data(pair)?,
                     Rule::expr => self.parse_data(pair)?,
                     Rule::block => self.parse_data(pair)?,
                     Rule::conditional => self.parse_data(pair)?,
+                    Rule::r#while => self.parse_data(pair)?,
+                    Rule::r#for => self.parse_data(pair)?,
+                    Rule::repeat => self.parse_data(pair)?,
+                    Rule::r#break => self.parse_data(pair)?,
+                    Rule::r#continue => self.parse_data(pair)?,
 
                     _ => {
                         return Err(LexerError {
@@ -217,14 +203,20 @@ impl<'i> Lexer {
                 span: pair.as_span().into(),
                 data: pair
                     .into_inner()
-                    .map(|pair| self.parse(pair).unwrap())
-                    .collect::<Vec<_>>(),
+                    .map(|pair| self.parse(pair))
+                    .collect::<Result<_>>()?,
             }),
 
             Rule::conditional => {
                 NodeData::Statement(StatementNode::Condition(self.condition(pair)?))
             }
 
+            Rule::r#while => NodeData::Statement(StatementNode::While(self.while_loop(pair)?)),
+            Rule::r#for => NodeData::Statement(StatementNode::For(self.for_loop(pair)?)),
+            Rule::repeat => NodeData::Statement(StatementNode::Repeat(self.repeat(pair)?)),
+            Rule::r#break => NodeData::Statement(StatementNode::Break(self.break_(pair)?)),
+            Rule::r#continue => NodeData::Statement(StatementNode::Continue(self.continue_(pair)?)),
+
             // Simple ones
             Rule::ret => NodeData::Statement(StatementNode::Return(ReturnNode {
                 span: pair.as_span().into(),
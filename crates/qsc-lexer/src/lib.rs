@@ -1,13 +1,26 @@
 #[macro_use]
 extern crate miette;
 
+pub mod array;
+pub mod attr;
 pub mod call;
+pub mod closure;
+pub mod comptime;
 pub mod cond;
+pub mod enumeration;
 pub mod function;
+pub mod implementation;
 pub mod lexer;
 pub mod literal;
+pub mod loops;
+pub mod macros;
+pub mod module;
 pub mod op;
 pub mod params;
 pub mod parser;
+pub mod structure;
+pub mod traits;
+pub mod tuple;
 pub mod ty;
 pub mod var;
+pub mod vis;
@@ -1,14 +1,244 @@
-use crate::lexer::Lexer;
+use pest::iterators::Pair;
+use qsc_ast::{
+    ast::{
+        expr::{array::ArrayExpr, ExpressionNode},
+        literal::{
+            char::CharNode, float::FloatNode, int::IntNode, string::StringNode, LiteralNode,
+        },
+        node::{data::NodeData, Node},
+    },
+    span::StaticSpan,
+};
+use qsc_core::{
+    conv::IntoSourceSpan,
+    error::{lexer::LexerError, Result},
+};
+
+use crate::{lexer::Lexer, parser::Rule};
 
 impl<'i> Lexer {
-    pub fn interp_literal(&self, val: impl AsRef<str>) -> String {
-        val.as_ref()
-            .replace("\\n", "\n")
-            .replace("\\t", "\t")
-            .replace("\\r", "\r")
-            .replace("\\0", "\0")
-            .replace("\\\\", "\\")
-            .replace("\\'", "'")
-            .replace("\\\"", "\"")
+    /// Decode the body of a string or character literal. `start` is where
+    /// the body begins in the source, so that bad escapes can be pointed
+    /// at. Byte literals only allow ASCII, plus `\x` escapes up to `\xff`.
+    pub fn unescape(&self, body: &str, start: usize, bytes: bool, raw: bool) -> Result<Vec<u32>> {
+        let mut values = Vec::new();
+        let mut chars = body.char_indices().peekable();
+
+        while let Some((idx, ch)) = chars.next() {
+            let value = if ch != '\\' || raw {
+                if bytes && !ch.is_ascii() {
+                    Err(miette!("Byte literals can only contain ASCII characters!"))
+                } else {
+                    Ok(ch as u32)
+                }
+            } else {
+                match chars.next().map(|(_, ch)| ch) {
+                    Some('n') => Ok('\n' as u32),
+                    Some('t') => Ok('\t' as u32),
+                    Some('r') => Ok('\r' as u32),
+                    Some('0') => Ok(0),
+                    Some('\\') => Ok('\\' as u32),
+                    Some('\'') => Ok('\'' as u32),
+                    Some('"') => Ok('"' as u32),
+
+                    Some('x') => {
+                        let hex = (0..2)
+                            .filter_map(|_| chars.next_if(|(_, ch)| ch.is_ascii_hexdigit()))
+                            .map(|(_, ch)| ch)
+                            .collect::<String>();
+
+                        match u32::from_str_radix(&hex, 16) {
+                            Ok(value) if hex.len() == 2 && (bytes || value <= 0x7f) => Ok(value),
+                            Ok(_) if hex.len() == 2 => Err(miette!(
+                                "Escapes in strings can only go up to \\x7f, use \\u{{..}} instead!"
+                            )),
+                            _ => Err(miette!("Expected two hex digits after \\x!")),
+                        }
+                    }
+
+                    Some('u') if !bytes => {
+                        let open = chars.next_if(|(_, ch)| *ch == '{').is_some();
+                        let hex =
+                            std::iter::from_fn(|| chars.next_if(|(_, ch)| ch.is_ascii_hexdigit()))
+                                .map(|(_, ch)| ch)
+                                .collect::<String>();
+                        let close = chars.next_if(|(_, ch)| *ch == '}').is_some();
+
+                        let value = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|_| open && close && hex.len() <= 6);
+
+                        match value {
+                            Some(value) if char::from_u32(value).is_some() => Ok(value),
+
+                            Some(value) => {
+                                Err(miette!("{:X} is not a valid unicode character!", value))
+                            }
+
+                            None => Err(miette!(
+                                "Expected up to six hex digits in braces, like \\u{{1F600}}!"
+                            )),
+                        }
+                    }
+
+                    Some(ch) => Err(miette!("Unknown escape sequence: \\{}", ch)),
+                    None => Err(miette!("Expected an escape sequence after \\!")),
+                }
+            };
+
+            match value {
+                Ok(value) => values.push(value),
+
+                Err(error) => {
+                    let end = chars.peek().map(|(idx, _)| *idx).unwrap_or(body.len());
+
+                    return Err(LexerError {
+                        src: self.err_src.clone(),
+                        location: (start + idx, end - idx).into(),
+                        error,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Strings can be raw (`r"..."`, `r#"..."#`), in which case escapes
+    /// aren't decoded, and byte strings (`b"..."`), which become `[u8; N]`
+    /// arrays.
+    pub fn string(&self, pair: Pair<'i, Rule>) -> Result<NodeData> {
+        let text = pair.as_str();
+        let bytes = text.starts_with('b');
+        let text = text.strip_prefix('b').unwrap_or(text);
+        let raw = text.starts_with('r');
+        let text = text.strip_prefix('r').unwrap_or(text);
+        let hashes = text.len() - text.trim_start_matches('#').len();
+
+        let body = &text[hashes + 1..text.len() - hashes - 1];
+        let start = pair.as_span().start() + bytes as usize + raw as usize + hashes + 1;
+        let values = self.unescape(body, start, bytes, raw)?;
+
+        if !bytes {
+            return Ok(NodeData::Literal(LiteralNode::String(StringNode {
+                span: pair.as_span().into(),
+                value: values.into_iter().filter_map(char::from_u32).collect(),
+            })));
+        }
+
+        let values = values
+            .into_iter()
+            .map(|value| Node {
+                span: pair.as_span().into(),
+                data: Box::new(NodeData::Literal(LiteralNode::Int(IntNode {
+                    span: pair.as_span().into(),
+                    value: value as i64,
+                    type_: Some("u8".to_string()),
+                }))),
+            })
+            .collect();
+
+        Ok(NodeData::Expr(ExpressionNode::Array(ArrayExpr {
+            span: pair.as_span().into(),
+            values,
+        })))
+    }
+
+    /// Byte characters (`b'a'`) are `u8`s.
+    pub fn char(&self, pair: Pair<'i, Rule>) -> Result<NodeData> {
+        let text = pair.as_str();
+        let bytes = text.starts_with('b');
+        let text = text.strip_prefix('b').unwrap_or(text);
+
+        let body = &text[1..text.len() - 1];
+        let start = pair.as_span().start() + bytes as usize + 1;
+        let values = self.unescape(body, start, bytes, false)?;
+
+        if values.len() != 1 {
+            return Err(LexerError {
+                src: self.err_src.clone(),
+                location: pair.as_span().into_source_span(),
+                error: miette!("Character literals must contain exactly one character!"),
+            }
+            .into());
+        }
+
+        Ok(NodeData::Literal(if bytes {
+            LiteralNode::Int(IntNode {
+                span: pair.as_span().into(),
+                value: values[0] as i64,
+                type_: Some("u8".to_string()),
+            })
+        } else {
+            LiteralNode::Char(CharNode {
+                span: pair.as_span().into(),
+                value: char::from_u32(values[0]).unwrap(),
+            })
+        }))
+    }
+
+    pub fn int(&self, pair: Pair<'i, Rule>) -> Result<IntNode> {
+        let text = pair.as_str().trim().replace('_', "");
+
+        // Neither `i` nor `u` are hex digits, so this can't cut into one.
+        let (digits, suffix) = match text.find(['i', 'u']) {
+            Some(idx) => (&text[..idx], Some(text[idx..].to_string())),
+            None => (text.as_str(), None),
+        };
+
+        let (digits, radix) = match digits.get(..2) {
+            Some("0x") => (&digits[2..], 16),
+            Some("0o") => (&digits[2..], 8),
+            Some("0b") => (&digits[2..], 2),
+            _ => (digits, 10),
+        };
+
+        // Anything up to `u64::MAX` is fine, it just wraps around to
+        // negative numbers when it doesn't fit in an `i64`.
+        let value = u64::from_str_radix(digits, radix).map_err(|_| LexerError {
+            src: self.err_src.clone(),
+            location: pair.as_span().into_source_span(),
+            error: miette!("Integer literal is too large: {}", pair.as_str().trim()),
+        })?;
+
+        Ok(IntNode {
+            span: pair.as_span().into(),
+            value: value as i64,
+            type_: suffix,
+        })
+    }
+
+    pub fn float(&self, pair: Pair<'i, Rule>) -> Result<FloatNode> {
+        let text = pair.as_str().trim().replace('_', "");
+
+        let (digits, suffix) = match text.find('f') {
+            Some(idx) => (&text[..idx], Some(text[idx..].to_string())),
+            None => (text.as_str(), None),
+        };
+
+        Ok(FloatNode {
+            span: pair.as_span().into(),
+            value: digits.parse().unwrap(),
+            type_: suffix,
+        })
+    }
+
+    pub fn negate_literal(&self, value: &Node, span: &StaticSpan) -> Option<LiteralNode> {
+        match value.data.as_literal().ok()? {
+            LiteralNode::Int(int) => Some(LiteralNode::Int(IntNode {
+                span: span.clone(),
+                value: int.value.wrapping_neg(),
+                type_: int.type_,
+            })),
+
+            LiteralNode::Float(float) => Some(LiteralNode::Float(FloatNode {
+                span: span.clone(),
+                value: -float.value,
+                type_: float.type_,
+            })),
+
+            _ => None,
+        }
     }
 }
@@ -0,0 +1,66 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::{
+    node::sym::SymbolNode,
+    stmt::loops::{BreakNode, ContinueNode, ForNode, RangeNode, WhileNode},
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn while_loop(&self, pair: Pair<'i, Rule>) -> Result<WhileNode> {
+        let mut inner = pair.clone().into_inner();
+        let condition = self.parse(inner.next().unwrap())?;
+        let block = self.parse_data(inner.next().unwrap())?.as_block()?;
+
+        Ok(WhileNode {
+            span: pair.as_span().into(),
+            condition,
+            block,
+        })
+    }
+
+    pub fn for_loop(&self, pair: Pair<'i, Rule>) -> Result<ForNode> {
+        let mut inner = pair.clone().into_inner();
+        let ident = inner.next().unwrap();
+        let range = self.range(inner.next().unwrap())?;
+        let block = self.parse_data(inner.next().unwrap())?.as_block()?;
+
+        Ok(ForNode {
+            span: pair.as_span().into(),
+            var: SymbolNode {
+                span: ident.as_span().into(),
+                value: ident.as_str().trim().to_string(),
+            },
+            type_: None,
+            range,
+            block,
+        })
+    }
+
+    pub fn range(&self, pair: Pair<'i, Rule>) -> Result<RangeNode> {
+        let mut inner = pair.clone().into_inner();
+        let start = self.parse(inner.next().unwrap())?;
+        let inclusive = inner.next().unwrap().as_str().trim() == "..=";
+        let end = self.parse(inner.next().unwrap())?;
+
+        Ok(RangeNode {
+            span: pair.as_span().into(),
+            start,
+            end,
+            inclusive,
+        })
+    }
+
+    pub fn break_(&self, pair: Pair<'i, Rule>) -> Result<BreakNode> {
+        Ok(BreakNode {
+            span: pair.as_span().into(),
+        })
+    }
+
+    pub fn continue_(&self, pair: Pair<'i, Rule>) -> Result<ContinueNode> {
+        Ok(ContinueNode {
+            span: pair.as_span().into(),
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::{
+    decl::macros::{MacroNode, MacroParam},
+    expr::macros::{MacroCallExpr, SpreadExpr},
+    node::sym::SymbolNode,
+    stmt::loops::RepeatNode,
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn macro_def(&self, pair: Pair<'i, Rule>) -> Result<MacroNode> {
+        let mut inner = pair.clone().into_inner();
+        let vis = self.vis(&mut inner);
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let mut params = Vec::new();
+
+        while inner.peek().map(|v| v.as_rule()) == Some(Rule::macro_param) {
+            let pair = inner.next().unwrap();
+            let mut param = pair.clone().into_inner();
+
+            params.push(MacroParam {
+                span: pair.as_span().into(),
+                name: param.next().unwrap().as_str().trim().to_string(),
+                variadic: param.next().is_some(),
+            });
+        }
+
+        let body = self.parse_data(inner.next().unwrap())?.as_block()?;
+
+        Ok(MacroNode {
+            span: pair.as_span().into(),
+            name,
+            params,
+            body,
+            vis,
+        })
+    }
+
+    pub fn macro_call(&self, pair: Pair<'i, Rule>) -> Result<MacroCallExpr> {
+        let mut inner = pair.clone().into_inner();
+        let name = inner.next().unwrap().as_str().trim().to_string();
+
+        let args = inner
+            .next()
+            .map(|pair| pair.into_inner().map(|pair| self.parse(pair)).collect())
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(MacroCallExpr {
+            span: pair.as_span().into(),
+            name,
+            args,
+            expansion: None,
+        })
+    }
+
+    pub fn spread(&self, pair: Pair<'i, Rule>) -> Result<SpreadExpr> {
+        let ident = pair.clone().into_inner().next().unwrap();
+
+        Ok(SpreadExpr {
+            span: pair.as_span().into(),
+            name: ident.as_str().trim().to_string(),
+        })
+    }
+
+    pub fn repeat(&self, pair: Pair<'i, Rule>) -> Result<RepeatNode> {
+        let mut inner = pair.clone().into_inner();
+        let ident = inner.next().unwrap();
+        let list = inner.next().unwrap();
+        let block = self.parse_data(inner.next().unwrap())?.as_block()?;
+
+        Ok(RepeatNode {
+            span: pair.as_span().into(),
+            var: SymbolNode {
+                span: ident.as_span().into(),
+                value: ident.as_str().trim().to_string(),
+            },
+            list: SymbolNode {
+                span: list.as_span().into(),
+                value: self.spread(list)?.name,
+            },
+            block,
+        })
+    }
+}
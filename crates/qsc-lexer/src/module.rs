@@ -0,0 +1,93 @@
+use std::{fs, path::Path};
+
+use miette::IntoDiagnostic;
+use pest::iterators::Pair;
+use qsc_ast::ast::{
+    decl::{import::UseNode, module::ModuleNode, DeclarationNode},
+    node::{data::NodeData, Node},
+    AbstractTree,
+};
+use qsc_core::{
+    conv::IntoSourceSpan,
+    error::{lexer::LexerError, Result},
+};
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn module(&self, pair: Pair<'i, Rule>) -> Result<ModuleNode> {
+        let mut inner = pair.clone().into_inner();
+        let vis = self.vis(&mut inner);
+        let name = inner.next().unwrap();
+
+        Ok(ModuleNode {
+            span: pair.as_span().into(),
+            name: name.as_str().trim().to_string(),
+            vis,
+            src: Default::default(),
+            data: Vec::new(),
+        })
+    }
+
+    pub fn use_(&self, pair: Pair<'i, Rule>) -> Result<UseNode> {
+        let path = pair.clone().into_inner().next().unwrap();
+
+        Ok(UseNode {
+            span: pair.as_span().into(),
+            path: path.as_str().trim().to_string(),
+        })
+    }
+
+    /// Lex a file, along with the files of all the modules it declares.
+    pub fn lex_file(path: impl AsRef<Path>) -> Result<AbstractTree> {
+        let path = path.as_ref();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(path).into_diagnostic()?;
+
+        let mut lexer = Lexer::new(name, source);
+        let mut tree = lexer.lex()?;
+
+        lexer.load_modules(&mut tree.data, path.parent().unwrap_or(Path::new("")))?;
+
+        Ok(tree)
+    }
+
+    /// Fill in the modules declared in `data`. A module `util` declared by
+    /// a file in `dir` lives in `dir/util.qs` or `dir/util/mod.qs`, and the
+    /// modules it declares itself live in `dir/util/`.
+    pub fn load_modules(&self, data: &mut [Node], dir: &Path) -> Result<()> {
+        for node in data {
+            let NodeData::Declaration(DeclarationNode::Module(module)) = node.data.as_mut() else {
+                continue;
+            };
+
+            let dir = dir.join(&module.name);
+            let files = [dir.with_extension("qs"), dir.join("mod.qs")];
+
+            let Some(file) = files.iter().find(|file| file.is_file()) else {
+                return Err(LexerError {
+                    src: self.err_src.clone(),
+                    location: module.span.into_source_span(),
+                    error: miette!(
+                        "Cannot find module {}, it should be in {} or {}!",
+                        module.name,
+                        files[0].display(),
+                        files[1].display()
+                    ),
+                }
+                .into());
+            };
+
+            let source = fs::read_to_string(file).into_diagnostic()?;
+            let mut lexer = Lexer::new(file.display().to_string(), source);
+            let tree = lexer.lex()?;
+
+            module.data = tree.data;
+            module.src = tree.src;
+
+            lexer.load_modules(&mut module.data, &dir)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,28 @@
-use pest::iterators::Pair;
-use qsc_ast::ast::expr::{binary::BinaryExpr, operator::Operator};
+use std::{str::FromStr, sync::LazyLock};
+
+use pest::{
+    iterators::Pair,
+    pratt_parser::{Assoc, Op, PrattParser},
+};
+
+use qsc_ast::{
+    ast::{
+        expr::{
+            array::IndexExpr,
+            binary::BinaryExpr,
+            cast::CastExpr,
+            method::MethodCallExpr,
+            operator::Operator,
+            pointer::{DerefExpr, RefExpr},
+            structure::FieldExpr,
+            unary::UnaryExpr,
+            ExpressionNode,
+        },
+        node::{data::NodeData, sym::SymbolNode, Node},
+    },
+    span::StaticSpan,
+};
+
 use qsc_core::{
     conv::IntoSourceSpan,
     error::{lexer::LexerError, Result},
@@ -7,220 +30,154 @@ use qsc_core::{
 
 use crate::{lexer::Lexer, parser::Rule};
 
+/// Operator precedence, from the loosest to the tightest binding.
+/// This follows C, with assignment being right-associative and casts
+/// sitting between the binary and the unary operators, like in Rust.
+static PRATT_PARSER: LazyLock<PrattParser<Rule>> = LazyLock::new(|| {
+    PrattParser::new()
+        .op(Op::infix(Rule::assign_op, Assoc::Right))
+        .op(Op::infix(Rule::or_op, Assoc::Left))
+        .op(Op::infix(Rule::and_op, Assoc::Left))
+        .op(Op::infix(Rule::bit_or_op, Assoc::Left))
+        .op(Op::infix(Rule::bit_xor_op, Assoc::Left))
+        .op(Op::infix(Rule::bit_and_op, Assoc::Left))
+        .op(Op::infix(Rule::eq_op, Assoc::Left))
+        .op(Op::infix(Rule::cmp_op, Assoc::Left))
+        .op(Op::infix(Rule::shift_op, Assoc::Left))
+        .op(Op::infix(Rule::add_op, Assoc::Left))
+        .op(Op::infix(Rule::mul_op, Assoc::Left))
+        .op(Op::postfix(Rule::cast))
+        .op(Op::prefix(Rule::unary_op))
+        .op(Op::postfix(Rule::method_call)
+            | Op::postfix(Rule::field_access)
+            | Op::postfix(Rule::index_access))
+});
+
 impl<'i> Lexer {
-    pub fn binary_op(&self, pair: Pair<'i, Rule>) -> Result<BinaryExpr> {
-        let mut inner = pair.clone().into_inner();
-
-        let span = pair.as_span().into();
-        let lhs = self.parse(inner.next().unwrap())?;
-        let op = inner.next().unwrap().as_str().trim().to_string();
-        let rhs = self.parse(inner.next().unwrap())?;
-
-        Ok(match op.as_str() {
-            "+" => BinaryExpr {
-                operator: Operator::Add,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "-" => BinaryExpr {
-                operator: Operator::Subtract,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "*" => BinaryExpr {
-                operator: Operator::Multiply,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "/" => BinaryExpr {
-                operator: Operator::Divide,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "%" => BinaryExpr {
-                operator: Operator::Modulo,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "&&" => BinaryExpr {
-                operator: Operator::And,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "||" => BinaryExpr {
-                operator: Operator::Or,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "^" => BinaryExpr {
-                operator: Operator::BitwiseXor,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "!" => BinaryExpr {
-                operator: Operator::Not,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "&" => BinaryExpr {
-                operator: Operator::BitwiseAnd,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "|" => BinaryExpr {
-                operator: Operator::BitwiseOr,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "~" => BinaryExpr {
-                operator: Operator::BitwiseNot,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "==" => BinaryExpr {
-                operator: Operator::Equal,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "!=" => BinaryExpr {
-                operator: Operator::NotEqual,
-                span,
-                lhs,
-                rhs,
-            },
-
-            ">" => BinaryExpr {
-                operator: Operator::Greater,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "<" => BinaryExpr {
-                operator: Operator::Less,
-                span,
-                lhs,
-                rhs,
-            },
-
-            ">=" => BinaryExpr {
-                operator: Operator::GreaterEqual,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "<=" => BinaryExpr {
-                operator: Operator::LessEqual,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "=>" => BinaryExpr {
-                operator: Operator::Assign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "+=" => BinaryExpr {
-                operator: Operator::AddAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "-=" => BinaryExpr {
-                operator: Operator::SubtractAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "*=" => BinaryExpr {
-                operator: Operator::MultiplyAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "/=" => BinaryExpr {
-                operator: Operator::DivideAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "%=" => BinaryExpr {
-                operator: Operator::ModuloAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "&=" => BinaryExpr {
-                operator: Operator::BitwiseAndAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "|=" => BinaryExpr {
-                operator: Operator::BitwiseOrAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "~=" => BinaryExpr {
-                operator: Operator::BitwiseNotAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            "^=" => BinaryExpr {
-                operator: Operator::BitwiseXorAssign,
-                span,
-                lhs,
-                rhs,
-            },
-
-            val => {
-                return Err(LexerError {
-                    src: self.err_src.clone(),
-                    location: pair.as_span().into_source_span(),
-                    error: miette!("Unsupported operator: {}", val),
+    pub fn expr(&self, pair: Pair<'i, Rule>) -> Result<Node> {
+        PRATT_PARSER
+            .map_primary(|pair| self.parse(pair))
+            .map_prefix(|op, value| {
+                let value = value?;
+                let span = StaticSpan::new(
+                    value.span.input.clone(),
+                    op.as_span().start(),
+                    value.span.end,
+                );
+
+                // Negative numbers are folded into the literal, so that
+                // their range can be checked as a whole, like `-128i8`.
+                if op.as_str().trim() == "-" {
+                    if let Some(lit) = self.negate_literal(&value, &span) {
+                        return Ok(Node {
+                            span,
+                            data: Box::new(NodeData::Literal(lit)),
+                        });
+                    }
                 }
-                .into())
+
+                let expr = match op.as_str().trim() {
+                    "&" => ExpressionNode::Ref(RefExpr {
+                        span: span.clone(),
+                        value,
+                    }),
+
+                    "*" => ExpressionNode::Deref(DerefExpr {
+                        span: span.clone(),
+                        value,
+                    }),
+
+                    _ => ExpressionNode::Unary(UnaryExpr {
+                        operator: self.operator(&op)?,
+                        span: span.clone(),
+                        value,
+                    }),
+                };
+
+                Ok(Node {
+                    span,
+                    data: Box::new(NodeData::Expr(expr)),
+                })
+            })
+            .map_postfix(|value, op| {
+                let value = value?;
+                let inner = op.clone().into_inner().next().unwrap();
+                let span = StaticSpan::new(
+                    value.span.input.clone(),
+                    value.span.start,
+                    op.as_span().end(),
+                );
+
+                let expr = match op.as_rule() {
+                    Rule::cast => ExpressionNode::Cast(CastExpr {
+                        span: span.clone(),
+                        value,
+                        type_: self.ty(op.clone().into_inner().nth(1).unwrap())?,
+                    }),
+
+                    Rule::index_access => ExpressionNode::Index(IndexExpr {
+                        span: span.clone(),
+                        value,
+                        index: self.parse(inner)?,
+                    }),
+
+                    Rule::method_call => ExpressionNode::Method(MethodCallExpr {
+                        span: span.clone(),
+                        value,
+                        method: SymbolNode {
+                            span: inner.as_span().into(),
+                            value: inner.as_str().trim().to_string(),
+                        },
+                        args: op
+                            .clone()
+                            .into_inner()
+                            .nth(1)
+                            .map(|pair| pair.into_inner().map(|pair| self.call_arg(pair)).collect())
+                            .transpose()?
+                            .unwrap_or_default(),
+                    }),
+
+                    _ => ExpressionNode::Field(FieldExpr {
+                        span: span.clone(),
+                        value,
+                        field: SymbolNode {
+                            span: inner.as_span().into(),
+                            value: inner.as_str().trim().to_string(),
+                        },
+                    }),
+                };
+
+                Ok(Node {
+                    span,
+                    data: Box::new(NodeData::Expr(expr)),
+                })
+            })
+            .map_infix(|lhs, op, rhs| {
+                let lhs = lhs?;
+                let rhs = rhs?;
+                let span = StaticSpan::new(lhs.span.input.clone(), lhs.span.start, rhs.span.end);
+
+                Ok(Node {
+                    span: span.clone(),
+                    data: Box::new(NodeData::Expr(ExpressionNode::Binary(BinaryExpr {
+                        operator: self.operator(&op)?,
+                        span,
+                        lhs,
+                        rhs,
+                    }))),
+                })
+            })
+            .parse(pair.into_inner())
+    }
+
+    pub fn operator(&self, pair: &Pair<'i, Rule>) -> Result<Operator> {
+        Operator::from_str(pair.as_str().trim()).map_err(|_| {
+            LexerError {
+                src: self.err_src.clone(),
+                location: pair.as_span().into_source_span(),
+                error: miette!("Unsupported operator: {}", pair.as_str().trim()),
             }
+            .into()
         })
     }
 }
@@ -0,0 +1,56 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::{
+    decl::structure::{StructField, StructNode},
+    expr::structure::{StructExpr, StructFieldValue},
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn struct_(&self, pair: Pair<'i, Rule>) -> Result<StructNode> {
+        let mut inner = pair.clone().into_inner();
+        let attrs = self.attrs(&mut inner)?;
+        let vis = self.vis(&mut inner);
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let mut fields = Vec::new();
+
+        for field in inner {
+            let span = field.as_span().into();
+            let mut inner = field.into_inner();
+            let name = inner.next().unwrap().as_str().trim().to_string();
+            let type_ = self.ty(inner.next().unwrap())?;
+
+            fields.push(StructField { span, name, type_ });
+        }
+
+        Ok(StructNode {
+            span: pair.as_span().into(),
+            name,
+            fields,
+            vis,
+            attrs,
+        })
+    }
+
+    pub fn struct_lit(&self, pair: Pair<'i, Rule>) -> Result<StructExpr> {
+        let mut inner = pair.clone().into_inner();
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let mut fields = Vec::new();
+
+        for field in inner {
+            let span = field.as_span().into();
+            let mut inner = field.into_inner();
+            let name = inner.next().unwrap().as_str().trim().to_string();
+            let value = self.parse(inner.next().unwrap())?;
+
+            fields.push(StructFieldValue { span, name, value });
+        }
+
+        Ok(StructExpr {
+            span: pair.as_span().into(),
+            name,
+            fields,
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use pest::iterators::Pair;
+use qsc_ast::ast::{
+    decl::traits::{TraitBound, TraitFunction, TraitNode},
+    node::block::Block,
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn trait_(&self, pair: Pair<'i, Rule>) -> Result<TraitNode> {
+        let mut inner = pair.clone().into_inner();
+        let attrs = self.attrs(&mut inner)?;
+        let vis = self.vis(&mut inner);
+        let name = inner.next().unwrap().as_str().trim().to_string();
+
+        let functions = inner
+            .map(|pair| self.trait_fn(pair))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TraitNode {
+            span: pair.as_span().into(),
+            name,
+            functions,
+            vis,
+            attrs,
+        })
+    }
+
+    pub fn trait_fn(&self, pair: Pair<'i, Rule>) -> Result<TraitFunction> {
+        let mut inner = pair.clone().into_inner();
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let args = self.method_args(&mut inner)?;
+
+        let ret = if inner.peek().map(|v| v.as_rule()) == Some(Rule::r#type) {
+            Some(self.ty(inner.next().unwrap())?)
+        } else {
+            None
+        };
+
+        let default = inner
+            .next()
+            .map(|pair| -> Result<Block> {
+                Ok(Block {
+                    span: pair.as_span().into(),
+                    data: pair
+                        .into_inner()
+                        .map(|pair| self.parse(pair))
+                        .collect::<Result<_>>()?,
+                })
+            })
+            .transpose()?;
+
+        Ok(TraitFunction {
+            span: pair.as_span().into(),
+            name,
+            args,
+            ret,
+            default,
+        })
+    }
+
+    /// Turn a list of bounds like `Display + Clone` on `param` into one
+    /// bound for each trait.
+    pub fn bounds(&self, param: &str, pair: Pair<'i, Rule>) -> Vec<TraitBound> {
+        pair.into_inner()
+            .map(|pair| TraitBound {
+                span: pair.as_span().into(),
+                param: param.to_string(),
+                trait_: pair.as_str().trim().to_string(),
+            })
+            .collect()
+    }
+}
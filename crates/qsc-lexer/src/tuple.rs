@@ -0,0 +1,108 @@
+use pest::iterators::Pair;
+use qsc_ast::{
+    ast::{
+        decl::{var::VariableNode, DeclarationNode},
+        expr::{structure::FieldExpr, tuple::TupleExpr, ExpressionNode},
+        node::{block::Block, data::NodeData, sym::SymbolNode, Node},
+    },
+    span::StaticSpan,
+};
+use qsc_core::error::Result;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    pub fn tuple_lit(&self, pair: Pair<'i, Rule>) -> Result<TupleExpr> {
+        let values = pair
+            .clone()
+            .into_inner()
+            .map(|pair| self.parse(pair))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(TupleExpr {
+            span: pair.as_span().into(),
+            values,
+        })
+    }
+
+    /// `let (a, b) = value;` is the same as binding the value to a hidden
+    /// variable, and then each name to one of its elements.
+    pub fn destructure(&self, pair: Pair<'i, Rule>) -> Result<Block> {
+        let span: StaticSpan = pair.as_span().into();
+        let mut inner = pair.clone().into_inner();
+        let pattern = inner.next().unwrap();
+
+        let type_ = if inner
+            .peek()
+            .map(|pair| pair.as_rule() == Rule::r#type)
+            .unwrap_or(false)
+        {
+            Some(self.ty(inner.next().unwrap())?)
+        } else {
+            None
+        };
+
+        let value = self.parse(inner.next().unwrap())?;
+        let tuple = format!("__tuple{}", span.start);
+        let node = |span: &StaticSpan, data| Node {
+            span: span.clone(),
+            data: Box::new(data),
+        };
+
+        let mut data = vec![node(
+            &span,
+            NodeData::Declaration(DeclarationNode::Variable(VariableNode {
+                span: span.clone(),
+                name: tuple.clone(),
+                type_,
+                value: Some(value),
+                mutable: false,
+            })),
+        )];
+
+        for (idx, binding) in pattern.into_inner().enumerate() {
+            let binding_span: StaticSpan = binding.as_span().into();
+            let mut inner = binding.into_inner();
+
+            if inner.peek().unwrap().as_rule() == Rule::wildcard {
+                continue;
+            }
+
+            let mutable = inner.peek().unwrap().as_rule() == Rule::r#mut;
+
+            if mutable {
+                inner.next();
+            }
+
+            let name = inner.next().unwrap().as_str().trim().to_string();
+
+            let field = NodeData::Expr(ExpressionNode::Field(FieldExpr {
+                span: binding_span.clone(),
+                value: node(
+                    &binding_span,
+                    NodeData::Symbol(SymbolNode {
+                        span: binding_span.clone(),
+                        value: tuple.clone(),
+                    }),
+                ),
+                field: SymbolNode {
+                    span: binding_span.clone(),
+                    value: idx.to_string(),
+                },
+            }));
+
+            data.push(node(
+                &binding_span,
+                NodeData::Declaration(DeclarationNode::Variable(VariableNode {
+                    span: binding_span.clone(),
+                    name,
+                    type_: None,
+                    value: Some(node(&binding_span, field)),
+                    mutable,
+                })),
+            ));
+        }
+
+        Ok(Block { span, data })
+    }
+}
@@ -1,5 +1,5 @@
 use pest::iterators::Pair;
-use qsc_ast::ast::node::ty::TypeNode;
+use qsc_ast::ast::node::{sym::SymbolNode, ty::TypeNode};
 use qsc_core::error::Result;
 
 use crate::{lexer::Lexer, parser::Rule};
@@ -7,19 +7,79 @@ use crate::{lexer::Lexer, parser::Rule};
 impl<'i> Lexer {
     pub fn ty(&self, pair: Pair<'i, Rule>) -> Result<TypeNode> {
         let mut inner = pair.clone().into_inner();
-        let name = inner.next().unwrap().as_str().trim();
-        let mut generics = Vec::new();
+        let first = inner.next().unwrap();
 
-        if let Some(pair) = inner.next() {
-            for pair in pair.into_inner() {
-                let pair = self.parse(pair)?;
+        if first.as_rule() == Rule::array_type {
+            let mut inner = first.into_inner();
+            let elem = self.ty(inner.next().unwrap())?;
+            let len = inner.next().unwrap();
 
-                if let Ok(sym) = pair.data.as_symbol() {
-                    generics.push(sym);
+            // The length can also be the name of a constant, which is
+            // filled in once it has been evaluated.
+            let name = if len.as_rule() == Rule::int {
+                TypeNode::array(&elem.as_str(), self.int(len)?.value as usize)
+            } else {
+                format!("[{}; {}]", elem.as_str(), len.as_str().trim())
+            };
+
+            return Ok(TypeNode {
+                span: pair.as_span().into(),
+                name,
+                generics: Vec::new(),
+            });
+        }
+
+        if first.as_rule() == Rule::fn_type {
+            let mut params = Vec::new();
+            let mut ret = None;
+
+            for pair in first.into_inner() {
+                if pair.as_rule() == Rule::fn_ret {
+                    ret = Some(self.ty(pair.into_inner().next().unwrap())?.as_str());
+                } else {
+                    params.push(self.ty(pair)?.as_str());
                 }
             }
+
+            return Ok(TypeNode {
+                span: pair.as_span().into(),
+                name: TypeNode::function(&params, ret.as_deref()),
+                generics: Vec::new(),
+            });
         }
 
+        if first.as_rule() == Rule::tuple_type {
+            let elems = first
+                .into_inner()
+                .map(|pair| Ok(self.ty(pair)?.as_str()))
+                .collect::<Result<Vec<_>>>()?;
+
+            return Ok(TypeNode {
+                span: pair.as_span().into(),
+                name: TypeNode::tuple(&elems),
+                generics: Vec::new(),
+            });
+        }
+
+        // `*T` is just another way to write `ptr<T>`.
+        let (name, generic) = if first.as_rule() == Rule::pointer_type {
+            ("ptr", first.into_inner().next())
+        } else {
+            (first.as_str().trim(), inner.next())
+        };
+
+        let generics = generic
+            .map(|pair| {
+                let ty = self.ty(pair.clone())?;
+
+                Ok(SymbolNode {
+                    span: pair.as_span().into(),
+                    value: ty.as_str(),
+                })
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
         Ok(TypeNode {
             span: pair.as_span().into(),
             name: name.to_string(),
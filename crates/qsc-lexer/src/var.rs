@@ -1,5 +1,5 @@
 use pest::iterators::Pair;
-use qsc_ast::ast::decl::var::VariableNode;
+use qsc_ast::ast::decl::{global::GlobalVariable, var::VariableNode};
 use qsc_core::error::Result;
 
 use crate::{lexer::Lexer, parser::Rule};
@@ -29,7 +29,7 @@ impl<'i> Lexer {
             None
         };
 
-        let value = inner.next().map(|pair| self.parse(pair).unwrap());
+        let value = inner.next().map(|pair| self.parse(pair)).transpose()?;
 
         Ok(VariableNode {
             span: pair.as_span().into(),
@@ -39,4 +39,35 @@ impl<'i> Lexer {
             mutable,
         })
     }
+
+    pub fn global(&self, pair: Pair<'i, Rule>) -> Result<GlobalVariable> {
+        let mut inner = pair.clone().into_inner();
+        let attrs = self.attrs(&mut inner)?;
+        let vis = self.vis(&mut inner);
+        let constant = inner.next().unwrap().as_str().trim() == "const";
+
+        let mutable = inner
+            .peek()
+            .map(|pair| pair.as_rule() == Rule::r#mut)
+            .unwrap_or(false);
+
+        if mutable {
+            inner.next();
+        }
+
+        let name = inner.next().unwrap().as_str().trim().to_string();
+        let type_ = self.ty(inner.next().unwrap())?;
+        let value = self.parse(inner.next().unwrap())?;
+
+        Ok(GlobalVariable {
+            span: pair.as_span().into(),
+            name,
+            type_,
+            value,
+            vis,
+            attrs,
+            mutable,
+            constant,
+        })
+    }
 }
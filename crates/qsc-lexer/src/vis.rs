@@ -0,0 +1,18 @@
+use std::str::FromStr;
+
+use pest::iterators::Pairs;
+use qsc_ast::ast::node::vis::Visibility;
+
+use crate::{lexer::Lexer, parser::Rule};
+
+impl<'i> Lexer {
+    /// Take the visibility off the front of a declaration. Declarations
+    /// without one are private to their module.
+    pub fn vis(&self, inner: &mut Pairs<'i, Rule>) -> Visibility {
+        if inner.peek().map(|v| v.as_rule()) != Some(Rule::vis) {
+            return Visibility::default();
+        }
+
+        Visibility::from_str(inner.next().unwrap().as_str()).unwrap()
+    }
+}
@@ -13,7 +13,7 @@ use serde_json::Value;
 use tower_lsp::{jsonrpc::Result, lsp_types::*, Client, LanguageServer};
 
 use crate::{
-    completion::{completion, LangCompletionItem},
+    completion::{completion, member_completion, LangCompletionItem},
     refs::get_reference,
     util::offset_to_position,
 };
@@ -237,19 +237,7 @@ impl LanguageServer for Backend {
 
         let inlay_hint_list = hashmap
             .into_iter()
-            .map(|(k, v)| {
-                (
-                    k.start,
-                    k.end,
-                    match v {
-                        LiteralNode::Bool(_) => "bool".to_string(),
-                        LiteralNode::Int(_) => "i32".to_string(),
-                        LiteralNode::Float(_) => "f32".to_string(),
-                        LiteralNode::String(_) => "str".to_string(),
-                        LiteralNode::Char(_) => "char".to_string(),
-                    },
-                )
-            })
+            .map(|(k, v)| (k.start, k.end, v.get_type().unwrap_or_default()))
             .filter_map(|item| {
                 // let start_position = offset_to_position(item.0, document)?;
                 let end_position = offset_to_position(item.1, &document)?;
@@ -293,7 +281,24 @@ impl LanguageServer for Backend {
             let ast = self.ast_map.get(&uri.to_string())?;
             let char = rope.try_line_to_char(position.line as usize).ok()?;
             let offset = char + position.character as usize;
-            let completions = completion(&ast, offset);
+
+            // After a `.`, only the fields and methods of the value before
+            // it make sense.
+            let completions = if offset > 0 && rope.get_char(offset - 1) == Some('.') {
+                let receiver = rope
+                    .slice(..offset - 1)
+                    .chars_at(offset - 1)
+                    .reversed()
+                    .take_while(|ch| ch.is_alphanumeric() || *ch == '_')
+                    .collect::<String>()
+                    .chars()
+                    .rev()
+                    .collect::<String>();
+
+                member_completion(&ast, &receiver, offset)
+            } else {
+                completion(&ast, offset)
+            };
             let mut ret = Vec::with_capacity(completions.len());
 
             for (_, item) in completions {
@@ -308,6 +313,16 @@ impl LanguageServer for Backend {
                         });
                     }
 
+                    LangCompletionItem::Field(field) => {
+                        ret.push(CompletionItem {
+                            label: field.clone(),
+                            insert_text: Some(field.clone()),
+                            kind: Some(CompletionItemKind::FIELD),
+                            detail: Some(field),
+                            ..Default::default()
+                        });
+                    }
+
                     LangCompletionItem::Function(name, args) => {
                         ret.push(CompletionItem {
                             label: name.clone(),
@@ -1,9 +1,20 @@
 use im_rc::HashMap;
 use qsc_ast::ast::{
-    decl::{var::VariableNode, DeclarationNode},
+    decl::{
+        func::FunctionNode,
+        implementation::{ImplNode, SELF_TYPE},
+        var::VariableNode,
+        DeclarationNode,
+    },
     expr::{binary::BinaryExpr, ExpressionNode},
-    node::{data::NodeData, sym::SymbolNode},
-    stmt::{call::CallNode, cond::ConditionalNode, ret::ReturnNode, StatementNode},
+    node::{data::NodeData, sym::SymbolNode, ty::TypeNode},
+    stmt::{
+        call::CallNode,
+        cond::ConditionalNode,
+        loops::{ForNode, RepeatNode, WhileNode},
+        ret::ReturnNode,
+        StatementNode,
+    },
     AbstractTree,
 };
 
@@ -13,6 +24,7 @@ use crate::util::Spanned;
 pub enum LangCompletionItem {
     Variable(String),
     Function(String, Vec<String>),
+    Field(String),
 }
 
 pub fn completion(ast: &AbstractTree, ident_offset: usize) -> HashMap<String, LangCompletionItem> {
@@ -51,6 +63,115 @@ pub fn completion(ast: &AbstractTree, ident_offset: usize) -> HashMap<String, La
     map
 }
 
+/// Complete the fields and methods of `receiver` after a `.`, using the
+/// type it has in the function around `ident_offset`.
+pub fn member_completion(
+    ast: &AbstractTree,
+    receiver: &str,
+    ident_offset: usize,
+) -> HashMap<String, LangCompletionItem> {
+    let mut map = HashMap::new();
+
+    let Some(ty) = receiver_type(ast, receiver, ident_offset) else {
+        return map;
+    };
+
+    if let Some(item) = ast.structs().get(&ty) {
+        for field in &item.fields {
+            map.insert(
+                field.name.clone(),
+                LangCompletionItem::Field(field.name.clone()),
+            );
+        }
+    }
+
+    for func in methods_of(ast, &ty) {
+        if func.args.first().is_some_and(|arg| arg.name == "self") {
+            map.insert(
+                func.name.clone(),
+                LangCompletionItem::Function(
+                    func.name.clone(),
+                    func.args
+                        .iter()
+                        .skip(1)
+                        .map(|arg| arg.name.clone())
+                        .collect(),
+                ),
+            );
+        }
+    }
+
+    map
+}
+
+/// Find the type of a variable in the function around `ident_offset`,
+/// without any pointers around it.
+fn receiver_type(ast: &AbstractTree, receiver: &str, ident_offset: usize) -> Option<String> {
+    let mut funcs = ast
+        .functions()
+        .into_values()
+        .map(|func| (None, func))
+        .collect::<Vec<_>>();
+
+    for node in &ast.data {
+        if let Ok(DeclarationNode::Impl(ImplNode {
+            name, functions, ..
+        })) = node.data.as_decl()
+        {
+            funcs.extend(functions.into_iter().map(|func| (Some(name.clone()), func)));
+        }
+    }
+
+    let (owner, func) = funcs
+        .into_iter()
+        .find(|(_, func)| func.span.start < ident_offset && func.span.end > ident_offset)?;
+
+    let var = func.variables().get(receiver).cloned()?;
+
+    let ty = match (var.type_, var.value) {
+        (Some(ty), _) => ty.as_str(),
+
+        (None, Some(value)) => match &*value.data {
+            NodeData::Expr(ExpressionNode::Struct(expr)) => expr.name.clone(),
+            data => data.get_type(&Some(func.name.clone()), ast).ok()?,
+        },
+
+        (None, None) => return None,
+    };
+
+    let ty = TypeNode::pointee(&ty).unwrap_or(&ty).to_string();
+
+    match owner {
+        Some(owner) if ty == SELF_TYPE => Some(owner),
+        _ => Some(ty),
+    }
+}
+
+/// Find the functions implemented for `ty`, either still in their `impl`
+/// blocks or already named like `Type::method`.
+fn methods_of(ast: &AbstractTree, ty: &str) -> Vec<FunctionNode> {
+    let mut methods = Vec::new();
+
+    for node in &ast.data {
+        match node.data.as_decl() {
+            Ok(DeclarationNode::Impl(block)) if block.name == ty => {
+                methods.extend(block.functions);
+            }
+
+            Ok(DeclarationNode::Function(mut func)) => {
+                if let Some(name) = func.name.strip_prefix(&format!("{}::", ty)) {
+                    func.name = name.to_string();
+                    methods.push(func);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    methods
+}
+
 pub fn get_completion_of(
     expr: &Spanned<NodeData>,
     definition_map: &mut HashMap<String, LangCompletionItem>,
@@ -189,6 +310,75 @@ pub fn get_completion_of(
                 }
             }
 
+            StatementNode::While(WhileNode {
+                condition,
+                block,
+                span: _,
+            }) => {
+                match get_completion_of(
+                    &(
+                        Box::into_inner(condition.data.clone()),
+                        condition.span.clone(),
+                    ),
+                    definition_map,
+                    ident_offset,
+                ) {
+                    true => {}
+                    false => return false,
+                }
+
+                get_completion_of(
+                    &(NodeData::Block(block.clone()), block.span.clone()),
+                    definition_map,
+                    ident_offset,
+                )
+            }
+
+            StatementNode::For(ForNode {
+                var,
+                type_: _,
+                range,
+                block,
+                span: _,
+            }) => {
+                definition_map.insert(
+                    var.value.clone(),
+                    LangCompletionItem::Variable(var.value.clone()),
+                );
+
+                for bound in [&range.start, &range.end] {
+                    match get_completion_of(
+                        &(Box::into_inner(bound.data.clone()), bound.span.clone()),
+                        definition_map,
+                        ident_offset,
+                    ) {
+                        true => continue,
+                        false => return false,
+                    }
+                }
+
+                get_completion_of(
+                    &(NodeData::Block(block.clone()), block.span.clone()),
+                    definition_map,
+                    ident_offset,
+                )
+            }
+
+            StatementNode::Repeat(RepeatNode { var, block, .. }) => {
+                definition_map.insert(
+                    var.value.clone(),
+                    LangCompletionItem::Variable(var.value.clone()),
+                );
+
+                get_completion_of(
+                    &(NodeData::Block(block.clone()), block.span.clone()),
+                    definition_map,
+                    ident_offset,
+                )
+            }
+
+            StatementNode::Break(_) | StatementNode::Continue(_) => true,
+
             StatementNode::Return(ReturnNode { span: _, value }) => {
                 if let Some(value) = value {
                     get_completion_of(
@@ -1,9 +1,28 @@
 use im_rc::Vector;
 use qsc_ast::ast::{
     decl::{var::VariableNode, DeclarationNode},
-    expr::{binary::BinaryExpr, unary::UnaryExpr, ExpressionNode},
+    expr::{
+        array::{ArrayExpr, IndexExpr},
+        binary::BinaryExpr,
+        cast::CastExpr,
+        closure::ClosureExpr,
+        comptime::ComptimeExpr,
+        enumeration::{EnumExpr, MatchExpr, VariantFieldExpr},
+        macros::MacroCallExpr,
+        method::MethodCallExpr,
+        pointer::{DerefExpr, RefExpr},
+        structure::{FieldExpr, StructExpr},
+        tuple::TupleExpr,
+        unary::UnaryExpr,
+        ExpressionNode,
+    },
     node::{data::NodeData, sym::SymbolNode},
-    stmt::{call::CallNode, cond::ConditionalNode, StatementNode},
+    stmt::{
+        call::CallNode,
+        cond::ConditionalNode,
+        loops::{ForNode, WhileNode},
+        StatementNode,
+    },
     AbstractTree,
 };
 
@@ -200,7 +219,7 @@ pub fn get_reference_of_expr(
 
             ExpressionNode::Unary(UnaryExpr {
                 value,
-                negative: _,
+                operator: _,
                 span: _,
             }) => get_reference_of_expr(
                 ast,
@@ -210,6 +229,169 @@ pub fn get_reference_of_expr(
                 reference_list,
                 include_self,
             ),
+
+            ExpressionNode::Struct(StructExpr { fields, .. }) => {
+                for field in fields {
+                    get_reference_of_expr(
+                        ast,
+                        &(
+                            Box::into_inner(field.value.data.clone()),
+                            field.value.span.clone(),
+                        ),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::Array(ArrayExpr { values, .. })
+            | ExpressionNode::Tuple(TupleExpr { values, .. }) => {
+                for value in values {
+                    get_reference_of_expr(
+                        ast,
+                        &(Box::into_inner(value.data.clone()), value.span.clone()),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::Ref(RefExpr { value, .. })
+            | ExpressionNode::Deref(DerefExpr { value, .. })
+            | ExpressionNode::Cast(CastExpr { value, .. }) => get_reference_of_expr(
+                ast,
+                &(Box::into_inner(value.data.clone()), value.span.clone()),
+                definition_ass_list.clone(),
+                reference_symbol.clone(),
+                reference_list,
+                include_self,
+            ),
+
+            ExpressionNode::Index(IndexExpr { value, index, .. }) => {
+                get_reference_of_expr(
+                    ast,
+                    &(Box::into_inner(value.data.clone()), value.span.clone()),
+                    definition_ass_list.clone(),
+                    reference_symbol.clone(),
+                    reference_list,
+                    include_self,
+                );
+
+                get_reference_of_expr(
+                    ast,
+                    &(Box::into_inner(index.data.clone()), index.span.clone()),
+                    definition_ass_list,
+                    reference_symbol,
+                    reference_list,
+                    include_self,
+                );
+            }
+
+            ExpressionNode::Enum(EnumExpr { values, .. }) => {
+                for value in values {
+                    get_reference_of_expr(
+                        ast,
+                        &(Box::into_inner(value.data.clone()), value.span.clone()),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::Match(MatchExpr { value, arms, .. }) => {
+                get_reference_of_expr(
+                    ast,
+                    &(Box::into_inner(value.data.clone()), value.span.clone()),
+                    definition_ass_list.clone(),
+                    reference_symbol.clone(),
+                    reference_list,
+                    include_self,
+                );
+
+                for arm in arms {
+                    get_reference_of_expr(
+                        ast,
+                        &(
+                            Box::into_inner(arm.body.data.clone()),
+                            arm.body.span.clone(),
+                        ),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::Method(MethodCallExpr { value, args, .. }) => {
+                get_reference_of_expr(
+                    ast,
+                    &(Box::into_inner(value.data.clone()), value.span.clone()),
+                    definition_ass_list.clone(),
+                    reference_symbol.clone(),
+                    reference_list,
+                    include_self,
+                );
+
+                for arg in args {
+                    get_reference_of_expr(
+                        ast,
+                        &(
+                            Box::into_inner(arg.value.data.clone()),
+                            arg.value.span.clone(),
+                        ),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::MacroCall(MacroCallExpr { args, .. }) => {
+                for arg in args {
+                    get_reference_of_expr(
+                        ast,
+                        &(Box::into_inner(arg.data.clone()), arg.span.clone()),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::Spread(_) => {}
+
+            ExpressionNode::Closure(ClosureExpr { body, .. }) => {
+                for node in &body.data {
+                    get_reference_of_expr(
+                        ast,
+                        &(Box::into_inner(node.data.clone()), node.span.clone()),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+            }
+
+            ExpressionNode::Field(FieldExpr { value, .. })
+            | ExpressionNode::VariantField(VariantFieldExpr { value, .. })
+            | ExpressionNode::Comptime(ComptimeExpr { value, .. }) => get_reference_of_expr(
+                ast,
+                &(Box::into_inner(value.data.clone()), value.span.clone()),
+                definition_ass_list.clone(),
+                reference_symbol.clone(),
+                reference_list,
+                include_self,
+            ),
         },
 
         NodeData::Statement(expr) => match expr {
@@ -280,6 +462,78 @@ pub fn get_reference_of_expr(
                 }
             }
 
+            StatementNode::For(ForNode {
+                var,
+                type_: _,
+                range,
+                block,
+                span: _,
+            }) => {
+                for bound in [&range.start, &range.end] {
+                    get_reference_of_expr(
+                        ast,
+                        &(Box::into_inner(bound.data.clone()), bound.span.clone()),
+                        definition_ass_list.clone(),
+                        reference_symbol.clone(),
+                        reference_list,
+                        include_self,
+                    );
+                }
+
+                let new_decl = Vector::unit((var.value.clone(), var.span.clone()));
+
+                let next_symbol = match reference_symbol {
+                    ReferenceSymbol::Founding(ident)
+                        if ident >= var.span.start && ident < var.span.end =>
+                    {
+                        let spanned_name = (var.value.clone(), var.span.clone());
+
+                        if include_self {
+                            reference_list.push(spanned_name.clone());
+                        }
+
+                        ReferenceSymbol::Founded(spanned_name)
+                    }
+                    _ => reference_symbol,
+                };
+
+                get_reference_of_expr(
+                    ast,
+                    &(NodeData::Block(block.clone()), block.span.clone()),
+                    new_decl + definition_ass_list,
+                    next_symbol,
+                    reference_list,
+                    include_self,
+                );
+            }
+
+            StatementNode::While(WhileNode {
+                condition,
+                block,
+                span: _,
+            }) => {
+                get_reference_of_expr(
+                    ast,
+                    &(
+                        Box::into_inner(condition.data.clone()),
+                        condition.span.clone(),
+                    ),
+                    definition_ass_list.clone(),
+                    reference_symbol.clone(),
+                    reference_list,
+                    include_self,
+                );
+
+                get_reference_of_expr(
+                    ast,
+                    &(NodeData::Block(block.clone()), block.span.clone()),
+                    definition_ass_list,
+                    reference_symbol.clone(),
+                    reference_list,
+                    include_self,
+                );
+            }
+
             _ => {}
         },
 
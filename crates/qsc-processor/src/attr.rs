@@ -0,0 +1,109 @@
+use qsc_ast::ast::{node::attr::Attribute, stmt::call::CallNode};
+use qsc_core::{
+    conv::IntoSourceSpan,
+    error::processor::{ProcessorError, ProcessorWarning},
+};
+
+use crate::{ctx::ProcessorContext, Processor, Result};
+
+/// The attributes the compiler knows about, with the most string
+/// arguments they take, whether they need all of them, and the kinds of
+/// declarations they can be used on.
+const ATTRIBUTES: &[(&str, usize, bool, &[&str])] = &[
+    ("Inline", 0, true, &["functions"]),
+    ("NoMangle", 0, true, &["functions"]),
+    ("Export", 1, true, &["functions"]),
+    ("Link", 1, true, &["externs"]),
+    ("Deprecated", 1, false, &["functions", "externs"]),
+];
+
+impl Processor {
+    /// Make sure the attributes on a declaration exist, can be used on
+    /// `kind`, and are given the right arguments.
+    pub fn check_attrs(
+        &self,
+        ctx: &mut ProcessorContext,
+        attrs: &[Attribute],
+        kind: &str,
+    ) -> Result<()> {
+        for (idx, attr) in attrs.iter().enumerate() {
+            let error = |error| -> Result<()> {
+                Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: attr.span.into_source_span(),
+                    error,
+                }
+                .into())
+            };
+
+            let Some((_, args, required, kinds)) =
+                ATTRIBUTES.iter().find(|(name, ..)| *name == attr.name)
+            else {
+                return error(miette!("Unknown attribute @{}!", attr.name));
+            };
+
+            if !kinds.contains(&kind) {
+                return error(miette!("@{} can't be used on {}!", attr.name, kind));
+            }
+
+            if attrs[..idx].iter().any(|prev| prev.name == attr.name) {
+                return error(miette!("@{} can only be used once!", attr.name));
+            }
+
+            let strings = attr
+                .args
+                .iter()
+                .all(|arg| arg.data.as_literal().is_ok_and(|lit| lit.is_string()));
+
+            let count = attr.args.len();
+
+            if !strings || count > *args || (*required && count < *args) {
+                return error(match (args, required) {
+                    (0, _) => miette!("@{} doesn't take any arguments!", attr.name),
+                    (_, true) => miette!("@{} takes a string argument!", attr.name),
+                    (_, false) => miette!("@{} takes an optional string argument!", attr.name),
+                });
+            }
+        }
+
+        if let (Some(_), Some(attr)) = (
+            Attribute::find(attrs, "Export"),
+            Attribute::find(attrs, "NoMangle"),
+        ) {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: attr.span.into_source_span(),
+                error: miette!("@Export and @NoMangle can't be used together!"),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Warn about a call to a function marked with `@Deprecated`.
+    pub fn check_deprecated(&self, ctx: &mut ProcessorContext, call: &CallNode) {
+        let attrs = match self.ast.functions().get(&call.func) {
+            Some(func) => func.attrs.clone(),
+            None => match self.ast.externs().get(&call.func) {
+                Some(func) => func.attrs.clone(),
+                None => return,
+            },
+        };
+
+        let Some(attr) = Attribute::find(&attrs, "Deprecated") else {
+            return;
+        };
+
+        let error = match attr.string() {
+            Some(msg) => miette!("{} is deprecated: {}", call.func, msg),
+            None => miette!("{} is deprecated!", call.func),
+        };
+
+        ctx.warnings.push(ProcessorWarning {
+            src: ctx.tree.src.clone().into(),
+            location: call.span.into_source_span(),
+            error,
+        });
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+
+use qsc_ast::ast::{
+    decl::{
+        func::{FunctionArgument, FunctionNode},
+        structure::{StructField, StructNode},
+        var::VariableNode,
+        DeclarationNode,
+    },
+    expr::{closure::ClosureExpr, pointer::DerefExpr, structure::FieldExpr, ExpressionNode},
+    node::{block::Block, data::NodeData, sym::SymbolNode, ty::TypeNode, vis::Visibility, Node},
+    stmt::StatementNode,
+};
+use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
+
+use crate::{ctx::ProcessorContext, Processor, Result};
+
+/// The argument lifted closures get their environment in.
+pub const ENV_ARG: &str = "__env";
+
+impl Processor {
+    /// Move the body of every closure into a function of its own, which
+    /// gets the variables the closure captures from an environment struct.
+    /// The closure itself is left behind to build that environment.
+    pub fn lift_closures(&mut self) -> Result<()> {
+        let mut queue = self
+            .ast
+            .functions()
+            .into_values()
+            .map(|func| func.name)
+            .collect::<Vec<_>>();
+
+        let mut count = 0;
+
+        while let Some(name) = queue.pop() {
+            loop {
+                let idx = self.function_index(&name);
+                let mut func = self.ast.data[idx].data.as_decl()?.as_function()?;
+                let src = self.sources[idx].clone();
+                let expected = self.expected_closure_types(&func);
+                let parent = func.clone();
+
+                let Some(closure) = func.content.data.iter_mut().find_map(find_closure) else {
+                    break;
+                };
+
+                let lifted = format!("__closure{}", count);
+                let expected = expected.get(&(closure.span.start, closure.span.end));
+
+                count += 1;
+
+                let (params, ret) = expected
+                    .and_then(|ty| TypeNode::function_parts(ty))
+                    .unwrap_or_default();
+
+                for (idx, arg) in closure.args.iter_mut().enumerate() {
+                    if arg.type_.is_some() {
+                        continue;
+                    }
+
+                    let Some(ty) = params.get(idx) else {
+                        return Err(ProcessorError {
+                            src: src.clone().into(),
+                            location: arg.span.into_source_span(),
+                            error: miette!(
+                                "Cannot infer the type of closure argument {}!",
+                                arg.name
+                            ),
+                        }
+                        .into());
+                    };
+
+                    arg.type_ = Some(TypeNode {
+                        span: arg.span.clone(),
+                        name: ty.clone(),
+                        generics: Vec::new(),
+                    });
+                }
+
+                closure.func = lifted.clone();
+                closure.captures = self.captures(&parent, closure);
+
+                let env = self.closure_env(&name, closure)?;
+                let mut target = self.lift(closure, &env);
+
+                if closure.ret.is_none() {
+                    closure.ret = match ret {
+                        Some(ret) => Some(TypeNode {
+                            span: closure.span.clone(),
+                            name: ret,
+                            generics: Vec::new(),
+                        }),
+
+                        None => self.infer_closure_ret(&target)?,
+                    };
+                }
+
+                target.ret = closure.ret.clone();
+                closure.body.data.clear();
+
+                let span = closure.span.clone();
+
+                self.ast.data.insert(
+                    idx,
+                    Node {
+                        span: span.clone(),
+                        data: Box::new(NodeData::Declaration(DeclarationNode::Function(target))),
+                    },
+                );
+
+                self.ast.data.insert(
+                    idx,
+                    Node {
+                        span,
+                        data: Box::new(NodeData::Declaration(DeclarationNode::Struct(env))),
+                    },
+                );
+
+                self.sources.insert(idx, src.clone());
+                self.sources.insert(idx, src);
+
+                *self.ast.data[idx + 2].data =
+                    NodeData::Declaration(DeclarationNode::Function(func));
+
+                queue.push(lifted);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the function types closures in `func` are expected to have,
+    /// from where they are used. The types are keyed by the closure's span.
+    pub fn expected_closure_types(&self, func: &FunctionNode) -> HashMap<(usize, usize), String> {
+        let mut types = HashMap::new();
+        let vars = func.variables();
+        let functions = self.ast.functions();
+        let externs = self.ast.externs();
+
+        let mut expect = |value: &Node, ty: String| {
+            if let Ok(ExpressionNode::Closure(closure)) = value.data.as_expr() {
+                if TypeNode::function_parts(&ty).is_some() {
+                    types.insert((closure.span.start, closure.span.end), ty);
+                }
+            }
+        };
+
+        func.content.walk(&mut |node| match &*node.data {
+            NodeData::Declaration(DeclarationNode::Variable(var)) => {
+                if let (Some(ty), Some(value)) = (&var.type_, &var.value) {
+                    expect(value, ty.as_str());
+                }
+            }
+
+            NodeData::Statement(StatementNode::Return(ret)) => {
+                if let (Some(ty), Some(value)) = (&func.ret, &ret.value) {
+                    expect(value, ty.as_str());
+                }
+            }
+
+            NodeData::Statement(StatementNode::Call(call)) => {
+                let params = if let Some(var) = vars.get(&call.func) {
+                    var.type_
+                        .as_ref()
+                        .and_then(|ty| TypeNode::function_parts(&ty.as_str()))
+                        .map(|(params, _)| params)
+                        .unwrap_or_default()
+                } else if let Some(func) = functions.get(&call.func) {
+                    func.args.iter().map(|arg| arg.type_.as_str()).collect()
+                } else if let Some(func) = externs.get(&call.func) {
+                    func.args.iter().map(|arg| arg.type_.as_str()).collect()
+                } else {
+                    Vec::new()
+                };
+
+                for (param, arg) in params.into_iter().zip(&call.args) {
+                    expect(&arg.value, param);
+                }
+            }
+
+            _ => {}
+        });
+
+        types
+    }
+
+    /// Find the variables of `func` that are used in the body of `closure`,
+    /// in the order they are first used in.
+    pub fn captures(&self, func: &FunctionNode, closure: &ClosureExpr) -> Vec<String> {
+        let vars = func.variables();
+        let own = closure.body.vars();
+        let mut captures = Vec::new();
+
+        let mut capture = |name: &String| {
+            let shadowed = closure.args.iter().any(|arg| arg.name == *name)
+                || own.iter().any(|var| var.name == *name);
+
+            if vars.contains_key(name) && !shadowed && !captures.contains(name) {
+                captures.push(name.clone());
+            }
+        };
+
+        for node in &closure.body.data {
+            node.walk(&mut |node| match &*node.data {
+                NodeData::Symbol(sym) => capture(&sym.value),
+                NodeData::Statement(StatementNode::Call(call)) => capture(&call.func),
+                _ => {}
+            });
+        }
+
+        captures
+    }
+
+    /// Build the struct holding the environment of `closure`, which starts
+    /// with a pointer to its code, followed by the values it captures from
+    /// `parent`.
+    pub fn closure_env(&self, parent: &str, closure: &ClosureExpr) -> Result<StructNode> {
+        let parent = Some(parent.to_string());
+        let mut fields = vec![StructField {
+            span: closure.span.clone(),
+            name: "code".to_string(),
+            type_: TypeNode {
+                span: closure.span.clone(),
+                name: "ptr".to_string(),
+                generics: Vec::new(),
+            },
+        }];
+
+        for name in &closure.captures {
+            let ty = NodeData::Symbol(SymbolNode {
+                span: closure.span.clone(),
+                value: name.clone(),
+            })
+            .get_type(&parent, &self.ast)?;
+
+            fields.push(StructField {
+                span: closure.span.clone(),
+                name: name.clone(),
+                type_: TypeNode {
+                    span: closure.span.clone(),
+                    name: ty,
+                    generics: Vec::new(),
+                },
+            });
+        }
+
+        Ok(StructNode {
+            span: closure.span.clone(),
+            name: closure.env(),
+            fields,
+            vis: Visibility::Private,
+            attrs: Vec::new(),
+        })
+    }
+
+    /// Make the function the body of `closure` is moved into. It takes the
+    /// closure's arguments, followed by a pointer to its environment, and
+    /// starts by copying the captured values out of it.
+    pub fn lift(&self, closure: &ClosureExpr, env: &StructNode) -> FunctionNode {
+        let span = closure.span.clone();
+        let node = |data: NodeData| Node {
+            span: span.clone(),
+            data: Box::new(data),
+        };
+
+        let mut args = closure
+            .args
+            .iter()
+            .map(|arg| FunctionArgument {
+                span: arg.span.clone(),
+                mutable: arg.mutable,
+                name: arg.name.clone(),
+                type_: arg.type_.clone().unwrap(),
+            })
+            .collect::<Vec<_>>();
+
+        args.push(FunctionArgument {
+            span: span.clone(),
+            mutable: false,
+            name: ENV_ARG.to_string(),
+            type_: TypeNode {
+                span: span.clone(),
+                name: TypeNode::pointer(&env.name),
+                generics: Vec::new(),
+            },
+        });
+
+        let mut data = Vec::new();
+
+        for field in env.fields.iter().skip(1) {
+            let env = node(NodeData::Expr(ExpressionNode::Deref(DerefExpr {
+                span: span.clone(),
+                value: node(NodeData::Symbol(SymbolNode {
+                    span: span.clone(),
+                    value: ENV_ARG.to_string(),
+                })),
+            })));
+
+            let value = node(NodeData::Expr(ExpressionNode::Field(FieldExpr {
+                span: span.clone(),
+                value: env,
+                field: SymbolNode {
+                    span: span.clone(),
+                    value: field.name.clone(),
+                },
+            })));
+
+            data.push(node(NodeData::Declaration(DeclarationNode::Variable(
+                VariableNode {
+                    span: span.clone(),
+                    name: field.name.clone(),
+                    type_: Some(field.type_.clone()),
+                    value: Some(value),
+                    mutable: false,
+                },
+            ))));
+        }
+
+        data.extend(closure.body.data.clone());
+
+        FunctionNode {
+            span: span.clone(),
+            name: closure.func.clone(),
+            generics: Vec::new(),
+            bounds: Vec::new(),
+            args,
+            content: Block {
+                span: closure.body.span.clone(),
+                data,
+            },
+            ret: closure.ret.clone(),
+            vis: Visibility::Private,
+            attrs: Vec::new(),
+            constant: false,
+        }
+    }
+
+    /// Whether `value` is known to need no environment when it's called, so
+    /// that C functions can be given just the address of its code. That's
+    /// the case for named functions and closures that capture nothing.
+    pub fn is_plain_function(&self, ctx: &ProcessorContext, value: &Node) -> bool {
+        match &*value.data {
+            NodeData::Symbol(sym) => {
+                !ctx.func
+                    .as_ref()
+                    .is_some_and(|func| func.variables().contains_key(&sym.value))
+                    && (self.ast.functions().contains_key(&sym.value)
+                        || self.ast.externs().contains_key(&sym.value))
+            }
+
+            NodeData::Expr(ExpressionNode::Ref(expr)) => self.is_plain_function(ctx, &expr.value),
+            NodeData::Expr(ExpressionNode::Closure(closure)) => closure.captures.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Work out the return type of a lifted closure from the first value it
+    /// returns.
+    pub fn infer_closure_ret(&self, func: &FunctionNode) -> Result<Option<TypeNode>> {
+        let mut tree = self.ast.clone();
+        tree.data.push(Node {
+            span: func.span.clone(),
+            data: Box::new(NodeData::Declaration(DeclarationNode::Function(
+                func.clone(),
+            ))),
+        });
+
+        let Some(value) = func.content.data.iter().find_map(find_return) else {
+            return Ok(None);
+        };
+
+        let ty = value.data.get_type(&Some(func.name.clone()), &tree)?;
+
+        Ok(Some(TypeNode {
+            span: value.span.clone(),
+            name: ty,
+            generics: Vec::new(),
+        }))
+    }
+}
+
+/// Find the first value returned in `node`, leaving out the ones returned
+/// by closures inside of it.
+fn find_return(node: &Node) -> Option<Node> {
+    match &*node.data {
+        NodeData::Statement(StatementNode::Return(ret)) => ret.value.clone(),
+        NodeData::Expr(ExpressionNode::Closure(_)) => None,
+        data => data.children().iter().find_map(find_return),
+    }
+}
+
+/// Find the first closure in `node` that hasn't been lifted yet.
+fn find_closure(node: &mut Node) -> Option<&mut ClosureExpr> {
+    if matches!(&*node.data, NodeData::Expr(ExpressionNode::Closure(closure)) if closure.func.is_empty())
+    {
+        let NodeData::Expr(ExpressionNode::Closure(closure)) = node.data.as_mut() else {
+            unreachable!();
+        };
+
+        return Some(closure);
+    }
+
+    node.data.children_mut().into_iter().find_map(find_closure)
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use qsc_ast::{
+    ast::{
+        decl::{func::FunctionNode, global::GlobalVariable, DeclarationNode},
+        expr::{comptime::ComptimeExpr, ExpressionNode},
+        node::{block::Block, data::NodeData, ty::TypeNode, Node},
+        stmt::StatementNode,
+    },
+    compat::WrappedNamedSource,
+    span::StaticSpan,
+};
+use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
+
+use crate::{
+    eval::{Evaluator, Value},
+    Processor, Result,
+};
+
+impl Processor {
+    /// Run every `comptime` expression and put its result in its place,
+    /// and work out the lengths of arrays that are given by a constant.
+    /// Top-level `comptime` blocks are only run, for their assertions.
+    pub fn evaluate_comptime(&mut self) -> Result<()> {
+        let functions = self.ast.functions();
+        let globals = self.ast.globals();
+
+        let mut data = Vec::new();
+        let mut sources = Vec::new();
+
+        for (mut node, src) in self.ast.data.clone().into_iter().zip(self.sources.clone()) {
+            if let NodeData::Expr(ExpressionNode::Comptime(expr)) = node.data.as_ref() {
+                evaluate(&functions, &globals, expr, &src)?;
+
+                continue;
+            }
+
+            let mut res = Ok(());
+
+            node.walk_mut(&mut |node| {
+                if res.is_ok() {
+                    res = evaluate_node(&functions, &globals, node, &src);
+                }
+            });
+
+            res?;
+
+            data.push(node);
+            sources.push(src);
+        }
+
+        self.ast.data = data;
+        self.sources = sources;
+
+        Ok(())
+    }
+}
+
+/// Replace a `comptime` expression with its result, or fill in the array
+/// lengths in the types this node declares.
+fn evaluate_node(
+    functions: &HashMap<String, FunctionNode>,
+    globals: &HashMap<String, GlobalVariable>,
+    node: &mut Node,
+    src: &WrappedNamedSource<String>,
+) -> Result<()> {
+    let sized = |ty: &mut TypeNode| -> Result<()> {
+        ty.name = array_lengths(functions, globals, &ty.name, &ty.span, src)?;
+
+        Ok(())
+    };
+
+    match node.data.as_mut() {
+        NodeData::Expr(ExpressionNode::Comptime(expr)) => {
+            let value = evaluate(functions, globals, expr, src)?;
+
+            // Only top-level blocks are run just for their assertions.
+            if matches!(value, Value::Void) && expr.value.data.is_block() {
+                return Err(ProcessorError {
+                    src: src.clone().into(),
+                    location: expr.value.span.into_source_span(),
+                    error: miette!("`comptime` blocks must `return` a value!"),
+                }
+                .into());
+            }
+
+            *node = value.to_node(&expr.span).unwrap_or(Node {
+                span: expr.span.clone(),
+                data: Box::new(NodeData::Block(Block {
+                    span: expr.span.clone(),
+                    data: Vec::new(),
+                })),
+            });
+        }
+
+        NodeData::Declaration(DeclarationNode::Function(func)) => {
+            for arg in &mut func.args {
+                sized(&mut arg.type_)?;
+            }
+
+            if let Some(ret) = &mut func.ret {
+                sized(ret)?;
+            }
+        }
+
+        NodeData::Declaration(DeclarationNode::Extern(func)) => {
+            for arg in &mut func.args {
+                sized(&mut arg.type_)?;
+            }
+
+            if let Some(ret) = &mut func.ret {
+                sized(ret)?;
+            }
+        }
+
+        NodeData::Declaration(DeclarationNode::Trait(item)) => {
+            for func in &mut item.functions {
+                for arg in &mut func.args {
+                    sized(&mut arg.type_)?;
+                }
+
+                if let Some(ret) = &mut func.ret {
+                    sized(ret)?;
+                }
+            }
+        }
+
+        NodeData::Declaration(DeclarationNode::Global(global)) => sized(&mut global.type_)?,
+
+        NodeData::Declaration(DeclarationNode::Struct(item)) => {
+            for field in &mut item.fields {
+                sized(&mut field.type_)?;
+            }
+        }
+
+        NodeData::Declaration(DeclarationNode::Enum(item)) => {
+            for variant in &mut item.variants {
+                for field in &mut variant.fields {
+                    sized(field)?;
+                }
+            }
+        }
+
+        NodeData::Declaration(DeclarationNode::Variable(var)) => {
+            if let Some(ty) = &mut var.type_ {
+                sized(ty)?;
+            }
+        }
+
+        NodeData::Expr(ExpressionNode::Cast(expr)) => sized(&mut expr.type_)?,
+
+        NodeData::Expr(ExpressionNode::Closure(expr)) => {
+            for arg in &mut expr.args {
+                if let Some(ty) = &mut arg.type_ {
+                    sized(ty)?;
+                }
+            }
+
+            if let Some(ret) = &mut expr.ret {
+                sized(ret)?;
+            }
+        }
+
+        NodeData::Statement(StatementNode::For(node)) => {
+            if let Some(ty) = &mut node.type_ {
+                sized(ty)?;
+            }
+        }
+
+        NodeData::Type(ty) => sized(ty)?,
+
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn evaluate(
+    functions: &HashMap<String, FunctionNode>,
+    globals: &HashMap<String, GlobalVariable>,
+    expr: &ComptimeExpr,
+    src: &WrappedNamedSource<String>,
+) -> Result<Value> {
+    Evaluator::new(functions, globals)
+        .comptime(&expr.value)
+        .map_err(|error| {
+            ProcessorError {
+                src: src.clone().into(),
+                location: expr.span.into_source_span(),
+                error,
+            }
+            .into()
+        })
+}
+
+/// Replace the constants used as array lengths in a type, like the `SIZE`
+/// in `[i32; SIZE]`, with their values.
+fn array_lengths(
+    functions: &HashMap<String, FunctionNode>,
+    globals: &HashMap<String, GlobalVariable>,
+    ty: &str,
+    span: &StaticSpan,
+    src: &WrappedNamedSource<String>,
+) -> Result<String> {
+    let sized = |ty: &str| array_lengths(functions, globals, ty, span, src);
+
+    if let Some((params, ret)) = TypeNode::function_parts(ty) {
+        let params = params
+            .iter()
+            .map(|param| sized(param))
+            .collect::<Result<Vec<_>>>()?;
+
+        let ret = ret.map(|ret| sized(&ret)).transpose()?;
+
+        return Ok(TypeNode::function(&params, ret.as_deref()));
+    }
+
+    if let Some((elem, len)) = TypeNode::array_parts(ty) {
+        return Ok(TypeNode::array(&sized(elem)?, len));
+    }
+
+    if let Some((elem, name)) = TypeNode::named_array_parts(ty) {
+        let error = |error| -> Result<String> {
+            Err(ProcessorError {
+                src: src.clone().into(),
+                location: span.into_source_span(),
+                error,
+            }
+            .into())
+        };
+
+        let len = match Evaluator::new(functions, globals).constant(name) {
+            Ok(Value::Int(len, _)) if len >= 0 => len as usize,
+
+            Ok(value) => {
+                return error(miette!(
+                    "Array lengths have to be positive integers, but {} is a {}!",
+                    name,
+                    value.type_name()
+                ))
+            }
+
+            Err(err) => return error(err),
+        };
+
+        return Ok(TypeNode::array(&sized(elem)?, len));
+    }
+
+    if let Some(pointee) = TypeNode::pointee(ty) {
+        return Ok(TypeNode::pointer(&sized(pointee)?));
+    }
+
+    if let Some(elems) = TypeNode::tuple_parts(ty) {
+        let elems = elems
+            .iter()
+            .map(|elem| sized(elem))
+            .collect::<Result<Vec<_>>>()?;
+
+        return Ok(TypeNode::tuple(&elems));
+    }
+
+    Ok(ty.to_string())
+}
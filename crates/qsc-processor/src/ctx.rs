@@ -1,13 +1,45 @@
-use qsc_ast::ast::{decl::func::FunctionNode, AbstractTree};
+use qsc_ast::ast::{
+    decl::{func::FunctionNode, DeclarationNode},
+    node::{data::NodeData, Node},
+    AbstractTree,
+};
+use qsc_core::error::processor::ProcessorWarning;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct ProcessorContext<'a> {
     pub func: Option<FunctionNode>,
     pub tree: &'a mut AbstractTree,
+    pub loop_depth: usize,
+    pub warnings: Vec<ProcessorWarning>,
 }
 
 impl<'a> ProcessorContext<'a> {
     pub fn new(tree: &'a mut AbstractTree) -> Self {
-        Self { tree, func: None }
+        Self {
+            tree,
+            func: None,
+            loop_depth: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Call `f` on every node of the current function, both in the tree and
+    /// in [`ProcessorContext::func`]. Types are looked up in the tree, so
+    /// this is how what was inferred for a variable is seen by the rest of
+    /// the function before all of it is processed.
+    pub fn update_func(&mut self, mut f: impl FnMut(&mut Node)) {
+        let Some(func) = &mut self.func else {
+            return;
+        };
+
+        func.content.walk_mut(&mut f);
+
+        for node in &mut self.tree.data {
+            if let NodeData::Declaration(DeclarationNode::Function(item)) = node.data.as_mut() {
+                if item.name == func.name {
+                    item.content.walk_mut(&mut f);
+                }
+            }
+        }
     }
 }
@@ -1,6 +1,7 @@
 use qsc_ast::ast::{
     decl::DeclarationNode,
-    node::{data::NodeData, ty::TypeNode},
+    expr::ExpressionNode,
+    node::{data::NodeData, ty::TypeNode, Node},
 };
 use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
 
@@ -14,6 +15,8 @@ impl Processor {
     ) -> Result<NodeData> {
         match &mut decl {
             DeclarationNode::Function(func) => {
+                self.check_attrs(ctx, &func.attrs, "functions")?;
+
                 ctx.func = Some(func.clone());
                 func.content = self.process_block(ctx, func.content.clone())?.as_block()?;
 
@@ -29,6 +32,8 @@ impl Processor {
             }
 
             DeclarationNode::Extern(func) => {
+                self.check_attrs(ctx, &func.attrs, "externs")?;
+
                 if func.ret.is_none() {
                     func.ret = Some(TypeNode {
                         generics: Vec::new(),
@@ -38,7 +43,123 @@ impl Processor {
                 }
             }
 
-            DeclarationNode::Global(_global) => todo!(),
+            DeclarationNode::Global(global) => {
+                self.check_attrs(ctx, &global.attrs, "globals")?;
+
+                if global.constant && global.mutable {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: global.span.into_source_span(),
+                        error: miette!("Constants cannot be mutable!"),
+                    }
+                    .into());
+                }
+
+                global.type_ = self
+                    .process_type(ctx, global.type_.clone())?
+                    .as_type()
+                    .unwrap();
+
+                global.value = self.process_node(ctx, &mut global.value)?;
+                self.coerce_literal(ctx, &mut global.value, &global.type_.as_str())?;
+
+                // Globals are emitted as plain data, so their value
+                // has to be known without running any code.
+                if !is_constant_data(&global.value) {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: global.value.span.into_source_span(),
+                        error: miette!(
+                            "Global values must be literals, or arrays, tuples and structs of them!"
+                        ),
+                    }
+                    .into());
+                }
+
+                if global.value.data.get_type(&None, ctx.tree)? != global.type_.as_str() {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: global.span.into_source_span(),
+                        error: miette!("Declared type does not match value type!"),
+                    }
+                    .into());
+                }
+            }
+
+            DeclarationNode::Struct(item) => {
+                self.check_attrs(ctx, &item.attrs, "structs")?;
+
+                for (idx, field) in item.fields.clone().iter().enumerate() {
+                    item.fields[idx].type_ = self
+                        .process_type(ctx, field.type_.clone())?
+                        .as_type()
+                        .unwrap();
+
+                    if item.fields[..idx].iter().any(|v| v.name == field.name) {
+                        return Err(ProcessorError {
+                            src: ctx.tree.src.clone().into(),
+                            location: field.span.into_source_span(),
+                            error: miette!("Field {} is declared more than once!", field.name),
+                        }
+                        .into());
+                    }
+                }
+
+                if self.contains_type(ctx, &item.name, &item.name, &mut Vec::new()) {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: item.span.into_source_span(),
+                        error: miette!(
+                            "Struct {} contains itself, so it would have an infinite size!",
+                            item.name
+                        ),
+                    }
+                    .into());
+                }
+            }
+
+            DeclarationNode::Enum(item) => {
+                self.check_attrs(ctx, &item.attrs, "enums")?;
+
+                for (idx, variant) in item.variants.clone().iter().enumerate() {
+                    for (field_idx, field) in variant.fields.iter().enumerate() {
+                        item.variants[idx].fields[field_idx] =
+                            self.process_type(ctx, field.clone())?.as_type().unwrap();
+                    }
+
+                    if item.variants[..idx].iter().any(|v| v.name == variant.name) {
+                        return Err(ProcessorError {
+                            src: ctx.tree.src.clone().into(),
+                            location: variant.span.into_source_span(),
+                            error: miette!("Variant {} is declared more than once!", variant.name),
+                        }
+                        .into());
+                    }
+                }
+
+                if self.contains_type(ctx, &item.name, &item.name, &mut Vec::new()) {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: item.span.into_source_span(),
+                        error: miette!(
+                            "Enum {} contains itself, so it would have an infinite size!",
+                            item.name
+                        ),
+                    }
+                    .into());
+                }
+            }
+
+            // Modules are flattened into the tree and macros expanded before
+            // anything else gets processed, so these are gone by now.
+            DeclarationNode::Module(_) | DeclarationNode::Use(_) | DeclarationNode::Macro(_) => {}
+
+            // Only trait impls are left, and their functions have been
+            // moved out already.
+            DeclarationNode::Impl(item) => self.check_trait_signatures(ctx, item)?,
+
+            // Traits only matter for the impls and bounds using them.
+            DeclarationNode::Trait(item) => self.check_attrs(ctx, &item.attrs, "traits")?,
 
             DeclarationNode::Variable(var) => {
                 if !var.mutable && var.value.is_none() {
@@ -50,10 +171,16 @@ impl Processor {
                     .into());
                 }
 
+                if let Some(val) = &mut var.value {
+                    *val = self.process_node(ctx, val)?;
+                }
+
                 if let Some(ty) = &mut var.type_ {
                     *ty = self.process_type(ctx, ty.clone())?.as_type().unwrap();
 
-                    if let Some(val) = &var.value {
+                    if let Some(val) = &mut var.value {
+                        self.coerce_literal(ctx, val, &ty.as_str())?;
+
                         if val
                             .data
                             .get_type(&ctx.func.clone().map(|v| v.name), &ctx.tree)?
@@ -95,9 +222,91 @@ impl Processor {
                         .into());
                     }
                 }
+
+                // The statements after this one see the inferred type and
+                // the literals that were given the declared one.
+                let var = var.clone();
+
+                ctx.update_func(|node| {
+                    if let NodeData::Declaration(DeclarationNode::Variable(item)) =
+                        node.data.as_mut()
+                    {
+                        if item.span == var.span && item.name == var.name {
+                            *item = var.clone();
+                        }
+                    }
+                });
             }
         };
 
         Ok(NodeData::Declaration(decl))
     }
+
+    /// Check whether a struct or enum (directly or through its fields)
+    /// contains a type named `target` by value.
+    pub fn contains_type(
+        &self,
+        ctx: &ProcessorContext,
+        ty: &String,
+        target: &String,
+        seen: &mut Vec<String>,
+    ) -> bool {
+        let mut fields: Vec<String> = if let Some(item) = ctx.tree.structs().get(ty) {
+            item.fields.iter().map(|v| v.type_.as_str()).collect()
+        } else if let Some(item) = ctx.tree.enums().get(ty) {
+            item.variants
+                .iter()
+                .flat_map(|v| v.fields.iter().map(|v| v.as_str()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        while let Some(mut field) = fields.pop() {
+            // Arrays and tuples contain their elements by value.
+            while let Some((elem, _)) = TypeNode::array_parts(&field) {
+                field = elem.to_string();
+            }
+
+            if let Some(elems) = TypeNode::tuple_parts(&field) {
+                fields.extend(elems);
+                continue;
+            }
+
+            if &field == target {
+                return true;
+            }
+
+            if seen.contains(&field) {
+                continue;
+            }
+
+            seen.push(field.clone());
+
+            if self.contains_type(ctx, &field, target, seen) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+/// Whether `node` can be written out as data: a literal, optionally with a
+/// unary operator in front of it, or an array, tuple or struct of those,
+/// like what a `comptime` block evaluates to.
+fn is_constant_data(node: &Node) -> bool {
+    match node.data.as_ref() {
+        NodeData::Literal(_) => true,
+        NodeData::Expr(ExpressionNode::Unary(expr)) => expr.value.data.is_literal(),
+        NodeData::Expr(ExpressionNode::Array(expr)) => expr.values.iter().all(is_constant_data),
+        NodeData::Expr(ExpressionNode::Tuple(expr)) => expr.values.iter().all(is_constant_data),
+
+        NodeData::Expr(ExpressionNode::Struct(expr)) => expr
+            .fields
+            .iter()
+            .all(|field| is_constant_data(&field.value)),
+
+        _ => false,
+    }
 }
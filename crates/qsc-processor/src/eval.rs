@@ -0,0 +1,880 @@
+use std::collections::HashMap;
+
+use miette::Report;
+use qsc_ast::{
+    ast::{
+        decl::{func::FunctionNode, global::GlobalVariable, DeclarationNode},
+        expr::{array::ArrayExpr, operator::Operator, ExpressionNode},
+        literal::{
+            boolean::BoolNode, char::CharNode, float::FloatNode, int::IntNode, string::StringNode,
+            LiteralNode,
+        },
+        node::{data::NodeData, ty::TypeNode, Node},
+        stmt::{call::CallNode, StatementNode},
+    },
+    span::StaticSpan,
+};
+
+/// How many nodes an evaluation can go through before it is stopped, so
+/// infinite loops don't hang the compiler.
+const MAX_STEPS: usize = 1_000_000;
+
+/// How deep calls can go while evaluating.
+const MAX_DEPTH: usize = 256;
+
+type Result<T> = std::result::Result<T, Report>;
+
+/// A value worked out at compile time. Numbers without a type keep it
+/// open, like the literals they come from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64, Option<String>),
+    Float(f64, Option<String>),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    Array(Vec<Value>),
+    Void,
+}
+
+impl Value {
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::Int(_, ty) => ty.clone().unwrap_or("i32".into()),
+            Value::Float(_, ty) => ty.clone().unwrap_or("f64".into()),
+            Value::Bool(_) => "bool".into(),
+            Value::Char(_) => "char".into(),
+            Value::Str(_) => "str".into(),
+            Value::Void => "void".into(),
+
+            Value::Array(values) => TypeNode::array(
+                &values
+                    .first()
+                    .map(|value| value.type_name())
+                    .unwrap_or("void".into()),
+                values.len(),
+            ),
+        }
+    }
+
+    /// Turn the value into a literal that can take the place of the
+    /// expression it came from.
+    pub fn to_node(&self, span: &StaticSpan) -> Option<Node> {
+        let span = span.clone();
+
+        let data = match self.clone() {
+            Value::Int(value, type_) => NodeData::Literal(LiteralNode::Int(IntNode {
+                span: span.clone(),
+                value,
+                type_,
+            })),
+
+            Value::Float(value, type_) => NodeData::Literal(LiteralNode::Float(FloatNode {
+                span: span.clone(),
+                value,
+                type_,
+            })),
+
+            Value::Bool(value) => NodeData::Literal(LiteralNode::Bool(BoolNode {
+                span: span.clone(),
+                value,
+            })),
+
+            Value::Char(value) => NodeData::Literal(LiteralNode::Char(CharNode {
+                span: span.clone(),
+                value,
+            })),
+
+            Value::Str(value) => NodeData::Literal(LiteralNode::String(StringNode {
+                span: span.clone(),
+                value,
+            })),
+
+            Value::Array(values) => NodeData::Expr(ExpressionNode::Array(ArrayExpr {
+                span: span.clone(),
+                values: values
+                    .iter()
+                    .map(|value| value.to_node(&span))
+                    .collect::<Option<_>>()?,
+            })),
+
+            Value::Void => return None,
+        };
+
+        Some(Node {
+            span,
+            data: Box::new(data),
+        })
+    }
+
+    /// Convert the value to `ty`, the way a cast would at runtime. Types
+    /// that can't be worked with at compile time leave it as it is.
+    pub fn cast(self, ty: &str) -> Result<Value> {
+        if let Some((elem, len)) = TypeNode::array_parts(ty) {
+            let Value::Array(values) = self else {
+                return Err(miette!("Expected an array, but got {}!", self.type_name()));
+            };
+
+            if values.len() != len {
+                return Err(miette!(
+                    "Expected an array of length {}, but got one of length {}!",
+                    len,
+                    values.len()
+                ));
+            }
+
+            return Ok(Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| value.cast(elem))
+                    .collect::<Result<_>>()?,
+            ));
+        }
+
+        Ok(match (self, ty) {
+            (Value::Int(value, from), "f32" | "f64") => {
+                let value = if from.as_deref().is_some_and(ty_unsigned) {
+                    value as u64 as f64
+                } else {
+                    value as f64
+                };
+
+                Value::Float(round_float(value, ty), Some(ty.into()))
+            }
+
+            (Value::Float(value, _), "f32" | "f64") => {
+                Value::Float(round_float(value, ty), Some(ty.into()))
+            }
+
+            (Value::Int(value, _), _) if is_int(ty) => Value::Int(wrap(value, ty), Some(ty.into())),
+            (Value::Float(value, _), _) if is_int(ty) => {
+                Value::Int(wrap(value as i64, ty), Some(ty.into()))
+            }
+            (Value::Char(value), _) if is_int(ty) => {
+                Value::Int(wrap(value as i64, ty), Some(ty.into()))
+            }
+            (Value::Bool(value), _) if is_int(ty) => Value::Int(value as i64, Some(ty.into())),
+
+            (Value::Int(value, _), "char") => Value::Char(
+                char::from_u32(value as u32)
+                    .ok_or_else(|| miette!("{} isn't a valid character!", value))?,
+            ),
+
+            (value, _) => value,
+        })
+    }
+}
+
+/// What running a statement did to the flow of the code around it.
+enum Flow {
+    Next,
+    Return(Value),
+    Break,
+    Continue,
+}
+
+/// Runs code while compiling, for `comptime` expressions and the values
+/// of constants. Only `const fn`s can be called.
+pub struct Evaluator<'a> {
+    pub functions: &'a HashMap<String, FunctionNode>,
+    pub globals: &'a HashMap<String, GlobalVariable>,
+    steps: usize,
+    frames: Vec<HashMap<String, Value>>,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(
+        functions: &'a HashMap<String, FunctionNode>,
+        globals: &'a HashMap<String, GlobalVariable>,
+    ) -> Self {
+        Self {
+            functions,
+            globals,
+            steps: 0,
+            frames: vec![HashMap::new()],
+        }
+    }
+
+    /// Work out the value of a `comptime` expression. Blocks give the
+    /// value they `return`, or nothing.
+    pub fn comptime(&mut self, value: &Node) -> Result<Value> {
+        let NodeData::Block(block) = value.data.as_ref() else {
+            return self.eval(value);
+        };
+
+        for node in &block.data {
+            match self.exec(node)? {
+                Flow::Next => {}
+                Flow::Return(value) => return Ok(value),
+                Flow::Break | Flow::Continue => {
+                    return Err(miette!("Cannot use break or continue outside of a loop!"))
+                }
+            }
+        }
+
+        Ok(Value::Void)
+    }
+
+    /// Evaluate the value of the constant called `name`.
+    pub fn constant(&mut self, name: &str) -> Result<Value> {
+        let Some(global) = self.globals.get(name) else {
+            return Err(miette!("Cannot find constant {}!", name));
+        };
+
+        if !global.constant {
+            return Err(miette!(
+                "Static {} can't be read at compile time, only constants can!",
+                name
+            ));
+        }
+
+        self.enter()?;
+
+        let value = self.eval(&global.value);
+
+        self.frames.pop();
+
+        value?.cast(&global.type_.as_str())
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        if self.frames.len() > MAX_DEPTH {
+            return Err(miette!("Compile-time evaluation recursed too deeply!"));
+        }
+
+        self.frames.push(HashMap::new());
+
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<()> {
+        self.steps += 1;
+
+        if self.steps > MAX_STEPS {
+            return Err(miette!(
+                "Compile-time evaluation took more than {} steps, is there an infinite loop?",
+                MAX_STEPS
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn vars(&mut self) -> &mut HashMap<String, Value> {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn exec_block(&mut self, data: &[Node]) -> Result<Flow> {
+        for node in data {
+            let flow = self.exec(node)?;
+
+            if !matches!(flow, Flow::Next) {
+                return Ok(flow);
+            }
+        }
+
+        Ok(Flow::Next)
+    }
+
+    fn exec(&mut self, node: &Node) -> Result<Flow> {
+        self.step()?;
+
+        match node.data.as_ref() {
+            NodeData::Block(block) => self.exec_block(&block.data),
+
+            NodeData::Declaration(DeclarationNode::Variable(var)) => {
+                let value = match &var.value {
+                    Some(value) => self.eval(value)?,
+                    None => Value::Void,
+                };
+
+                let value = match &var.type_ {
+                    Some(ty) => value.cast(&ty.as_str())?,
+                    None => value,
+                };
+
+                self.vars().insert(var.name.clone(), value);
+
+                Ok(Flow::Next)
+            }
+
+            NodeData::Statement(StatementNode::Return(ret)) => Ok(Flow::Return(
+                ret.value
+                    .as_ref()
+                    .map(|value| self.eval(value))
+                    .transpose()?
+                    .unwrap_or(Value::Void),
+            )),
+
+            NodeData::Statement(StatementNode::Break(_)) => Ok(Flow::Break),
+            NodeData::Statement(StatementNode::Continue(_)) => Ok(Flow::Continue),
+
+            NodeData::Statement(StatementNode::Condition(cond)) => {
+                if self.condition(&cond.condition)? {
+                    self.exec_block(&cond.block.data)
+                } else if let Some(block) = &cond.else_block {
+                    self.exec_block(&block.data)
+                } else {
+                    Ok(Flow::Next)
+                }
+            }
+
+            NodeData::Statement(StatementNode::While(node)) => {
+                while self.condition(&node.condition)? {
+                    match self.exec_block(&node.block.data)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+
+                Ok(Flow::Next)
+            }
+
+            NodeData::Statement(StatementNode::For(node)) => {
+                let start = self.eval(&node.range.start)?;
+                let end = self.eval(&node.range.end)?;
+
+                let ty = node
+                    .type_
+                    .as_ref()
+                    .map(|ty| ty.as_str())
+                    .unwrap_or(start.type_name());
+
+                let (Value::Int(start, _), Value::Int(end, _)) = (start, end) else {
+                    return Err(miette!("Ranges have to be made of integers!"));
+                };
+
+                let end = if node.range.inclusive { end + 1 } else { end };
+
+                for idx in start..end {
+                    self.step()?;
+
+                    let value = Value::Int(idx, Some(ty.clone())).cast(&ty)?;
+
+                    self.vars().insert(node.var.value.clone(), value);
+
+                    match self.exec_block(&node.block.data)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+
+                Ok(Flow::Next)
+            }
+
+            _ => {
+                self.eval(node)?;
+
+                Ok(Flow::Next)
+            }
+        }
+    }
+
+    fn condition(&mut self, node: &Node) -> Result<bool> {
+        match self.eval(node)? {
+            Value::Bool(value) => Ok(value),
+            value => Err(miette!("Expected a bool, but got {}!", value.type_name())),
+        }
+    }
+
+    fn eval(&mut self, node: &Node) -> Result<Value> {
+        self.step()?;
+
+        match node.data.as_ref() {
+            NodeData::Literal(lit) => Ok(match lit.clone() {
+                LiteralNode::Int(int) => match int.type_ {
+                    Some(ty) => Value::Int(wrap(int.value, &ty), Some(ty)),
+                    None => Value::Int(int.value, None),
+                },
+
+                LiteralNode::Float(float) => Value::Float(float.value, float.type_),
+                LiteralNode::Bool(value) => Value::Bool(value.value),
+                LiteralNode::Char(value) => Value::Char(value.value),
+                LiteralNode::String(value) => Value::Str(value.value),
+            }),
+
+            NodeData::Symbol(sym) => {
+                if let Some(value) = self.vars().get(&sym.value) {
+                    return Ok(value.clone());
+                }
+
+                self.constant(&sym.value)
+            }
+
+            NodeData::Statement(StatementNode::Call(call)) => self.call(call),
+
+            NodeData::Expr(expr) => match expr {
+                ExpressionNode::Binary(expr) if expr.operator.is_assign() => {
+                    let value = self.eval(&expr.rhs)?;
+
+                    let value = match expr.operator.base_op() {
+                        Some(op) => {
+                            let current = self.eval(&expr.lhs)?;
+
+                            binary(op, current, value)?
+                        }
+
+                        None => value,
+                    };
+
+                    self.assign(&expr.lhs, value)?;
+
+                    Ok(Value::Void)
+                }
+
+                ExpressionNode::Binary(expr) if expr.operator == Operator::And => Ok(Value::Bool(
+                    self.condition(&expr.lhs)? && self.condition(&expr.rhs)?,
+                )),
+
+                ExpressionNode::Binary(expr) if expr.operator == Operator::Or => Ok(Value::Bool(
+                    self.condition(&expr.lhs)? || self.condition(&expr.rhs)?,
+                )),
+
+                ExpressionNode::Binary(expr) => {
+                    let lhs = self.eval(&expr.lhs)?;
+                    let rhs = self.eval(&expr.rhs)?;
+
+                    binary(expr.operator, lhs, rhs)
+                }
+
+                ExpressionNode::Unary(expr) => {
+                    let value = self.eval(&expr.value)?;
+
+                    unary(expr.operator, value)
+                }
+
+                ExpressionNode::Array(expr) => Ok(Value::Array(
+                    expr.values
+                        .iter()
+                        .map(|value| self.eval(value))
+                        .collect::<Result<_>>()?,
+                )),
+
+                ExpressionNode::Index(expr) => {
+                    let value = self.eval(&expr.value)?;
+                    let idx = self.eval(&expr.index)?;
+
+                    Ok(index(&value, &idx)?.clone())
+                }
+
+                ExpressionNode::Cast(expr) => self.eval(&expr.value)?.cast(&expr.type_.as_str()),
+                ExpressionNode::Comptime(expr) => self.comptime(&expr.value),
+
+                // Macros give the value of the last thing in their body.
+                ExpressionNode::MacroCall(expr) => {
+                    let Some((last, rest)) = expr
+                        .expansion
+                        .as_ref()
+                        .and_then(|block| block.data.split_last())
+                    else {
+                        return Ok(Value::Void);
+                    };
+
+                    for node in rest {
+                        if !matches!(self.exec(node)?, Flow::Next) {
+                            return Err(miette!("Control flow can't be used as a value!"));
+                        }
+                    }
+
+                    self.eval(last)
+                }
+
+                ExpressionNode::Spread(expr) => {
+                    Err(miette!("{}... can only be used in a macro!", expr.name))
+                }
+
+                ExpressionNode::Struct(_) | ExpressionNode::Field(_) => {
+                    Err(miette!("Structs can't be used at compile time yet!"))
+                }
+
+                ExpressionNode::Tuple(_) => {
+                    Err(miette!("Tuples can't be used at compile time yet!"))
+                }
+
+                ExpressionNode::Enum(_)
+                | ExpressionNode::Match(_)
+                | ExpressionNode::VariantField(_) => {
+                    Err(miette!("Enums can't be used at compile time yet!"))
+                }
+
+                ExpressionNode::Ref(_) | ExpressionNode::Deref(_) => {
+                    Err(miette!("Pointers can't be used at compile time!"))
+                }
+
+                ExpressionNode::Closure(_) => {
+                    Err(miette!("Closures can't be used at compile time yet!"))
+                }
+
+                ExpressionNode::Method(_) => {
+                    Err(miette!("Methods can't be called at compile time yet!"))
+                }
+            },
+
+            NodeData::Block(_) | NodeData::Declaration(_) | NodeData::Statement(_) => {
+                match self.exec(node)? {
+                    Flow::Next => Ok(Value::Void),
+                    _ => Err(miette!("Control flow can't be used as a value!")),
+                }
+            }
+
+            NodeData::Type(_) | NodeData::EOI => {
+                Err(miette!("This can't be evaluated at compile time!"))
+            }
+        }
+    }
+
+    /// Store a value in a variable, or in an element of an array in one.
+    fn assign(&mut self, target: &Node, value: Value) -> Result<()> {
+        let mut path = Vec::new();
+        let mut node = target;
+
+        while let NodeData::Expr(ExpressionNode::Index(expr)) = node.data.as_ref() {
+            path.push(self.eval(&expr.index)?);
+            node = &expr.value;
+        }
+
+        let NodeData::Symbol(sym) = node.data.as_ref() else {
+            return Err(miette!(
+                "Only variables can be assigned to at compile time!"
+            ));
+        };
+
+        let Some(mut slot) = self.vars().get_mut(&sym.value) else {
+            return Err(miette!(
+                "Cannot assign to {}, only local variables can be changed at compile time!",
+                sym.value
+            ));
+        };
+
+        for idx in path.iter().rev() {
+            let Value::Array(values) = slot else {
+                return Err(miette!("Type {} cannot be indexed!", slot.type_name()));
+            };
+
+            let len = values.len();
+            let idx = checked_index(idx, len)?;
+
+            slot = &mut values[idx];
+        }
+
+        let ty = slot.type_name();
+
+        *slot = match slot {
+            Value::Void => value,
+            _ => value.cast(&ty)?,
+        };
+
+        Ok(())
+    }
+
+    fn call(&mut self, call: &CallNode) -> Result<Value> {
+        let args = call
+            .args
+            .iter()
+            .map(|arg| self.eval(&arg.value))
+            .collect::<Result<Vec<_>>>()?;
+
+        let Some(func) = self.functions.get(&call.func) else {
+            if call.func == "assert" {
+                return assert(args);
+            }
+
+            return Err(miette!(
+                "{} isn't a const fn, so it can't be called at compile time!",
+                call.func
+            ));
+        };
+
+        if !func.constant {
+            return Err(miette!(
+                "{} isn't a const fn, so it can't be called at compile time!",
+                call.func
+            ));
+        }
+
+        if args.len() != func.args.len() {
+            return Err(miette!(
+                "Function \"{}\" takes {} arguments, but {} were given!",
+                call.func,
+                func.args.len(),
+                args.len()
+            ));
+        }
+
+        self.enter()?;
+
+        for (arg, value) in func.args.iter().zip(args) {
+            let value = value.cast(&arg.type_.as_str());
+
+            match value {
+                Ok(value) => {
+                    self.vars().insert(arg.name.clone(), value);
+                }
+
+                Err(err) => {
+                    self.frames.pop();
+
+                    return Err(err);
+                }
+            }
+        }
+
+        let flow = self.exec_block(&func.content.data);
+
+        self.frames.pop();
+
+        let value = match flow? {
+            Flow::Return(value) => value,
+            _ => Value::Void,
+        };
+
+        match &func.ret {
+            Some(ret) if ret.as_str() != "void" => value.cast(&ret.as_str()),
+            _ => Ok(value),
+        }
+    }
+}
+
+/// `assert(cond)` or `assert(cond, "message")`, which fails evaluation
+/// if the condition doesn't hold.
+fn assert(args: Vec<Value>) -> Result<Value> {
+    match args.as_slice() {
+        [Value::Bool(true)] | [Value::Bool(true), Value::Str(_)] => Ok(Value::Void),
+        [Value::Bool(false)] => Err(miette!("Compile-time assertion failed!")),
+
+        [Value::Bool(false), Value::Str(msg)] => {
+            Err(miette!("Compile-time assertion failed: {}", msg))
+        }
+
+        _ => Err(miette!(
+            "assert takes a bool and an optional message string!"
+        )),
+    }
+}
+
+fn index<'v>(value: &'v Value, idx: &Value) -> Result<&'v Value> {
+    let Value::Array(values) = value else {
+        return Err(miette!("Type {} cannot be indexed!", value.type_name()));
+    };
+
+    Ok(&values[checked_index(idx, values.len())?])
+}
+
+fn checked_index(idx: &Value, len: usize) -> Result<usize> {
+    let Value::Int(idx, _) = idx else {
+        return Err(miette!("Arrays can only be indexed with integers!"));
+    };
+
+    if *idx < 0 || *idx as usize >= len {
+        return Err(miette!(
+            "Index {} is out of bounds for an array of length {}!",
+            idx,
+            len
+        ));
+    }
+
+    Ok(*idx as usize)
+}
+
+fn unary(op: Operator, value: Value) -> Result<Value> {
+    Ok(match (op, value) {
+        (Operator::Add, value @ (Value::Int(..) | Value::Float(..))) => value,
+
+        (Operator::Subtract, Value::Int(value, ty)) => {
+            let value = value.wrapping_neg();
+
+            match ty {
+                Some(ty) => Value::Int(wrap(value, &ty), Some(ty)),
+                None => Value::Int(value, None),
+            }
+        }
+
+        (Operator::Subtract, Value::Float(value, ty)) => Value::Float(-value, ty),
+        (Operator::Not, Value::Bool(value)) => Value::Bool(!value),
+
+        (Operator::BitwiseNot | Operator::Not, Value::Int(value, ty)) => match ty {
+            Some(ty) => Value::Int(wrap(!value, &ty), Some(ty)),
+            None => Value::Int(!value, None),
+        },
+
+        (op, value) => {
+            return Err(miette!(
+                "Operator {:?} can't be used on {} at compile time!",
+                op,
+                value.type_name()
+            ))
+        }
+    })
+}
+
+fn binary(op: Operator, lhs: Value, rhs: Value) -> Result<Value> {
+    // Untyped numbers take the type of the other side.
+    let (lhs, rhs) = match (lhs, rhs) {
+        (Value::Int(value, None), rhs @ Value::Float(..)) => {
+            (Value::Float(value as f64, None), rhs)
+        }
+        (lhs @ Value::Float(..), Value::Int(value, None)) => {
+            (lhs, Value::Float(value as f64, None))
+        }
+        pair => pair,
+    };
+
+    let mismatch = |lhs: &Value, rhs: &Value| {
+        miette!(
+            "Operator {:?} can't be used on {} and {} at compile time!",
+            op,
+            lhs.type_name(),
+            rhs.type_name()
+        )
+    };
+
+    match (&lhs, &rhs) {
+        (Value::Int(a, lty), Value::Int(b, rty)) => {
+            let ty = lty.clone().or(rty.clone());
+            let unsigned = ty.as_deref().is_some_and(ty_unsigned);
+            let (a, b) = (*a, *b);
+
+            // Shift amounts are masked by the width of the value, which is
+            // what the shift instructions of the generated code do too.
+            let bits = ty.as_deref().map_or(64, ty_bits);
+            let shift = b as u32 & (bits - 1);
+
+            let value = match op {
+                Operator::Add => a.wrapping_add(b),
+                Operator::Subtract => a.wrapping_sub(b),
+                Operator::Multiply => a.wrapping_mul(b),
+
+                Operator::Divide | Operator::Modulo if b == 0 => {
+                    return Err(miette!("Division by zero at compile time!"))
+                }
+
+                Operator::Divide if unsigned => (a as u64 / b as u64) as i64,
+                Operator::Modulo if unsigned => (a as u64 % b as u64) as i64,
+                Operator::Divide => a.wrapping_div(b),
+                Operator::Modulo => a.wrapping_rem(b),
+                Operator::BitwiseAnd => a & b,
+                Operator::BitwiseOr => a | b,
+                Operator::BitwiseXor => a ^ b,
+                Operator::ShiftLeft => a.wrapping_shl(shift),
+                Operator::ShiftRight if unsigned => {
+                    (wrap(a, ty.as_deref().unwrap()) as u64 >> shift) as i64
+                }
+                Operator::ShiftRight => a >> shift,
+
+                op if op.is_comparison() => {
+                    let ord = if unsigned {
+                        (a as u64).cmp(&(b as u64))
+                    } else {
+                        a.cmp(&b)
+                    };
+
+                    return Ok(Value::Bool(compare(op, ord)));
+                }
+
+                _ => return Err(mismatch(&lhs, &rhs)),
+            };
+
+            Ok(match ty {
+                Some(ty) => Value::Int(wrap(value, &ty), Some(ty)),
+                None => Value::Int(value, None),
+            })
+        }
+
+        (Value::Float(a, lty), Value::Float(b, rty)) => {
+            let ty = lty.clone().or(rty.clone());
+            let (a, b) = (*a, *b);
+
+            let value = match op {
+                Operator::Add => a + b,
+                Operator::Subtract => a - b,
+                Operator::Multiply => a * b,
+                Operator::Divide => a / b,
+                Operator::Modulo => a % b,
+
+                op if op.is_comparison() => {
+                    let Some(ord) = a.partial_cmp(&b) else {
+                        return Ok(Value::Bool(op == Operator::NotEqual));
+                    };
+
+                    return Ok(Value::Bool(compare(op, ord)));
+                }
+
+                _ => return Err(mismatch(&lhs, &rhs)),
+            };
+
+            let value = match &ty {
+                Some(ty) => round_float(value, ty),
+                None => value,
+            };
+
+            Ok(Value::Float(value, ty))
+        }
+
+        (Value::Char(a), Value::Char(b)) if op.is_comparison() => {
+            Ok(Value::Bool(compare(op, a.cmp(b))))
+        }
+
+        (Value::Bool(a), Value::Bool(b)) if matches!(op, Operator::Equal | Operator::NotEqual) => {
+            Ok(Value::Bool(compare(op, a.cmp(b))))
+        }
+
+        (Value::Str(a), Value::Str(b)) if matches!(op, Operator::Equal | Operator::NotEqual) => {
+            Ok(Value::Bool(compare(op, a.cmp(b))))
+        }
+
+        _ => Err(mismatch(&lhs, &rhs)),
+    }
+}
+
+fn compare(op: Operator, ord: std::cmp::Ordering) -> bool {
+    match op {
+        Operator::Equal => ord.is_eq(),
+        Operator::NotEqual => ord.is_ne(),
+        Operator::Greater => ord.is_gt(),
+        Operator::Less => ord.is_lt(),
+        Operator::GreaterEqual => ord.is_ge(),
+        Operator::LessEqual => ord.is_le(),
+        _ => unreachable!(),
+    }
+}
+
+fn is_int(ty: &str) -> bool {
+    ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"].contains(&ty)
+}
+
+fn ty_unsigned(ty: &str) -> bool {
+    ["u8", "u16", "u32", "u64"].contains(&ty)
+}
+
+fn ty_bits(ty: &str) -> u32 {
+    match ty {
+        "i8" | "u8" => 8,
+        "i16" | "u16" => 16,
+        "i32" | "u32" => 32,
+        _ => 64,
+    }
+}
+
+/// Wrap an integer around to fit in `ty`, like the arithmetic the
+/// generated code does.
+fn wrap(value: i64, ty: &str) -> i64 {
+    match ty {
+        "i8" => value as i8 as i64,
+        "i16" => value as i16 as i64,
+        "i32" => value as i32 as i64,
+        "u8" => value as u8 as i64,
+        "u16" => value as u16 as i64,
+        "u32" => value as u32 as i64,
+        _ => value,
+    }
+}
+
+fn round_float(value: f64, ty: &str) -> f64 {
+    if ty == "f32" {
+        value as f32 as f64
+    } else {
+        value
+    }
+}
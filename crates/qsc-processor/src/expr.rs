@@ -1,4 +1,25 @@
-use qsc_ast::ast::{expr::ExpressionNode, node::data::NodeData};
+use qsc_ast::{
+    ast::{
+        expr::{
+            array::{ArrayExpr, IndexExpr},
+            binary::BinaryExpr,
+            cast::CastExpr,
+            enumeration::{EnumExpr, MatchExpr, Pattern},
+            method::MethodCallExpr,
+            operator::Operator,
+            pointer::{DerefExpr, RefExpr},
+            structure::StructExpr,
+            ExpressionNode,
+        },
+        node::{data::NodeData, ty::TypeNode, vis::Visibility, Node},
+        stmt::{
+            call::{CallArgument, CallNode},
+            StatementNode,
+        },
+    },
+    span::StaticSpan,
+};
+use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
 
 use crate::{ctx::ProcessorContext, Processor, Result};
 
@@ -12,13 +33,890 @@ impl Processor {
             ExpressionNode::Binary(binary) => {
                 binary.lhs = self.process_node(ctx, &mut binary.lhs)?;
                 binary.rhs = self.process_node(ctx, &mut binary.rhs)?;
+
+                self.coerce_operands(ctx, &mut binary.lhs, &mut binary.rhs)?;
+
+                if binary.operator.is_assign() {
+                    self.process_assign(ctx, binary)?;
+
+                    if let Some(operator) = binary.operator.base_op() {
+                        self.process_operands(ctx, binary, operator)?;
+                    }
+                } else if binary.operator.is_logical() {
+                    let func = ctx.func.clone().map(|v| v.name);
+                    let lhs = binary.lhs.data.get_type(&func, ctx.tree)?;
+                    let rhs = binary.rhs.data.get_type(&func, ctx.tree)?;
+
+                    if lhs != "bool" || rhs != "bool" {
+                        return Err(ProcessorError {
+                            src: ctx.tree.src.clone().into(),
+                            location: binary.span.into_source_span(),
+                            error: miette!(
+                                "Logical operators require boolean operands, found {} and {}!",
+                                lhs,
+                                rhs
+                            ),
+                        }
+                        .into());
+                    }
+                } else {
+                    self.process_pointer_op(ctx, binary)?;
+                    self.process_operands(ctx, binary, binary.operator)?;
+                }
             }
 
             ExpressionNode::Unary(unary) => {
                 unary.value = self.process_node(ctx, &mut unary.value)?;
             }
+
+            ExpressionNode::Struct(lit) => {
+                for field in &mut lit.fields {
+                    field.value = self.process_node(ctx, &mut field.value)?;
+                }
+
+                self.process_struct_lit(ctx, lit)?;
+            }
+
+            ExpressionNode::Enum(lit) => {
+                for value in &mut lit.values {
+                    *value = self.process_node(ctx, value)?;
+                }
+
+                self.process_enum_lit(ctx, lit)?;
+            }
+
+            ExpressionNode::Match(expr) => {
+                expr.value = self.process_node(ctx, &mut expr.value)?;
+                self.process_match(ctx, expr)?;
+
+                for arm in &mut expr.arms {
+                    arm.body = self.process_node(ctx, &mut arm.body)?;
+                }
+            }
+
+            // These only ever come from match patterns, which get
+            // checked along with the match itself.
+            ExpressionNode::VariantField(_) => {}
+
+            ExpressionNode::Array(lit) => {
+                for value in &mut lit.values {
+                    *value = self.process_node(ctx, value)?;
+                }
+
+                self.process_array_lit(ctx, lit)?;
+            }
+
+            ExpressionNode::Tuple(lit) => {
+                for value in &mut lit.values {
+                    *value = self.process_node(ctx, value)?;
+                }
+            }
+
+            ExpressionNode::Index(index) => {
+                index.value = self.process_node(ctx, &mut index.value)?;
+                index.index = self.process_node(ctx, &mut index.index)?;
+
+                self.process_index(ctx, index)?;
+            }
+
+            ExpressionNode::Cast(expr) => {
+                expr.value = self.process_node(ctx, &mut expr.value)?;
+                expr.type_ = self
+                    .process_type(ctx, expr.type_.clone())?
+                    .as_type()
+                    .unwrap();
+
+                self.process_cast(ctx, expr)?;
+            }
+
+            ExpressionNode::Ref(expr) => {
+                expr.value = self.process_node(ctx, &mut expr.value)?;
+
+                // `&square` is the same as `square`, since functions are
+                // already used by reference.
+                if let Ok(sym) = expr.value.data.as_symbol() {
+                    if self.is_function_value(ctx, &sym.value) {
+                        return Ok(NodeData::Symbol(sym));
+                    }
+                }
+
+                self.process_ref(ctx, expr)?;
+            }
+
+            // Closures have been lifted into functions of their own by now.
+            ExpressionNode::Closure(_) => {}
+
+            // And `comptime` expressions have been replaced by their results.
+            ExpressionNode::Comptime(_) => {}
+
+            // Spreads are replaced by their arguments when macros get expanded.
+            ExpressionNode::Spread(_) => {}
+
+            ExpressionNode::MacroCall(call) => self.process_macro_call(ctx, call)?,
+
+            ExpressionNode::Method(expr) => return self.process_method(ctx, expr),
+
+            ExpressionNode::Deref(expr) => {
+                expr.value = self.process_node(ctx, &mut expr.value)?;
+
+                // This errors if the value isn't a typed pointer.
+                ExpressionNode::Deref(expr.clone())
+                    .get_type(&ctx.func.clone().map(|v| v.name), ctx.tree)?;
+            }
+
+            ExpressionNode::Field(field) => {
+                field.value = self.process_node(ctx, &mut field.value)?;
+
+                // This errors if the field doesn't exist.
+                ExpressionNode::Field(field.clone())
+                    .get_type(&ctx.func.clone().map(|v| v.name), ctx.tree)?;
+            }
         };
 
         Ok(NodeData::Expr(expr))
     }
+
+    /// Turn a method call into a plain call to the method, with the value
+    /// it's called on as the first argument. The value is referenced or
+    /// dereferenced to match what the method takes as `self`.
+    pub fn process_method(
+        &self,
+        ctx: &mut ProcessorContext,
+        expr: &mut MethodCallExpr,
+    ) -> Result<NodeData> {
+        expr.value = self.process_node(ctx, &mut expr.value)?;
+
+        let func = ctx.func.clone().map(|v| v.name);
+        let ty = expr.value.data.get_type(&func, ctx.tree)?;
+
+        let Some(method) = ctx.tree.method(&ty, &expr.method.value) else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.method.span.into_source_span(),
+                error: miette!("Type {} has no method named {}!", ty, expr.method.value),
+            }
+            .into());
+        };
+
+        let Some(receiver) = method.args.first().filter(|arg| arg.name == "self") else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.method.span.into_source_span(),
+                error: miette!(
+                    "{} doesn't take self, so it has to be called as {}()!",
+                    expr.method.value,
+                    method.name
+                ),
+            }
+            .into());
+        };
+
+        let module = |name: &str| {
+            name.rsplit_once("::")
+                .map(|(path, _)| path.to_string())
+                .unwrap_or_default()
+        };
+
+        // Methods are private to the module of the type they belong to.
+        let owner = module(&module(&method.name));
+        let caller = module(&func.clone().unwrap_or_default());
+
+        if method.vis == Visibility::Private
+            && !owner.is_empty()
+            && caller != owner
+            && !caller.starts_with(&format!("{}::", owner))
+        {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.method.span.into_source_span(),
+                error: miette!("{} is private to module {}!", method.name, owner),
+            }
+            .into());
+        }
+
+        let span = expr.value.span.clone();
+        let wanted = receiver.type_.as_str();
+
+        let value = if TypeNode::pointee(&wanted).is_some() && TypeNode::pointee(&ty).is_none() {
+            if receiver.mutable {
+                self.check_mutable(ctx, &expr.value, &expr.span, true)?;
+            }
+
+            let value = RefExpr {
+                span: span.clone(),
+                value: expr.value.clone(),
+            };
+
+            self.process_ref(ctx, &value)?;
+
+            NodeData::Expr(ExpressionNode::Ref(value))
+        } else if TypeNode::pointee(&wanted).is_none() && TypeNode::pointee(&ty).is_some() {
+            NodeData::Expr(ExpressionNode::Deref(DerefExpr {
+                span: span.clone(),
+                value: expr.value.clone(),
+            }))
+        } else {
+            *expr.value.data.clone()
+        };
+
+        let mut args = vec![CallArgument {
+            span: span.clone(),
+            value: Node {
+                span,
+                data: Box::new(value),
+            },
+        }];
+
+        args.extend(expr.args.clone());
+
+        self.process_stmt(
+            ctx,
+            StatementNode::Call(CallNode {
+                span: expr.span.clone(),
+                func: method.name,
+                args,
+            }),
+        )
+    }
+
+    pub fn process_struct_lit(
+        &self,
+        ctx: &mut ProcessorContext,
+        lit: &mut StructExpr,
+    ) -> Result<()> {
+        let Some(item) = ctx.tree.structs().get(&lit.name).cloned() else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: lit.span.into_source_span(),
+                error: miette!("Cannot find struct: {}", lit.name),
+            }
+            .into());
+        };
+
+        let func = ctx.func.clone().map(|v| v.name);
+
+        for idx in 0..lit.fields.len() {
+            let value = &lit.fields[idx];
+            let Some(field) = item.field(&value.name) else {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: value.span.into_source_span(),
+                    error: miette!("Type {} has no field named {}!", lit.name, value.name),
+                }
+                .into());
+            };
+
+            if lit.fields[..idx].iter().any(|v| v.name == value.name) {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: value.span.into_source_span(),
+                    error: miette!("Field {} is specified more than once!", value.name),
+                }
+                .into());
+            }
+
+            let value = &mut lit.fields[idx];
+
+            self.coerce_literal(ctx, &mut value.value, &field.type_.as_str())?;
+
+            let ty = value.value.data.get_type(&func, ctx.tree)?;
+
+            if ty != field.type_.as_str() {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: value.span.into_source_span(),
+                    error: miette!(
+                        "Field {} has type {}, but was given a value of type {}!",
+                        value.name,
+                        field.type_.as_str(),
+                        ty
+                    ),
+                }
+                .into());
+            }
+        }
+
+        for field in &item.fields {
+            if !lit.fields.iter().any(|v| v.name == field.name) {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: lit.span.into_source_span(),
+                    error: miette!("Missing field {} in {} literal!", field.name, lit.name),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn process_enum_lit(&self, ctx: &mut ProcessorContext, lit: &mut EnumExpr) -> Result<()> {
+        let Some(item) = ctx.tree.enums().get(&lit.name).cloned() else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: lit.span.into_source_span(),
+                error: miette!("Cannot find enum: {}", lit.name),
+            }
+            .into());
+        };
+
+        let Some((_, variant)) = item.variant(&lit.variant) else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: lit.span.into_source_span(),
+                error: miette!("Enum {} has no variant named {}!", lit.name, lit.variant),
+            }
+            .into());
+        };
+
+        if variant.fields.len() != lit.values.len() {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: lit.span.into_source_span(),
+                error: miette!(
+                    "Variant {}::{} has {} fields, but was given {} values!",
+                    lit.name,
+                    lit.variant,
+                    variant.fields.len(),
+                    lit.values.len()
+                ),
+            }
+            .into());
+        }
+
+        let func = ctx.func.clone().map(|v| v.name);
+
+        for (field, value) in variant.fields.iter().zip(&mut lit.values) {
+            self.coerce_literal(ctx, value, &field.as_str())?;
+
+            let ty = value.data.get_type(&func, ctx.tree)?;
+
+            if ty != field.as_str() {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: value.span.into_source_span(),
+                    error: miette!("Expected a value of type {}, found {}!", field.as_str(), ty),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn process_array_lit(&self, ctx: &mut ProcessorContext, lit: &mut ArrayExpr) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+
+        // Unsuffixed literals take the type of the other elements.
+        let typed = lit
+            .values
+            .iter()
+            .find(|v| !v.data.as_literal().is_ok_and(|v| v.is_untyped()));
+
+        if let Some(typed) = typed {
+            let elem = typed.data.get_type(&func, ctx.tree)?;
+
+            for value in &mut lit.values {
+                self.coerce_literal(ctx, value, &elem)?;
+            }
+        }
+
+        // This errors if the array is empty.
+        let ty = ExpressionNode::Array(lit.clone()).get_type(&func, ctx.tree)?;
+        let (elem, _) = TypeNode::array_parts(&ty).unwrap();
+
+        for value in &lit.values {
+            let ty = value.data.get_type(&func, ctx.tree)?;
+
+            if ty != elem {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: value.span.into_source_span(),
+                    error: miette!("Expected an array element of type {}, found {}!", elem, ty),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn process_index(&self, ctx: &mut ProcessorContext, expr: &IndexExpr) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+
+        // This errors if the value isn't an array.
+        ExpressionNode::Index(expr.clone()).get_type(&func, ctx.tree)?;
+
+        let ty = expr.value.data.get_type(&func, ctx.tree)?;
+        let (_, len) = TypeNode::array_parts(&ty).unwrap();
+
+        if !expr.index.data.is_int(&func, ctx.tree)? {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.index.span.into_source_span(),
+                error: miette!(
+                    "Array indices must be integers, found {}!",
+                    expr.index.data.get_type(&func, ctx.tree)?
+                ),
+            }
+            .into());
+        }
+
+        // Constant indices can be checked right away.
+        if let Ok(index) = expr.index.data.as_literal().and_then(|v| v.as_int()) {
+            if index.value < 0 || index.value as usize >= len {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: expr.index.span.into_source_span(),
+                    error: miette!(
+                        "Index {} is out of bounds for an array of length {}!",
+                        index.value,
+                        len
+                    ),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check arithmetic on pointers, which can be offset by an integer,
+    /// subtracted from each other or compared.
+    pub fn process_pointer_op(
+        &self,
+        ctx: &mut ProcessorContext,
+        binary: &BinaryExpr,
+    ) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let lhs = binary.lhs.data.get_type(&func, ctx.tree)?;
+
+        if TypeNode::pointee(&lhs).is_none() {
+            return Ok(());
+        }
+
+        let rhs = binary.rhs.data.get_type(&func, ctx.tree)?;
+        let offset = binary.rhs.data.is_int(&func, ctx.tree)?;
+
+        let valid = match binary.operator {
+            Operator::Add => offset,
+            Operator::Subtract => offset || lhs == rhs,
+            operator => operator.is_comparison() && lhs == rhs,
+        };
+
+        if !valid {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: binary.span.into_source_span(),
+                error: miette!(
+                    "The {:?} operator is not supported for {} and {}!",
+                    binary.operator,
+                    lhs,
+                    rhs
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that `operator` can be used on the operands of `binary`. This
+    /// also catches operators used on type parameters that were given a
+    /// type without them, once a generic function is instantiated.
+    pub fn process_operands(
+        &self,
+        ctx: &mut ProcessorContext,
+        binary: &BinaryExpr,
+        operator: Operator,
+    ) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let lhs = binary.lhs.data.get_type(&func, ctx.tree)?;
+        let rhs = binary.rhs.data.get_type(&func, ctx.tree)?;
+
+        // Pointers are checked on their own.
+        if TypeNode::pointee(&lhs).is_some() {
+            return Ok(());
+        }
+
+        let int = |ty: &str| ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"].contains(&ty);
+        let float = |ty: &str| ["f32", "f64"].contains(&ty);
+
+        let equality = matches!(operator, Operator::Equal | Operator::NotEqual);
+        let bitwise = matches!(
+            operator,
+            Operator::BitwiseAnd | Operator::BitwiseOr | Operator::BitwiseXor
+        );
+
+        let valid = match (lhs.as_str(), rhs.as_str()) {
+            (lhs, rhs) if int(lhs) && int(rhs) => true,
+
+            (lhs, rhs) if float(lhs) && float(rhs) => {
+                operator.is_comparison()
+                    || matches!(
+                        operator,
+                        Operator::Add
+                            | Operator::Subtract
+                            | Operator::Multiply
+                            | Operator::Divide
+                            | Operator::Modulo
+                    )
+            }
+
+            ("bool", "bool") => equality || bitwise,
+            ("char", "char") => operator.is_comparison(),
+            ("str", "str") => equality,
+            _ => false,
+        };
+
+        if !valid {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: binary.span.into_source_span(),
+                error: miette!(
+                    "The {:?} operator is not supported for {} and {}!",
+                    operator,
+                    lhs,
+                    rhs
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn process_cast(&self, ctx: &mut ProcessorContext, expr: &CastExpr) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let from = expr.value.data.get_type(&func, ctx.tree)?;
+        let to = expr.type_.as_str();
+
+        let int = |ty: &str| ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"].contains(&ty);
+        let float = |ty: &str| ["f32", "f64"].contains(&ty);
+        let pointer = |ty: &str| ty == "ptr" || ty == "str" || TypeNode::pointee(ty).is_some();
+
+        let valid = from == to
+            || (int(&to) && (int(&from) || float(&from) || pointer(&from)))
+            || (int(&to) && ["char", "bool"].contains(&from.as_str()))
+            || (float(&to) && (int(&from) || float(&from)))
+            || (pointer(&to) && (int(&from) || pointer(&from)))
+            || (to == "char" && int(&from));
+
+        if !valid {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.span.into_source_span(),
+                error: miette!("Cannot cast a value of type {} to {}!", from, to),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn process_ref(&self, ctx: &mut ProcessorContext, expr: &RefExpr) -> Result<()> {
+        let mut target = expr.value.clone();
+
+        while let Ok(expr) = target.data.as_expr() {
+            target = match expr {
+                ExpressionNode::Field(field) => field.value,
+                ExpressionNode::Index(index) => index.value,
+
+                // Whatever the pointer points to is a place already.
+                ExpressionNode::Deref(_) => return Ok(()),
+
+                _ => break,
+            };
+        }
+
+        let Ok(sym) = target.data.as_symbol() else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.value.span.into_source_span(),
+                error: miette!("Cannot take the address of a temporary value!"),
+            }
+            .into());
+        };
+
+        let local = ctx
+            .func
+            .as_ref()
+            .is_some_and(|func| func.variables().contains_key(&sym.value));
+
+        if !local
+            && ctx
+                .tree
+                .globals()
+                .get(&sym.value)
+                .is_some_and(|v| v.constant)
+        {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.value.span.into_source_span(),
+                error: miette!("Cannot take the address of constant \"{}\"!", sym.value),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn process_match(&self, ctx: &mut ProcessorContext, expr: &MatchExpr) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let ty = expr.value.data.get_type(&func, ctx.tree)?;
+
+        let Some(item) = ctx.tree.enums().get(&ty).cloned() else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.value.span.into_source_span(),
+                error: miette!("Cannot match on a value of type {}!", ty),
+            }
+            .into());
+        };
+
+        let mut matched = Vec::new();
+        let mut wildcard = false;
+
+        for arm in &expr.arms {
+            let pattern = match &arm.pattern {
+                Pattern::Variant(pattern) => pattern,
+
+                Pattern::Wildcard(_) => {
+                    wildcard = true;
+                    continue;
+                }
+            };
+
+            if pattern.name != item.name {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: pattern.span.into_source_span(),
+                    error: miette!(
+                        "A pattern for {} cannot match a value of type {}!",
+                        pattern.name,
+                        item.name
+                    ),
+                }
+                .into());
+            }
+
+            let Some((_, variant)) = item.variant(&pattern.variant) else {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: pattern.span.into_source_span(),
+                    error: miette!(
+                        "Enum {} has no variant named {}!",
+                        item.name,
+                        pattern.variant
+                    ),
+                }
+                .into());
+            };
+
+            if variant.fields.len() != pattern.bindings.len() {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: pattern.span.into_source_span(),
+                    error: miette!(
+                        "Variant {}::{} has {} fields, but the pattern has {}!",
+                        item.name,
+                        variant.name,
+                        variant.fields.len(),
+                        pattern.bindings.len()
+                    ),
+                }
+                .into());
+            }
+
+            if matched.contains(&variant.name) {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: pattern.span.into_source_span(),
+                    error: miette!(
+                        "Variant {}::{} is matched more than once!",
+                        item.name,
+                        variant.name
+                    ),
+                }
+                .into());
+            }
+
+            matched.push(variant.name.clone());
+        }
+
+        let missing = item
+            .variants
+            .iter()
+            .filter(|v| !matched.contains(&v.name))
+            .map(|v| format!("{}::{}", item.name, v.name))
+            .collect::<Vec<_>>();
+
+        if !wildcard && !missing.is_empty() {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: expr.span.into_source_span(),
+                error: miette!(
+                    "Match is not exhaustive, missing variants: {}",
+                    missing.join(", ")
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    pub fn process_assign(&self, ctx: &mut ProcessorContext, binary: &BinaryExpr) -> Result<()> {
+        self.check_mutable(ctx, &binary.lhs, &binary.span, false)?;
+        self.process_assign_type(ctx, binary)
+    }
+
+    /// Check that the variable or static holding `place` can be changed,
+    /// for assigning to it or, with `borrow`, for calling a method taking
+    /// `&mut self` on it. Values that aren't stored anywhere can always be
+    /// borrowed.
+    pub fn check_mutable(
+        &self,
+        ctx: &ProcessorContext,
+        place: &Node,
+        span: &StaticSpan,
+        borrow: bool,
+    ) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let through_pointer = |value: &Node| {
+            value
+                .data
+                .get_type(&func, ctx.tree)
+                .is_ok_and(|ty| TypeNode::pointee(&ty).is_some())
+        };
+
+        // Changing a field or an element needs the variable holding the
+        // struct or array.
+        let mut target = place.clone();
+
+        while let Ok(expr) = target.data.as_expr() {
+            target = match expr {
+                // Writing through a pointer doesn't touch the pointer itself.
+                ExpressionNode::Field(field) if through_pointer(&field.value) => return Ok(()),
+                ExpressionNode::Index(index) if through_pointer(&index.value) => return Ok(()),
+                ExpressionNode::Deref(_) => return Ok(()),
+
+                ExpressionNode::Field(field) => field.value,
+                ExpressionNode::Index(index) => index.value,
+
+                _ => break,
+            };
+        }
+
+        let Ok(sym) = target.data.as_symbol() else {
+            if borrow {
+                return Ok(());
+            }
+
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: place.span.into_source_span(),
+                error: miette!("Invalid left-hand side of assignment!"),
+            }
+            .into());
+        };
+
+        let var = ctx
+            .func
+            .as_ref()
+            .and_then(|func| func.variables().get(&sym.value).cloned());
+
+        let error = match var {
+            Some(var) if !var.mutable && borrow => Some(format!(
+                "Cannot borrow immutable variable \"{}\" as mutable!",
+                sym.value
+            )),
+
+            Some(var) if !var.mutable => Some(format!(
+                "Cannot assign twice to immutable variable \"{}\"!",
+                sym.value
+            )),
+
+            Some(_) => None,
+
+            None => match ctx.tree.globals().get(&sym.value) {
+                Some(global) if global.constant && borrow => Some(format!(
+                    "Cannot borrow constant \"{}\" as mutable!",
+                    sym.value
+                )),
+
+                Some(global) if global.constant => {
+                    Some(format!("Cannot assign to constant \"{}\"!", sym.value))
+                }
+
+                Some(global) if !global.mutable && borrow => Some(format!(
+                    "Cannot borrow immutable static \"{}\" as mutable!",
+                    sym.value
+                )),
+
+                Some(global) if !global.mutable => Some(format!(
+                    "Cannot assign to immutable static \"{}\"!",
+                    sym.value
+                )),
+
+                Some(_) => None,
+
+                // Functions used as values aren't stored anywhere.
+                None if borrow => None,
+
+                None => {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: place.span.into_source_span(),
+                        error: miette!("Cannot find symbol: {}", sym.value),
+                    }
+                    .into());
+                }
+            },
+        };
+
+        match error {
+            Some(error) => Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: span.into_source_span(),
+                error: miette!("{}", error),
+            }
+            .into()),
+
+            None => Ok(()),
+        }
+    }
+
+    pub fn process_assign_type(
+        &self,
+        ctx: &mut ProcessorContext,
+        binary: &BinaryExpr,
+    ) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let lhs = binary.lhs.data.get_type(&func, ctx.tree)?;
+        let rhs = binary.rhs.data.get_type(&func, ctx.tree)?;
+
+        // Pointers can be moved along by an integer offset.
+        let offset = TypeNode::pointee(&lhs).is_some()
+            && matches!(
+                binary.operator,
+                Operator::AddAssign | Operator::SubtractAssign
+            )
+            && binary.rhs.data.is_int(&func, ctx.tree)?;
+
+        if lhs != rhs && !offset {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: binary.span.into_source_span(),
+                error: miette!(
+                    "Cannot assign a value of type {} to a variable of type {}!",
+                    rhs,
+                    lhs
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+
+use qsc_ast::{
+    ast::{
+        decl::{func::FunctionNode, DeclarationNode},
+        expr::ExpressionNode,
+        node::{attr::Attribute, data::NodeData, ty::TypeNode, Node},
+        stmt::{call::CallNode, StatementNode},
+    },
+    compat::WrappedNamedSource,
+};
+use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
+
+use crate::{Processor, Result};
+
+impl Processor {
+    /// Replace each generic function with a copy of it for every set of
+    /// types it is called with. The copies are named after the types, like
+    /// `max<i32>`, and take the place of the generic function in the tree.
+    pub fn monomorphize(&mut self) -> Result<()> {
+        let templates = self
+            .ast
+            .functions()
+            .into_iter()
+            .filter(|(_, func)| !func.generics.is_empty())
+            .collect::<HashMap<_, _>>();
+
+        if templates.is_empty() {
+            return Ok(());
+        }
+
+        for template in templates.values() {
+            let src = &self.sources[self.function_index(&template.name)];
+
+            self.check_bound_usage(template, src)?;
+
+            for attr in ["NoMangle", "Export"] {
+                if let Some(attr) = Attribute::find(&template.attrs, attr) {
+                    return Err(ProcessorError {
+                        src: src.clone().into(),
+                        location: attr.span.into_source_span(),
+                        error: miette!("@{} can't be used on generic functions!", attr.name),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        let originals = self.ast.data.len();
+        let mut instances = Vec::new();
+
+        let mut queue = self
+            .ast
+            .functions()
+            .into_values()
+            .filter(|func| func.generics.is_empty())
+            .map(|func| func.name)
+            .collect::<Vec<_>>();
+
+        while let Some(name) = queue.pop() {
+            // The tree is updated after every call, so the types of values
+            // returned by earlier generic calls can be found.
+            loop {
+                let idx = self.function_index(&name);
+                let mut func = self.ast.data[idx].data.as_decl()?.as_function()?;
+                let src = self.sources[idx].clone();
+
+                let Some(call) = func
+                    .content
+                    .data
+                    .iter_mut()
+                    .find_map(|node| find_generic_call(node, &templates))
+                else {
+                    break;
+                };
+
+                let template = &templates[&call.func];
+                let types = self.infer_generics(template, call, &name, &src)?;
+
+                self.check_bounds(template, &types, call, &src)?;
+
+                let instance = format!("{}<{}>", template.name, types.join(", "));
+
+                if !self.ast.functions().contains_key(&instance) {
+                    let template_idx = self.function_index(&template.name);
+
+                    self.ast.data.push(instantiate(template, &types, &instance));
+                    self.sources.push(self.sources[template_idx].clone());
+
+                    instances.push(template.name.clone());
+                    queue.push(instance.clone());
+                }
+
+                call.func = instance;
+
+                *self.ast.data[idx].data = NodeData::Declaration(DeclarationNode::Function(func));
+            }
+        }
+
+        let mut data = Vec::new();
+        let mut sources = Vec::new();
+
+        for idx in 0..originals {
+            let node = &self.ast.data[idx];
+
+            let Some(template) = node
+                .data
+                .as_decl()
+                .and_then(|decl| decl.as_function())
+                .ok()
+                .filter(|func| !func.generics.is_empty())
+            else {
+                data.push(node.clone());
+                sources.push(self.sources[idx].clone());
+                continue;
+            };
+
+            for (offset, name) in instances.iter().enumerate() {
+                if *name == template.name {
+                    data.push(self.ast.data[originals + offset].clone());
+                    sources.push(self.sources[originals + offset].clone());
+                }
+            }
+        }
+
+        self.ast.data = data;
+        self.sources = sources;
+
+        Ok(())
+    }
+
+    pub fn function_index(&self, name: &str) -> usize {
+        self.ast
+            .data
+            .iter()
+            .position(|node| {
+                node.data
+                    .as_decl()
+                    .and_then(|decl| decl.as_function())
+                    .is_ok_and(|func| func.name == name)
+            })
+            .unwrap()
+    }
+
+    /// Work out the types to use for the type parameters of `template`,
+    /// from the types of the arguments `call` passes to it. Untyped
+    /// literals only decide a type if nothing else does.
+    pub fn infer_generics(
+        &self,
+        template: &FunctionNode,
+        call: &CallNode,
+        func: &str,
+        src: &WrappedNamedSource<String>,
+    ) -> Result<Vec<String>> {
+        let func = Some(func.to_string());
+        let mut types = HashMap::<String, String>::new();
+
+        for untyped in [false, true] {
+            for (param, arg) in template.args.iter().zip(&call.args) {
+                let is_untyped = arg
+                    .value
+                    .data
+                    .as_literal()
+                    .is_ok_and(|lit| lit.is_untyped());
+
+                if is_untyped != untyped {
+                    continue;
+                }
+
+                let ty = arg.value.data.get_type(&func, &self.ast)?;
+                let mut found = HashMap::new();
+
+                unify(&param.type_.as_str(), &ty, &template.generics, &mut found);
+
+                for (name, ty) in found {
+                    match types.get(&name) {
+                        // A literal only gives way if it can take the type
+                        // that was inferred already.
+                        Some(prev)
+                            if *prev != ty
+                                && (!untyped || !literal_fits(&arg.value.data, prev)) =>
+                        {
+                            return Err(ProcessorError {
+                                src: src.clone().into(),
+                                location: arg.span.into_source_span(),
+                                error: miette!(
+                                    "Type parameter {} of {} can't be both {} and {}!",
+                                    name,
+                                    template.name,
+                                    prev,
+                                    ty
+                                ),
+                            }
+                            .into());
+                        }
+
+                        Some(_) => {}
+
+                        None => {
+                            types.insert(name, ty);
+                        }
+                    }
+                }
+            }
+        }
+
+        template
+            .generics
+            .iter()
+            .map(|name| {
+                types.get(name).cloned().ok_or_else(|| {
+                    ProcessorError {
+                        src: src.clone().into(),
+                        location: call.span.into_source_span(),
+                        error: miette!(
+                            "Cannot infer type parameter {} of {}!",
+                            name,
+                            template.name
+                        ),
+                    }
+                    .into()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whether an untyped literal can be given the type `ty`, the way
+/// [`Processor::coerce_literal`] would.
+fn literal_fits(data: &NodeData, ty: &str) -> bool {
+    let int = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"].contains(&ty);
+    let float = ["f32", "f64"].contains(&ty);
+
+    match data.as_literal() {
+        Ok(lit) if lit.is_int() => int,
+        Ok(lit) if lit.is_float() => float,
+        _ => false,
+    }
+}
+
+/// Find the first call to a generic function in `node`. Calls in the
+/// arguments come first, since their types are needed to infer the
+/// types of the call they are in.
+fn find_generic_call<'a>(
+    node: &'a mut Node,
+    templates: &HashMap<String, FunctionNode>,
+) -> Option<&'a mut CallNode> {
+    let nested = node.data.children().into_iter().any(|child| {
+        let mut found = false;
+
+        child.walk(&mut |node| {
+            if let Ok(StatementNode::Call(call)) = node.data.as_stmt() {
+                found |= templates.contains_key(&call.func);
+            }
+        });
+
+        found
+    });
+
+    if nested {
+        return node
+            .data
+            .children_mut()
+            .into_iter()
+            .find_map(|child| find_generic_call(child, templates));
+    }
+
+    match node.data.as_mut() {
+        NodeData::Statement(StatementNode::Call(call)) if templates.contains_key(&call.func) => {
+            Some(call)
+        }
+
+        _ => None,
+    }
+}
+
+/// Match a parameter type against the type of its argument, recording
+/// what each type parameter in it stands for.
+fn unify(param: &str, arg: &str, generics: &[String], found: &mut HashMap<String, String>) {
+    if generics.iter().any(|name| name == param) {
+        found.insert(param.to_string(), arg.to_string());
+    } else if let (Some((param, _)), Some((arg, _))) =
+        (TypeNode::array_parts(param), TypeNode::array_parts(arg))
+    {
+        unify(param, arg, generics, found);
+    } else if let (Some(param), Some(arg)) = (TypeNode::pointee(param), TypeNode::pointee(arg)) {
+        unify(param, arg, generics, found);
+    } else if let (Some(params), Some(args)) =
+        (TypeNode::tuple_parts(param), TypeNode::tuple_parts(arg))
+    {
+        for (param, arg) in params.iter().zip(&args) {
+            unify(param, arg, generics, found);
+        }
+    } else if let (Some((params, param_ret)), Some((args, arg_ret))) = (
+        TypeNode::function_parts(param),
+        TypeNode::function_parts(arg),
+    ) {
+        for (param, arg) in params.iter().zip(&args) {
+            unify(param, arg, generics, found);
+        }
+
+        if let (Some(param), Some(arg)) = (param_ret, arg_ret) {
+            unify(&param, &arg, generics, found);
+        }
+    }
+}
+
+/// Replace the type parameters in a type with the types they stand for.
+pub fn substitute(ty: &str, types: &HashMap<&String, &String>) -> String {
+    if let Some((elem, len)) = TypeNode::array_parts(ty) {
+        return TypeNode::array(&substitute(elem, types), len);
+    }
+
+    if let Some(pointee) = TypeNode::pointee(ty) {
+        return TypeNode::pointer(&substitute(pointee, types));
+    }
+
+    if let Some(elems) = TypeNode::tuple_parts(ty) {
+        let elems = elems
+            .iter()
+            .map(|elem| substitute(elem, types))
+            .collect::<Vec<_>>();
+
+        return TypeNode::tuple(&elems);
+    }
+
+    if let Some((params, ret)) = TypeNode::function_parts(ty) {
+        let params = params
+            .iter()
+            .map(|param| substitute(param, types))
+            .collect::<Vec<_>>();
+
+        return TypeNode::function(&params, ret.map(|ret| substitute(&ret, types)).as_deref());
+    }
+
+    types
+        .get(&ty.to_string())
+        .map(|ty| ty.to_string())
+        .unwrap_or(ty.to_string())
+}
+
+fn substitute_node(ty: &mut TypeNode, types: &HashMap<&String, &String>) {
+    ty.name = substitute(&ty.name, types);
+
+    for generic in &mut ty.generics {
+        generic.value = substitute(&generic.value, types);
+    }
+}
+
+/// Make a copy of a generic function with its type parameters replaced.
+fn instantiate(template: &FunctionNode, types: &[String], name: &str) -> Node {
+    let types = template
+        .generics
+        .iter()
+        .zip(types)
+        .collect::<HashMap<_, _>>();
+    let mut func = template.clone();
+
+    func.name = name.to_string();
+    func.generics = Vec::new();
+
+    substitute_function(&mut func, &types);
+
+    Node {
+        span: template.span.clone(),
+        data: Box::new(NodeData::Declaration(DeclarationNode::Function(func))),
+    }
+}
+
+/// Replace the type parameters in the signature and the body of a
+/// function with the types they stand for.
+pub fn substitute_function(func: &mut FunctionNode, types: &HashMap<&String, &String>) {
+    for arg in &mut func.args {
+        substitute_node(&mut arg.type_, types);
+    }
+
+    if let Some(ret) = &mut func.ret {
+        substitute_node(ret, types);
+    }
+
+    func.content.walk_mut(&mut |node| match node.data.as_mut() {
+        NodeData::Declaration(DeclarationNode::Variable(var)) => {
+            if let Some(ty) = &mut var.type_ {
+                substitute_node(ty, types);
+            }
+        }
+
+        NodeData::Expr(ExpressionNode::Cast(expr)) => substitute_node(&mut expr.type_, types),
+
+        // Paths like `T::new()` call the function of the type `T` stands for.
+        NodeData::Statement(StatementNode::Call(call)) => {
+            if let Some((ty, func)) = call.func.rsplit_once("::") {
+                if let Some(ty) = types.get(&ty.to_string()) {
+                    call.func = format!("{}::{}", ty, func);
+                }
+            }
+        }
+
+        NodeData::Expr(ExpressionNode::Closure(expr)) => {
+            for arg in &mut expr.args {
+                if let Some(ty) = &mut arg.type_ {
+                    substitute_node(ty, types);
+                }
+            }
+
+            if let Some(ret) = &mut expr.ret {
+                substitute_node(ret, types);
+            }
+        }
+
+        _ => {}
+    });
+}
@@ -6,32 +6,69 @@ extern crate log;
 #[macro_use]
 extern crate miette;
 
+pub mod attr;
 pub mod block;
+pub mod closure;
+pub mod comptime;
 pub mod ctx;
 pub mod decl;
+pub mod eval;
 pub mod expr;
+pub mod generic;
+pub mod literal;
+pub mod macros;
+pub mod module;
 pub mod stmt;
 pub mod sym;
+pub mod traits;
 pub mod ty;
 
+use std::collections::HashMap;
+
 use ctx::ProcessorContext;
-use qsc_ast::ast::{
-    node::{data::NodeData, Node},
-    AbstractTree,
+use qsc_ast::{
+    ast::{
+        decl::macros::MacroNode,
+        node::{data::NodeData, Node},
+        AbstractTree,
+    },
+    compat::WrappedNamedSource,
 };
-use qsc_core::error::Result;
+use qsc_core::error::{processor::ProcessorWarning, Result};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug)]
 pub struct Processor {
     pub ast: AbstractTree,
+
+    /// The source each top-level declaration came from, since modules
+    /// live in files of their own.
+    pub sources: Vec<WrappedNamedSource<String>>,
+
+    /// Problems found while processing that don't stop compilation.
+    pub warnings: Vec<ProcessorWarning>,
+
+    /// The macros in the program by their full path, with the source they
+    /// were declared in.
+    pub macros: HashMap<String, (MacroNode, WrappedNamedSource<String>)>,
 }
 
 impl Processor {
     pub fn new(ast: AbstractTree) -> Self {
-        Self { ast }
+        Self {
+            ast,
+            sources: Vec::new(),
+            warnings: Vec::new(),
+            macros: HashMap::new(),
+        }
     }
 
     pub fn process(&mut self) -> Result<AbstractTree> {
+        self.resolve_modules()?;
+        self.expand_macros()?;
+        self.evaluate_comptime()?;
+        self.monomorphize()?;
+        self.lift_closures()?;
+
         let mut ast = self.ast.clone();
         let ptr = &mut ast;
         let ptr2: &mut AbstractTree;
@@ -42,10 +79,14 @@ impl Processor {
 
         let mut ctx = ProcessorContext::new(ptr);
 
-        for node in &mut ptr2.data {
+        for (node, src) in ptr2.data.iter_mut().zip(&self.sources) {
+            ctx.tree.src = src.clone();
             *node = self.process_node(&mut ctx, node)?;
         }
 
+        ctx.tree.src = self.ast.src.clone();
+        self.warnings = ctx.warnings;
+
         Ok(ast)
     }
 
@@ -54,7 +95,7 @@ impl Processor {
             NodeData::Block(block) => self.process_block(ctx, block),
             NodeData::Declaration(decl) => self.process_decl(ctx, decl),
             NodeData::Expr(expr) => self.process_expr(ctx, expr),
-            NodeData::Literal(lit) => Ok(NodeData::Literal(lit)),
+            NodeData::Literal(lit) => self.process_literal(ctx, lit),
             NodeData::Statement(stmt) => self.process_stmt(ctx, stmt),
             NodeData::Symbol(sym) => self.process_symbol(ctx, sym),
             NodeData::Type(ty) => self.process_type(ctx, ty),
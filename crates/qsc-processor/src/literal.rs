@@ -0,0 +1,128 @@
+use qsc_ast::ast::{
+    expr::{operator::Operator, ExpressionNode},
+    literal::LiteralNode,
+    node::{data::NodeData, ty::TypeNode, Node},
+};
+use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
+
+use crate::{ctx::ProcessorContext, Processor, Result};
+
+impl Processor {
+    pub fn process_literal(&self, ctx: &ProcessorContext, lit: LiteralNode) -> Result<NodeData> {
+        let LiteralNode::Int(int) = &lit else {
+            return Ok(NodeData::Literal(lit));
+        };
+
+        let Some(ty) = &int.type_ else {
+            return Ok(NodeData::Literal(lit));
+        };
+
+        // 64-bit literals are stored as an `i64` either way, so anything
+        // that made it through the lexer fits.
+        let (min, max) = match ty.as_str() {
+            "i8" => (i8::MIN as i64, i8::MAX as i64),
+            "i16" => (i16::MIN as i64, i16::MAX as i64),
+            "i32" => (i32::MIN as i64, i32::MAX as i64),
+            "u8" => (0, u8::MAX as i64),
+            "u16" => (0, u16::MAX as i64),
+            "u32" => (0, u32::MAX as i64),
+            _ => return Ok(NodeData::Literal(lit)),
+        };
+
+        if int.value < min || int.value > max {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: int.span.into_source_span(),
+                error: miette!("Literal {} is out of range for {}!", int.value, ty),
+            }
+            .into());
+        }
+
+        Ok(NodeData::Literal(lit))
+    }
+
+    /// Give unsuffixed number literals in `node` the type `target`, if they
+    /// are the same kind of number. This looks through arithmetic, so
+    /// `1 + 2` becomes an `i64` as a whole when an `i64` is expected.
+    pub fn coerce_literal(
+        &self,
+        ctx: &ProcessorContext,
+        node: &mut Node,
+        target: &str,
+    ) -> Result<()> {
+        let int = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"].contains(&target);
+        let float = ["f32", "f64"].contains(&target);
+
+        match node.data.as_mut() {
+            NodeData::Literal(lit) if lit.is_untyped() => {
+                match lit {
+                    LiteralNode::Int(lit) if int => lit.type_ = Some(target.to_string()),
+                    LiteralNode::Float(lit) if float => lit.type_ = Some(target.to_string()),
+                    _ => return Ok(()),
+                }
+
+                *node.data = self.process_literal(ctx, lit.clone())?;
+            }
+
+            NodeData::Expr(ExpressionNode::Unary(unary))
+                if matches!(unary.operator, Operator::Add | Operator::BitwiseNot) =>
+            {
+                self.coerce_literal(ctx, &mut unary.value, target)?;
+            }
+
+            NodeData::Expr(ExpressionNode::Binary(binary))
+                if !binary.operator.is_assign()
+                    && !binary.operator.is_comparison()
+                    && !binary.operator.is_logical() =>
+            {
+                self.coerce_literal(ctx, &mut binary.lhs, target)?;
+
+                // The amount to shift by keeps its own type.
+                if !matches!(binary.operator, Operator::ShiftLeft | Operator::ShiftRight) {
+                    self.coerce_literal(ctx, &mut binary.rhs, target)?;
+                }
+            }
+
+            NodeData::Expr(ExpressionNode::Array(lit)) => {
+                if let Some((elem, _)) = TypeNode::array_parts(target) {
+                    for value in &mut lit.values {
+                        self.coerce_literal(ctx, value, elem)?;
+                    }
+                }
+            }
+
+            NodeData::Expr(ExpressionNode::Tuple(lit)) => {
+                if let Some(elems) = TypeNode::tuple_parts(target) {
+                    for (value, elem) in lit.values.iter_mut().zip(&elems) {
+                        self.coerce_literal(ctx, value, elem)?;
+                    }
+                }
+            }
+
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Make the untyped side of a binary operation take the type of the
+    /// other side, like `x + 1` with an `i64` x.
+    pub fn coerce_operands(
+        &self,
+        ctx: &ProcessorContext,
+        lhs: &mut Node,
+        rhs: &mut Node,
+    ) -> Result<()> {
+        let func = ctx.func.clone().map(|v| v.name);
+        let lhs_ty = lhs.data.get_type(&func, ctx.tree)?;
+        let rhs_ty = rhs.data.get_type(&func, ctx.tree)?;
+
+        if lhs_ty == rhs_ty {
+            return Ok(());
+        }
+
+        // Only unsuffixed literals are changed, so at most one side will.
+        self.coerce_literal(ctx, rhs, &lhs_ty)?;
+        self.coerce_literal(ctx, lhs, &rhs_ty)
+    }
+}
@@ -0,0 +1,448 @@
+use std::{collections::HashMap, mem};
+
+use qsc_ast::{
+    ast::{
+        decl::{macros::MacroNode, DeclarationNode},
+        expr::{macros::MacroCallExpr, ExpressionNode},
+        node::{block::Block, data::NodeData, Node},
+        stmt::{call::CallArgument, StatementNode},
+    },
+    compat::WrappedNamedSource,
+    span::StaticSpan,
+};
+use qsc_core::{
+    conv::IntoSourceSpan,
+    error::processor::{MacroError, ProcessorError},
+};
+
+use crate::{ctx::ProcessorContext, Processor, Result};
+
+/// How deep macros can be expanded inside of each other.
+const MAX_DEPTH: usize = 64;
+
+/// The arguments a macro was given, by the names of its parameters.
+#[derive(Debug, Clone, Default)]
+struct Bindings {
+    args: HashMap<String, Node>,
+
+    /// The arguments given for the variadic parameter.
+    lists: HashMap<String, Vec<Node>>,
+}
+
+impl Processor {
+    /// Replace every macro invocation with a copy of the macro's body. The
+    /// definitions are taken out of the tree, and kept so errors in the
+    /// expanded code can point back at them.
+    pub fn expand_macros(&mut self) -> Result<()> {
+        let mut data = Vec::new();
+        let mut sources = Vec::new();
+
+        for (node, src) in mem::take(&mut self.ast.data)
+            .into_iter()
+            .zip(mem::take(&mut self.sources))
+        {
+            if let NodeData::Declaration(DeclarationNode::Macro(item)) = node.data.as_ref() {
+                self.macros.insert(item.name.clone(), (item.clone(), src));
+                continue;
+            }
+
+            data.push(node);
+            sources.push(src);
+        }
+
+        let mut count = 0;
+
+        for (node, src) in data.iter_mut().zip(&sources) {
+            self.expand(node, src, 0, &mut count)?;
+        }
+
+        self.ast.data = data;
+        self.sources = sources;
+
+        Ok(())
+    }
+
+    fn expand(
+        &self,
+        node: &mut Node,
+        src: &WrappedNamedSource<String>,
+        depth: usize,
+        count: &mut usize,
+    ) -> Result<()> {
+        let error = |span: &StaticSpan, error| -> Result<()> {
+            Err(ProcessorError {
+                src: src.clone().into(),
+                location: span.into_source_span(),
+                error,
+            }
+            .into())
+        };
+
+        match node.data.as_mut() {
+            NodeData::Expr(ExpressionNode::MacroCall(call)) if call.expansion.is_none() => {
+                let (item, def) = &self.macros[&call.name];
+
+                if depth >= MAX_DEPTH {
+                    return error(
+                        &call.span,
+                        miette!(
+                            "Macro {} is expanded too many times inside itself!",
+                            call.name
+                        ),
+                    );
+                }
+
+                // The arguments are code from where the macro is invoked, so
+                // they are expanded there too.
+                for arg in &mut call.args {
+                    self.expand(arg, src, depth, count)?;
+                }
+
+                *count += 1;
+
+                let mut expansion = self.expand_call(call, item, def, src, *count)?;
+
+                for node in &mut expansion.data {
+                    self.expand(node, def, depth + 1, count)?;
+                }
+
+                call.expansion = Some(expansion);
+
+                return Ok(());
+            }
+
+            NodeData::Expr(ExpressionNode::Spread(expr)) => {
+                return error(
+                    &expr.span,
+                    miette!("{}... can only be used in a macro!", expr.name),
+                )
+            }
+
+            NodeData::Statement(StatementNode::Repeat(node)) => {
+                return error(
+                    &node.span,
+                    miette!(
+                        "for ... in {}... can only be used in a macro!",
+                        node.list.value
+                    ),
+                )
+            }
+
+            _ => {}
+        }
+
+        for child in node.data.children_mut() {
+            self.expand(child, src, depth, count)?;
+        }
+
+        Ok(())
+    }
+
+    /// Make a copy of the body of `item` for an invocation of it. The
+    /// variables it declares get names of their own, so they can't clash
+    /// with the ones where it is expanded.
+    fn expand_call(
+        &self,
+        call: &MacroCallExpr,
+        item: &MacroNode,
+        def: &WrappedNamedSource<String>,
+        src: &WrappedNamedSource<String>,
+        id: usize,
+    ) -> Result<Block> {
+        let fixed = item.params.len() - item.variadic().is_some() as usize;
+
+        let wrong = match item.variadic() {
+            Some(_) => call.args.len() < fixed,
+            None => call.args.len() != fixed,
+        };
+
+        if wrong {
+            return Err(ProcessorError {
+                src: src.clone().into(),
+                location: call.span.into_source_span(),
+                error: match item.variadic() {
+                    Some(_) => miette!(
+                        "Macro {} takes at least {} arguments, but {} were given!",
+                        call.name,
+                        fixed,
+                        call.args.len()
+                    ),
+
+                    None => miette!(
+                        "Macro {} takes {} arguments, but {} were given!",
+                        call.name,
+                        fixed,
+                        call.args.len()
+                    ),
+                },
+            }
+            .into());
+        }
+
+        let mut bindings = Bindings::default();
+
+        for (param, arg) in item.params.iter().zip(&call.args) {
+            if !param.variadic {
+                bindings.args.insert(param.name.clone(), arg.clone());
+            }
+        }
+
+        if let Some(param) = item.variadic() {
+            bindings
+                .lists
+                .insert(param.name.clone(), call.args[fixed..].to_vec());
+        }
+
+        let mut body = item.body.clone();
+
+        let names = body
+            .vars()
+            .into_iter()
+            .filter(|var| item.params.iter().all(|param| param.name != var.name))
+            .map(|var| (var.name.clone(), format!("__macro{}_{}", id, var.name)))
+            .collect();
+
+        rename(&mut body, &names);
+
+        for node in &mut body.data {
+            substitute(node, &bindings, def)?;
+        }
+
+        Ok(Block {
+            span: call.span.clone(),
+            data: body.data,
+        })
+    }
+}
+
+/// Give the variables in `block` the new names in `names`.
+fn rename(block: &mut Block, names: &HashMap<String, String>) {
+    let renamed = |name: &mut String| {
+        if let Some(new) = names.get(name) {
+            *name = new.clone();
+        }
+    };
+
+    block.walk_mut(&mut |node| match node.data.as_mut() {
+        NodeData::Symbol(sym) => renamed(&mut sym.value),
+        NodeData::Declaration(DeclarationNode::Variable(var)) => renamed(&mut var.name),
+        NodeData::Statement(StatementNode::For(node)) => renamed(&mut node.var.value),
+        NodeData::Statement(StatementNode::Call(call)) => renamed(&mut call.func),
+        _ => {}
+    });
+}
+
+/// Put the arguments in place of the parameters they were given for.
+fn substitute(
+    node: &mut Node,
+    bindings: &Bindings,
+    src: &WrappedNamedSource<String>,
+) -> Result<()> {
+    let error = |span: &StaticSpan, error| -> Result<()> {
+        Err(ProcessorError {
+            src: src.clone().into(),
+            location: span.into_source_span(),
+            error,
+        }
+        .into())
+    };
+
+    match node.data.as_mut() {
+        NodeData::Symbol(sym) => {
+            if let Some(arg) = bindings.args.get(&sym.value) {
+                *node = arg.clone();
+            } else if bindings.lists.contains_key(&sym.value) {
+                return error(
+                    &sym.span,
+                    miette!(
+                        "{} stands for any number of arguments, so it has to be used as {}...!",
+                        sym.value,
+                        sym.value
+                    ),
+                );
+            }
+
+            return Ok(());
+        }
+
+        // Functions can be given to macros by name, to be called.
+        NodeData::Statement(StatementNode::Call(call)) => {
+            if let Some(arg) = bindings.args.get(&call.func) {
+                let Ok(sym) = arg.data.as_symbol() else {
+                    return error(
+                        &call.span,
+                        miette!("{} can only be called if it is given a name!", call.func),
+                    );
+                };
+
+                call.func = sym.value;
+            }
+        }
+
+        // The block is copied for each argument, with variables of its own.
+        NodeData::Statement(StatementNode::Repeat(repeat)) => {
+            let Some(args) = bindings.lists.get(&repeat.list.value) else {
+                return error(
+                    &repeat.list.span,
+                    miette!("{} isn't a variadic macro parameter!", repeat.list.value),
+                );
+            };
+
+            let mut data = Vec::new();
+
+            for (idx, arg) in args.iter().enumerate() {
+                let mut block = repeat.block.clone();
+
+                let names = block
+                    .vars()
+                    .into_iter()
+                    .map(|var| (var.name.clone(), format!("{}_{}", var.name, idx)))
+                    .collect();
+
+                rename(&mut block, &names);
+
+                let mut bindings = bindings.clone();
+
+                bindings.args.insert(repeat.var.value.clone(), arg.clone());
+
+                for node in &mut block.data {
+                    substitute(node, &bindings, src)?;
+                }
+
+                data.push(Node {
+                    span: block.span.clone(),
+                    data: Box::new(NodeData::Block(block)),
+                });
+            }
+
+            *node = Node {
+                span: repeat.span.clone(),
+                data: Box::new(NodeData::Block(Block {
+                    span: repeat.span.clone(),
+                    data,
+                })),
+            };
+
+            return Ok(());
+        }
+
+        _ => {}
+    }
+
+    let lists = matches!(
+        node.data.as_ref(),
+        NodeData::Statement(StatementNode::Call(_))
+            | NodeData::Expr(ExpressionNode::Array(_))
+            | NodeData::Expr(ExpressionNode::MacroCall(_))
+            | NodeData::Expr(ExpressionNode::Method(_))
+    );
+
+    for child in node.data.children_mut() {
+        if let NodeData::Expr(ExpressionNode::Spread(expr)) = child.data.as_ref() {
+            if lists {
+                continue;
+            }
+
+            return error(
+                &expr.span,
+                miette!("{}... can only be used in a list of arguments!", expr.name),
+            );
+        }
+
+        substitute(child, bindings, src)?;
+    }
+
+    if !lists {
+        return Ok(());
+    }
+
+    let spread = |values: Vec<Node>| -> Result<Vec<Node>> {
+        let mut out = Vec::new();
+
+        for value in values {
+            let NodeData::Expr(ExpressionNode::Spread(expr)) = value.data.as_ref() else {
+                out.push(value);
+                continue;
+            };
+
+            let Some(args) = bindings.lists.get(&expr.name) else {
+                return Err(ProcessorError {
+                    src: src.clone().into(),
+                    location: expr.span.into_source_span(),
+                    error: miette!("{} isn't a variadic macro parameter!", expr.name),
+                }
+                .into());
+            };
+
+            out.extend(args.iter().cloned());
+        }
+
+        Ok(out)
+    };
+
+    let spread_args = |args: &mut Vec<CallArgument>| -> Result<()> {
+        let values = spread(args.iter().map(|arg| arg.value.clone()).collect())?;
+
+        *args = values
+            .into_iter()
+            .map(|value| CallArgument {
+                span: value.span.clone(),
+                value,
+            })
+            .collect();
+
+        Ok(())
+    };
+
+    match node.data.as_mut() {
+        NodeData::Statement(StatementNode::Call(call)) => spread_args(&mut call.args)?,
+        NodeData::Expr(ExpressionNode::Method(expr)) => spread_args(&mut expr.args)?,
+        NodeData::Expr(ExpressionNode::Array(expr)) => {
+            expr.values = spread(mem::take(&mut expr.values))?
+        }
+        NodeData::Expr(ExpressionNode::MacroCall(expr)) => {
+            expr.args = spread(mem::take(&mut expr.args))?
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+impl Processor {
+    /// Process the code a macro was expanded to, with errors in it pointing
+    /// into the macro as well as at the invocation.
+    pub fn process_macro_call(
+        &self,
+        ctx: &mut ProcessorContext,
+        call: &mut MacroCallExpr,
+    ) -> Result<()> {
+        let Some(expansion) = call.expansion.take() else {
+            return Err(ProcessorError {
+                src: ctx.tree.src.clone().into(),
+                location: call.span.into_source_span(),
+                error: miette!("Macro {} hasn't been expanded!", call.name),
+            }
+            .into());
+        };
+
+        let (_, def) = &self.macros[&call.name];
+        let src = mem::replace(&mut ctx.tree.src, def.clone());
+        let res = self.process_block(ctx, expansion);
+
+        ctx.tree.src = src;
+
+        let expansion = res.map_err(|err| {
+            Box::new(MacroError {
+                name: call.name.clone(),
+                src: ctx.tree.src.clone().into(),
+                location: call.span.into_source_span(),
+                errors: vec![err],
+            })
+        })?;
+
+        call.expansion = Some(expansion.as_block()?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,924 @@
+use std::collections::HashMap;
+
+use qsc_ast::{
+    ast::{
+        decl::{
+            func::FunctionNode,
+            implementation::{ImplNode, SELF_TYPE},
+            traits::TraitNode,
+            DeclarationNode,
+        },
+        expr::{
+            enumeration::{EnumExpr, Pattern},
+            ExpressionNode,
+        },
+        node::{data::NodeData, sym::SymbolNode, ty::TypeNode, vis::Visibility, Node},
+        stmt::StatementNode,
+    },
+    compat::WrappedNamedSource,
+    span::StaticSpan,
+};
+use qsc_core::{
+    conv::IntoSourceSpan,
+    error::processor::{DuplicateError, ProcessorError},
+};
+
+use crate::{generic::substitute_function, Processor, Result};
+
+/// What kind of declaration a name refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Function,
+    Extern,
+    Global,
+    Struct,
+    Enum,
+    Module,
+    Trait,
+    Macro,
+}
+
+/// A declaration somewhere in the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub kind: ItemKind,
+
+    /// The name it gets in the flattened tree.
+    pub name: String,
+
+    pub vis: Visibility,
+
+    /// The path of the module it was declared in.
+    pub module: String,
+}
+
+/// The names visible from inside of a module.
+#[derive(Debug, Clone)]
+pub struct Scope<'a> {
+    /// The module's path, like `util::io`. This is empty for the root.
+    pub path: String,
+
+    /// Names brought in with `use`, mapped to their full paths.
+    pub uses: HashMap<String, String>,
+
+    /// Every declaration in the program by its full path.
+    pub items: &'a HashMap<String, Item>,
+
+    /// The traits in the program by their full path, as they were written.
+    pub traits: &'a HashMap<String, TraitNode>,
+
+    pub src: WrappedNamedSource<String>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn qualify(&self, name: &str) -> String {
+        if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}::{}", self.path, name)
+        }
+    }
+
+    /// Get the full path of the declaration `name` refers to. Paths start
+    /// in the current module, then in its imports and then in the root.
+    pub fn resolve_path(&self, name: &str) -> Option<String> {
+        let (first, rest) = match name.split_once("::") {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (name, None),
+        };
+
+        let local = self.qualify(first);
+
+        let base = if self.items.contains_key(&local) {
+            local
+        } else if let Some(path) = self.uses.get(first) {
+            path.clone()
+        } else {
+            first.to_string()
+        };
+
+        let path = match rest {
+            Some(rest) => format!("{}::{}", base, rest),
+            None => base,
+        };
+
+        self.items.contains_key(&path).then_some(path)
+    }
+
+    /// Private items can only be used in the module they are declared in
+    /// and the modules inside of it.
+    pub fn can_see(&self, item: &Item) -> bool {
+        item.vis != Visibility::Private
+            || item.module.is_empty()
+            || self.path == item.module
+            || self.path.starts_with(&format!("{}::", item.module))
+    }
+
+    pub fn resolve(&self, name: &str, span: &StaticSpan) -> Result<Option<Item>> {
+        let Some(path) = self.resolve_path(name) else {
+            return Ok(None);
+        };
+
+        // Every module on the way has to be visible, not just the item.
+        let mut prefix = String::new();
+
+        for segment in path.split("::") {
+            if !prefix.is_empty() {
+                prefix.push_str("::");
+            }
+
+            prefix.push_str(segment);
+
+            let Some(item) = self.items.get(&prefix) else {
+                continue;
+            };
+
+            if !self.can_see(item) {
+                return Err(ProcessorError {
+                    src: self.src.clone().into(),
+                    location: span.into_source_span(),
+                    error: miette!("{} is private to module {}!", prefix, item.module),
+                }
+                .into());
+            }
+        }
+
+        Ok(Some(self.items[&path].clone()))
+    }
+
+    /// Resolve the structs and enums in a type, including the ones inside
+    /// of arrays, pointers, tuples and function types.
+    pub fn resolve_type(&self, ty: &str, span: &StaticSpan) -> Result<String> {
+        if let Some((params, ret)) = TypeNode::function_parts(ty) {
+            let params = params
+                .iter()
+                .map(|param| self.resolve_type(param, span))
+                .collect::<Result<Vec<_>>>()?;
+
+            let ret = ret.map(|ret| self.resolve_type(&ret, span)).transpose()?;
+
+            return Ok(TypeNode::function(&params, ret.as_deref()));
+        }
+
+        if let Some((elem, len)) = TypeNode::array_parts(ty) {
+            return Ok(TypeNode::array(&self.resolve_type(elem, span)?, len));
+        }
+
+        if let Some((elem, len)) = TypeNode::named_array_parts(ty) {
+            let len = match self.resolve(len, span)? {
+                Some(item) if item.kind == ItemKind::Global => item.name,
+                _ => len.to_string(),
+            };
+
+            return Ok(format!("[{}; {}]", self.resolve_type(elem, span)?, len));
+        }
+
+        if let Some(pointee) = TypeNode::pointee(ty) {
+            return Ok(TypeNode::pointer(&self.resolve_type(pointee, span)?));
+        }
+
+        if let Some(elems) = TypeNode::tuple_parts(ty) {
+            let elems = elems
+                .iter()
+                .map(|elem| self.resolve_type(elem, span))
+                .collect::<Result<Vec<_>>>()?;
+
+            return Ok(TypeNode::tuple(&elems));
+        }
+
+        Ok(match self.resolve(ty, span)? {
+            Some(item) if matches!(item.kind, ItemKind::Struct | ItemKind::Enum) => item.name,
+            _ => ty.to_string(),
+        })
+    }
+
+    pub fn resolve_type_node(&self, ty: &mut TypeNode) -> Result<()> {
+        ty.name = self.resolve_type(&ty.name, &ty.span)?;
+
+        for generic in &mut ty.generics {
+            generic.value = self.resolve_type(&generic.value, &generic.span)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Processor {
+    /// Flatten the modules in the tree into a single list of declarations.
+    /// Everything declared in a module gets its full path as its name, like
+    /// `util::helper`, and the names used in the module are resolved to
+    /// those paths.
+    pub fn resolve_modules(&mut self) -> Result<()> {
+        let mut items = HashMap::new();
+        let mut traits = HashMap::new();
+
+        self.collect_items(&self.ast.data, "", &mut items, &mut traits);
+
+        let scope = Scope {
+            path: String::new(),
+            uses: HashMap::new(),
+            items: &items,
+            traits: &traits,
+            src: self.ast.src.clone(),
+        };
+
+        let mut data = Vec::new();
+        let mut sources = Vec::new();
+
+        self.flatten_module(scope, self.ast.data.clone(), &mut data, &mut sources)?;
+
+        self.ast.data = data;
+        self.sources = sources;
+
+        Ok(())
+    }
+
+    pub fn collect_items(
+        &self,
+        data: &[Node],
+        path: &str,
+        items: &mut HashMap<String, Item>,
+        traits: &mut HashMap<String, TraitNode>,
+    ) {
+        let qualify = |name: &str| {
+            if path.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}::{}", path, name)
+            }
+        };
+
+        for node in data {
+            let Ok(decl) = node.data.as_decl() else {
+                continue;
+            };
+
+            let (name, kind, vis) = match decl {
+                DeclarationNode::Function(func) => (func.name, ItemKind::Function, func.vis),
+                DeclarationNode::Global(global) => (global.name, ItemKind::Global, global.vis),
+                DeclarationNode::Struct(item) => (item.name, ItemKind::Struct, item.vis),
+                DeclarationNode::Enum(item) => (item.name, ItemKind::Enum, item.vis),
+                DeclarationNode::Macro(item) => (item.name, ItemKind::Macro, item.vis),
+
+                // Externs are C functions, so they keep their own names.
+                DeclarationNode::Extern(func) => {
+                    let item = Item {
+                        kind: ItemKind::Extern,
+                        name: func.name.clone(),
+                        vis: func.vis,
+                        module: path.to_string(),
+                    };
+
+                    items.insert(qualify(&func.name), item);
+                    continue;
+                }
+
+                // Methods are functions named after the type they belong to.
+                DeclarationNode::Impl(block) => {
+                    let ty = self.impl_type(&block.name, path);
+
+                    for func in &block.functions {
+                        let name = format!("{}::{}", ty, func.name);
+                        let item = Item {
+                            kind: ItemKind::Function,
+                            name: name.clone(),
+                            vis: func.vis,
+                            module: path.to_string(),
+                        };
+
+                        items.insert(name, item);
+                    }
+
+                    continue;
+                }
+
+                DeclarationNode::Trait(item) => {
+                    traits.insert(qualify(&item.name), item.clone());
+
+                    (item.name, ItemKind::Trait, item.vis)
+                }
+
+                DeclarationNode::Module(module) => {
+                    self.collect_items(&module.data, &qualify(&module.name), items, traits);
+
+                    (module.name, ItemKind::Module, module.vis)
+                }
+
+                DeclarationNode::Variable(_) | DeclarationNode::Use(_) => continue,
+            };
+
+            let item = Item {
+                kind,
+                name: qualify(&name),
+                vis,
+                module: path.to_string(),
+            };
+
+            items.insert(qualify(&name), item);
+        }
+    }
+
+    pub fn flatten_module(
+        &self,
+        mut scope: Scope,
+        data: Vec<Node>,
+        out: &mut Vec<Node>,
+        sources: &mut Vec<WrappedNamedSource<String>>,
+    ) -> Result<()> {
+        let mut imported = HashMap::new();
+
+        for node in &data {
+            let Ok(DeclarationNode::Use(import)) = node.data.as_decl() else {
+                continue;
+            };
+
+            // Imports can't take a name the module declares or imports itself.
+            let name = import.name().to_string();
+            let previous = imported
+                .get(&name)
+                .cloned()
+                .or_else(|| declaration_span(&data, &name));
+
+            if let Some(previous) = previous {
+                let (first, second) = if previous.start < import.span.start {
+                    (previous, import.span.clone())
+                } else {
+                    (import.span.clone(), previous)
+                };
+
+                return Err(DuplicateError {
+                    src: scope.src.clone().into(),
+                    first: first.into_source_span(),
+                    second: second.into_source_span(),
+                    error: miette!("{} is declared more than once in this module!", name),
+                }
+                .into());
+            }
+
+            imported.insert(name, import.span.clone());
+
+            if scope.resolve(&import.path, &import.span)?.is_none() {
+                return Err(ProcessorError {
+                    src: scope.src.clone().into(),
+                    location: import.span.into_source_span(),
+                    error: miette!("Cannot find {} to import!", import.path),
+                }
+                .into());
+            }
+
+            let path = scope.resolve_path(&import.path).unwrap();
+
+            scope.uses.insert(import.name().to_string(), path);
+        }
+
+        let (data, methods) = self.expand_impls(&scope, data)?;
+
+        for mut func in methods {
+            self.resolve_function(&scope, &mut func)?;
+
+            out.push(Node {
+                span: func.span.clone(),
+                data: Box::new(NodeData::Declaration(DeclarationNode::Function(func))),
+            });
+
+            sources.push(scope.src.clone());
+        }
+
+        for mut node in data {
+            match node.data.as_mut() {
+                NodeData::Declaration(DeclarationNode::Module(module)) => {
+                    let inner = Scope {
+                        path: scope.qualify(&module.name),
+                        uses: HashMap::new(),
+                        items: scope.items,
+                        traits: scope.traits,
+                        src: module.src.clone(),
+                    };
+
+                    self.flatten_module(inner, module.data.clone(), out, sources)?;
+
+                    continue;
+                }
+
+                NodeData::Declaration(DeclarationNode::Use(_)) => continue,
+
+                NodeData::Declaration(DeclarationNode::Function(func)) => {
+                    func.name = scope.qualify(&func.name);
+
+                    self.resolve_function(&scope, func)?;
+                }
+
+                // Default bodies are resolved where they get copied to.
+                NodeData::Declaration(DeclarationNode::Trait(item)) => {
+                    item.name = scope.qualify(&item.name);
+
+                    for func in &mut item.functions {
+                        for arg in &mut func.args {
+                            scope.resolve_type_node(&mut arg.type_)?;
+                        }
+
+                        if let Some(ret) = &mut func.ret {
+                            scope.resolve_type_node(ret)?;
+                        }
+                    }
+                }
+
+                NodeData::Declaration(DeclarationNode::Extern(func)) => {
+                    for arg in &mut func.args {
+                        scope.resolve_type_node(&mut arg.type_)?;
+                    }
+
+                    if let Some(ret) = &mut func.ret {
+                        scope.resolve_type_node(ret)?;
+                    }
+                }
+
+                NodeData::Declaration(DeclarationNode::Global(global)) => {
+                    global.name = scope.qualify(&global.name);
+                    scope.resolve_type_node(&mut global.type_)?;
+
+                    let mut res = Ok(());
+
+                    global.value.walk_mut(&mut |node| {
+                        if res.is_ok() {
+                            res = self.resolve_node(&scope, &[], node);
+                        }
+                    });
+
+                    res?;
+                }
+
+                // Names in the body are resolved where the macro is declared,
+                // so it can use what is visible there wherever it is expanded.
+                NodeData::Declaration(DeclarationNode::Macro(item)) => {
+                    item.name = scope.qualify(&item.name);
+
+                    let mut locals = item
+                        .params
+                        .iter()
+                        .map(|param| param.name.clone())
+                        .chain(item.body.vars().into_iter().map(|var| var.name))
+                        .collect::<Vec<_>>();
+
+                    item.body.walk(&mut |node| {
+                        if let Ok(StatementNode::Repeat(node)) = node.data.as_stmt() {
+                            locals.push(node.var.value);
+                        }
+                    });
+
+                    let mut res = Ok(());
+
+                    item.body.walk_mut(&mut |node| {
+                        if res.is_ok() {
+                            res = self.resolve_node(&scope, &locals, node);
+                        }
+                    });
+
+                    res?;
+                }
+
+                NodeData::Expr(ExpressionNode::Comptime(_)) => {
+                    let mut res = Ok(());
+
+                    node.walk_mut(&mut |node| {
+                        if res.is_ok() {
+                            res = self.resolve_node(&scope, &[], node);
+                        }
+                    });
+
+                    res?;
+                }
+
+                NodeData::Declaration(DeclarationNode::Struct(item)) => {
+                    item.name = scope.qualify(&item.name);
+
+                    for field in &mut item.fields {
+                        scope.resolve_type_node(&mut field.type_)?;
+                    }
+                }
+
+                NodeData::Declaration(DeclarationNode::Enum(item)) => {
+                    item.name = scope.qualify(&item.name);
+
+                    for variant in &mut item.variants {
+                        for field in &mut variant.fields {
+                            scope.resolve_type_node(field)?;
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+
+            out.push(node);
+            sources.push(scope.src.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the types and names used in a function. Its own name is
+    /// left as it is.
+    pub fn resolve_function(&self, scope: &Scope, func: &mut FunctionNode) -> Result<()> {
+        let locals = func.variables().into_keys().collect::<Vec<_>>();
+
+        for arg in &mut func.args {
+            scope.resolve_type_node(&mut arg.type_)?;
+        }
+
+        if let Some(ret) = &mut func.ret {
+            scope.resolve_type_node(ret)?;
+        }
+
+        for bound in &mut func.bounds {
+            bound.trait_ = self.resolve_trait(scope, &bound.trait_, &bound.span)?;
+
+            if !func.generics.contains(&bound.param) {
+                return Err(ProcessorError {
+                    src: scope.src.clone().into(),
+                    location: bound.span.into_source_span(),
+                    error: miette!("Cannot find type parameter {}!", bound.param),
+                }
+                .into());
+            }
+        }
+
+        let mut res = Ok(());
+
+        func.content.walk_mut(&mut |node| {
+            if res.is_ok() {
+                res = self.resolve_node(scope, &locals, node);
+            }
+        });
+
+        res
+    }
+
+    /// Get the full path of the trait `name` refers to.
+    pub fn resolve_trait(&self, scope: &Scope, name: &str, span: &StaticSpan) -> Result<String> {
+        match scope.resolve(name, span)? {
+            Some(item) if item.kind == ItemKind::Trait => Ok(item.name),
+
+            _ => Err(ProcessorError {
+                src: scope.src.clone().into(),
+                location: span.into_source_span(),
+                error: miette!("Cannot find trait {}!", name),
+            }
+            .into()),
+        }
+    }
+
+    /// The path of the type an `impl` block in module `path` is for, which
+    /// its methods are named after. Primitives are the same everywhere.
+    pub fn impl_type(&self, name: &str, path: &str) -> String {
+        if path.is_empty() || self.ast.primitives().contains(&name) {
+            name.to_string()
+        } else {
+            format!("{}::{}", path, name)
+        }
+    }
+
+    /// Take the `impl` blocks out of a module, and turn the functions inside
+    /// of them into plain ones named like `Type::method`, with `Self` filled
+    /// in. Trait impls are left behind without their functions.
+    pub fn expand_impls(
+        &self,
+        scope: &Scope,
+        data: Vec<Node>,
+    ) -> Result<(Vec<Node>, Vec<FunctionNode>)> {
+        let mut out = Vec::new();
+        let mut methods = Vec::new();
+        let mut declared: HashMap<String, StaticSpan> = HashMap::new();
+
+        for node in data {
+            let Ok(DeclarationNode::Impl(mut block)) = node.data.as_decl() else {
+                out.push(node);
+                continue;
+            };
+
+            self.check_impl(scope, &block)?;
+
+            let ty = self.impl_type(&block.name, &scope.path);
+            let written = block.functions.len();
+
+            if let Some(name) = &block.trait_ {
+                let path = self.resolve_trait(scope, name, &block.span)?;
+
+                self.check_trait_impl(scope, &mut block, &scope.traits[&path])?;
+
+                out.push(Node {
+                    span: node.span.clone(),
+                    data: Box::new(NodeData::Declaration(DeclarationNode::Impl(ImplNode {
+                        span: block.span.clone(),
+                        name: ty.clone(),
+                        trait_: Some(path),
+                        functions: Vec::new(),
+                    }))),
+                });
+            }
+
+            let self_type = SELF_TYPE.to_string();
+            let types = HashMap::from([(&self_type, &block.name)]);
+
+            for (idx, mut func) in block.functions.clone().into_iter().enumerate() {
+                func.name = format!("{}::{}", ty, func.name);
+
+                // Methods are named after their type only, so two impls
+                // can't both have one with the same name. Default bodies
+                // come from the trait, so the block itself is pointed at.
+                let span = if idx < written {
+                    func.span.clone()
+                } else {
+                    block.span.clone()
+                };
+
+                if let Some(first) = declared.insert(func.name.clone(), span.clone()) {
+                    return Err(DuplicateError {
+                        src: scope.src.clone().into(),
+                        first: first.into_source_span(),
+                        second: span.into_source_span(),
+                        error: miette!("Method {} is declared more than once!", func.name),
+                    }
+                    .into());
+                }
+
+                substitute_function(&mut func, &types);
+
+                // `Self` also names the type in literals and paths.
+                func.content.walk_mut(&mut |node| match node.data.as_mut() {
+                    NodeData::Expr(ExpressionNode::Struct(expr)) if expr.name == SELF_TYPE => {
+                        expr.name = block.name.clone()
+                    }
+
+                    NodeData::Expr(ExpressionNode::Enum(expr)) if expr.name == SELF_TYPE => {
+                        expr.name = block.name.clone()
+                    }
+
+                    NodeData::Statement(StatementNode::Call(call)) => {
+                        if let Some(rest) = call.func.strip_prefix("Self::") {
+                            call.func = block.method_name(rest);
+                        }
+                    }
+
+                    _ => {}
+                });
+
+                methods.push(func);
+            }
+        }
+
+        Ok((out, methods))
+    }
+
+    /// Methods can only be added to structs and enums declared in the same
+    /// module as the `impl` block. Traits can be implemented for primitives
+    /// too.
+    pub fn check_impl(&self, scope: &Scope, block: &ImplNode) -> Result<()> {
+        if block.trait_.is_some() && self.ast.primitives().contains(&block.name.as_str()) {
+            return Ok(());
+        }
+
+        let item = scope.resolve(&block.name, &block.span)?;
+
+        match item {
+            Some(item)
+                if matches!(item.kind, ItemKind::Struct | ItemKind::Enum)
+                    && item.module == scope.path =>
+            {
+                Ok(())
+            }
+
+            Some(item) if matches!(item.kind, ItemKind::Struct | ItemKind::Enum) => {
+                Err(ProcessorError {
+                    src: scope.src.clone().into(),
+                    location: block.span.into_source_span(),
+                    error: miette!(
+                        "Methods for {} have to be implemented in module {}!",
+                        item.name,
+                        item.module
+                    ),
+                }
+                .into())
+            }
+
+            _ => Err(ProcessorError {
+                src: scope.src.clone().into(),
+                location: block.span.into_source_span(),
+                error: miette!("Cannot find struct or enum {} to implement!", block.name),
+            }
+            .into()),
+        }
+    }
+
+    /// Check that an `impl Trait for Type` block has every function of the
+    /// trait and nothing else, and fill in the ones it leaves out with their
+    /// default bodies. The functions are as visible as the trait is.
+    pub fn check_trait_impl(
+        &self,
+        scope: &Scope,
+        block: &mut ImplNode,
+        item: &TraitNode,
+    ) -> Result<()> {
+        for func in &mut block.functions {
+            if item.function(&func.name).is_none() {
+                return Err(ProcessorError {
+                    src: scope.src.clone().into(),
+                    location: func.span.into_source_span(),
+                    error: miette!("{} is not a function of trait {}!", func.name, item.name),
+                }
+                .into());
+            }
+
+            func.vis = Visibility::Public;
+        }
+
+        let mut missing = Vec::new();
+
+        for func in &item.functions {
+            if block.functions.iter().any(|v| v.name == func.name) {
+                continue;
+            }
+
+            match func.to_function(Visibility::Public) {
+                Some(func) => block.functions.push(func),
+                None => missing.push(func.name.clone()),
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(ProcessorError {
+                src: scope.src.clone().into(),
+                location: block.span.into_source_span(),
+                error: miette!(
+                    "The impl of {} for {} is missing {}!",
+                    item.name,
+                    block.name,
+                    missing.join(", ")
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the names used directly by `node`. Variables in `locals`
+    /// shadow declarations with the same name.
+    pub fn resolve_node(&self, scope: &Scope, locals: &[String], node: &mut Node) -> Result<()> {
+        let mut replacement = None;
+
+        match node.data.as_mut() {
+            NodeData::Symbol(sym) => {
+                if !locals.contains(&sym.value) {
+                    if let Some(item) = scope.resolve(&sym.value, &sym.span)? {
+                        if matches!(
+                            item.kind,
+                            ItemKind::Global | ItemKind::Function | ItemKind::Extern
+                        ) {
+                            sym.value = item.name;
+                        }
+                    }
+                }
+            }
+
+            // Calls through a variable holding a function.
+            NodeData::Statement(StatementNode::Call(call)) if locals.contains(&call.func) => {}
+
+            NodeData::Statement(StatementNode::Call(call)) => {
+                match scope.resolve(&call.func, &call.span)? {
+                    Some(item) if matches!(item.kind, ItemKind::Function | ItemKind::Extern) => {
+                        call.func = item.name
+                    }
+
+                    // Calls like `Shape::Circle(1.0)` are really enum literals.
+                    _ => {
+                        let Some((path, variant)) = call.func.rsplit_once("::") else {
+                            return Ok(());
+                        };
+
+                        if let Some(item) = scope.resolve(path, &call.span)? {
+                            if item.kind == ItemKind::Enum {
+                                replacement =
+                                    Some(NodeData::Expr(ExpressionNode::Enum(EnumExpr {
+                                        span: call.span.clone(),
+                                        name: item.name,
+                                        variant: variant.to_string(),
+                                        values: call
+                                            .args
+                                            .iter()
+                                            .map(|arg| arg.value.clone())
+                                            .collect(),
+                                    })));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // And things like `util::LIMIT` look like enum literals.
+            NodeData::Expr(ExpressionNode::Enum(expr)) => {
+                match scope.resolve(&expr.name, &expr.span)? {
+                    Some(item) if item.kind == ItemKind::Enum => expr.name = item.name,
+
+                    _ => {
+                        let path = format!("{}::{}", expr.name, expr.variant);
+
+                        if let Some(item) = scope.resolve(&path, &expr.span)? {
+                            if item.kind == ItemKind::Global {
+                                replacement = Some(NodeData::Symbol(SymbolNode {
+                                    span: expr.span.clone(),
+                                    value: item.name,
+                                }));
+                            }
+                        }
+                    }
+                }
+            }
+
+            NodeData::Expr(ExpressionNode::MacroCall(expr)) => {
+                match scope.resolve(&expr.name, &expr.span)? {
+                    Some(item) if item.kind == ItemKind::Macro => expr.name = item.name,
+
+                    _ => {
+                        return Err(ProcessorError {
+                            src: scope.src.clone().into(),
+                            location: expr.span.into_source_span(),
+                            error: miette!("Cannot find macro {}!", expr.name),
+                        }
+                        .into())
+                    }
+                }
+            }
+
+            NodeData::Expr(ExpressionNode::Struct(expr)) => {
+                if let Some(item) = scope.resolve(&expr.name, &expr.span)? {
+                    if item.kind == ItemKind::Struct {
+                        expr.name = item.name;
+                    }
+                }
+            }
+
+            NodeData::Expr(ExpressionNode::Match(expr)) => {
+                for arm in &mut expr.arms {
+                    if let Pattern::Variant(pattern) = &mut arm.pattern {
+                        if let Some(item) = scope.resolve(&pattern.name, &pattern.span)? {
+                            if item.kind == ItemKind::Enum {
+                                pattern.name = item.name;
+                            }
+                        }
+                    }
+                }
+            }
+
+            NodeData::Expr(ExpressionNode::Cast(expr)) => {
+                scope.resolve_type_node(&mut expr.type_)?
+            }
+
+            NodeData::Expr(ExpressionNode::Closure(expr)) => {
+                for arg in &mut expr.args {
+                    if let Some(ty) = &mut arg.type_ {
+                        scope.resolve_type_node(ty)?;
+                    }
+                }
+
+                if let Some(ret) = &mut expr.ret {
+                    scope.resolve_type_node(ret)?;
+                }
+            }
+
+            NodeData::Declaration(DeclarationNode::Variable(var)) => {
+                if let Some(ty) = &mut var.type_ {
+                    scope.resolve_type_node(ty)?;
+                }
+            }
+
+            _ => {}
+        }
+
+        if let Some(data) = replacement {
+            *node.data = data;
+        }
+
+        Ok(())
+    }
+}
+
+/// Find where something named `name` is declared in `data`, leaving out
+/// imports and `impl` blocks, whose functions are named after their type.
+fn declaration_span(data: &[Node], name: &str) -> Option<StaticSpan> {
+    data.iter().find_map(|node| {
+        let found = match node.data.as_decl().ok()? {
+            DeclarationNode::Function(func) => func.name == name,
+            DeclarationNode::Extern(func) => func.name == name,
+            DeclarationNode::Global(global) => global.name == name,
+            DeclarationNode::Struct(item) => item.name == name,
+            DeclarationNode::Enum(item) => item.name == name,
+            DeclarationNode::Trait(item) => item.name == name,
+            DeclarationNode::Macro(item) => item.name == name,
+            DeclarationNode::Module(module) => module.name == name,
+            _ => false,
+        };
+
+        found.then(|| node.span.clone())
+    })
+}
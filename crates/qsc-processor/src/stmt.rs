@@ -1,4 +1,8 @@
-use qsc_ast::ast::{node::data::NodeData, stmt::StatementNode};
+use qsc_ast::ast::{
+    decl::func::FunctionArgument,
+    node::{data::NodeData, sym::SymbolNode, ty::TypeNode},
+    stmt::StatementNode,
+};
 use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
 
 use crate::{ctx::ProcessorContext, Processor, Result};
@@ -11,32 +15,225 @@ impl Processor {
     ) -> Result<NodeData> {
         match &mut stmt {
             StatementNode::Call(call) => {
-                if !self.ast.functions().contains_key(&call.func)
-                    && !self.ast.imported_functions().contains(&call.func.as_str())
-                    && !self.ast.externs().contains_key(&call.func)
-                {
+                let func = ctx.func.clone().map(|v| v.name);
+                let types = |args: &[FunctionArgument]| {
+                    args.iter()
+                        .map(|arg| arg.type_.as_str())
+                        .collect::<Vec<_>>()
+                };
+
+                // Variables and globals can hold functions to call too.
+                let value = NodeData::Symbol(SymbolNode {
+                    span: call.span.clone(),
+                    value: call.func.clone(),
+                })
+                .get_type(&func, ctx.tree)
+                .ok()
+                .and_then(|ty| TypeNode::function_parts(&ty));
+
+                let (params, variadic, foreign) = if let Some((params, _)) = value.filter(|_| {
+                    ctx.func
+                        .as_ref()
+                        .is_some_and(|func| func.variables().contains_key(&call.func))
+                        || ctx.tree.globals().contains_key(&call.func)
+                }) {
+                    (params, false, false)
+                } else if let Some(func) = self.ast.functions().get(&call.func) {
+                    (types(&func.args), false, false)
+                } else if let Some(func) = self.ast.externs().get(&call.func) {
+                    (types(&func.args), func.variadic, true)
+                } else {
                     return Err(ProcessorError {
                         src: ctx.tree.src.clone().into(),
                         location: call.span.into_source_span(),
                         error: miette!("Cannot find function \"{}\"!", call.func),
                     }
                     .into());
+                };
+
+                if call.args.len() < params.len() || (!variadic && call.args.len() > params.len()) {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: call.span.into_source_span(),
+                        error: miette!(
+                            "Function \"{}\" takes {}{} arguments, but {} were given!",
+                            call.func,
+                            if variadic { "at least " } else { "" },
+                            params.len(),
+                            call.args.len()
+                        ),
+                    }
+                    .into());
                 }
 
+                self.check_deprecated(ctx, call);
+
                 for arg in &mut call.args {
                     arg.value = self.process_node(ctx, &mut arg.value)?;
                 }
+
+                // Typed pointers and functions have to be the right type.
+                for (expected, arg) in params.iter().zip(&mut call.args) {
+                    self.coerce_literal(ctx, &mut arg.value, expected)?;
+
+                    // Integers of another size get converted, but nothing
+                    // else can be passed for one.
+                    let int = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"]
+                        .contains(&expected.as_str());
+
+                    if int && !arg.value.data.is_int(&func, ctx.tree)? {
+                        let ty = arg.value.data.get_type(&func, ctx.tree)?;
+
+                        return Err(ProcessorError {
+                            src: ctx.tree.src.clone().into(),
+                            location: arg.span.into_source_span(),
+                            error: miette!("Expected a value of type {}, found {}!", expected, ty),
+                        }
+                        .into());
+                    }
+
+                    if TypeNode::pointee(expected).is_none()
+                        && TypeNode::function_parts(expected).is_none()
+                    {
+                        continue;
+                    }
+
+                    let ty = arg.value.data.get_type(&func, ctx.tree)?;
+
+                    if ty != *expected {
+                        return Err(ProcessorError {
+                            src: ctx.tree.src.clone().into(),
+                            location: arg.span.into_source_span(),
+                            error: miette!("Expected a value of type {}, found {}!", expected, ty),
+                        }
+                        .into());
+                    }
+
+                    // C functions only get the address of the code, so the
+                    // environment of a closure would be lost on the way.
+                    if foreign
+                        && TypeNode::function_parts(expected).is_some()
+                        && !self.is_plain_function(ctx, &arg.value)
+                    {
+                        return Err(ProcessorError {
+                            src: ctx.tree.src.clone().into(),
+                            location: arg.span.into_source_span(),
+                            error: miette!(
+                                "Only functions and closures that don't capture anything can be passed to C functions!"
+                            ),
+                        }
+                        .into());
+                    }
+                }
             }
 
             StatementNode::Return(ret) => {
                 if let Some(val) = &mut ret.value {
                     *val = self.process_node(ctx, val)?;
+
+                    if let Some(ty) = ctx.func.clone().and_then(|v| v.ret) {
+                        self.coerce_literal(ctx, val, &ty.as_str())?;
+                    }
                 }
             }
 
             StatementNode::Condition(cond) => {
                 cond.condition = self.process_node(ctx, &mut cond.condition)?;
                 cond.block = self.process_block(ctx, cond.block.clone())?.as_block()?;
+
+                if let Some(else_block) = &mut cond.else_block {
+                    *else_block = self.process_block(ctx, else_block.clone())?.as_block()?;
+                }
+            }
+
+            StatementNode::While(node) => {
+                node.condition = self.process_node(ctx, &mut node.condition)?;
+
+                ctx.loop_depth += 1;
+                node.block = self.process_block(ctx, node.block.clone())?.as_block()?;
+                ctx.loop_depth -= 1;
+            }
+
+            StatementNode::For(node) => {
+                node.range.start = self.process_node(ctx, &mut node.range.start)?;
+                node.range.end = self.process_node(ctx, &mut node.range.end)?;
+
+                self.coerce_operands(ctx, &mut node.range.start, &mut node.range.end)?;
+
+                let func = ctx.func.clone().map(|v| v.name);
+                let start = node.range.start.data.get_type(&func, ctx.tree)?;
+                let end = node.range.end.data.get_type(&func, ctx.tree)?;
+
+                if !node.range.start.data.is_int(&func, ctx.tree)?
+                    || !node.range.end.data.is_int(&func, ctx.tree)?
+                {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: node.range.span.into_source_span(),
+                        error: miette!(
+                            "Range bounds must be integers, found {} and {}!",
+                            start,
+                            end
+                        ),
+                    }
+                    .into());
+                }
+
+                if start != end {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: node.range.span.into_source_span(),
+                        error: miette!("Range bounds' types do not match ({} and {})!", start, end),
+                    }
+                    .into());
+                }
+
+                node.type_ = Some(TypeNode {
+                    generics: Vec::new(),
+                    name: start,
+                    span: node.range.span.clone(),
+                });
+
+                // The body has to see the type of the loop variable.
+                let processed = node.clone();
+
+                ctx.update_func(|node| {
+                    if let NodeData::Statement(StatementNode::For(item)) = node.data.as_mut() {
+                        if item.span == processed.span {
+                            item.type_ = processed.type_.clone();
+                            item.range = processed.range.clone();
+                        }
+                    }
+                });
+
+                ctx.loop_depth += 1;
+                node.block = self.process_block(ctx, node.block.clone())?.as_block()?;
+                ctx.loop_depth -= 1;
+            }
+
+            // Repetitions are unrolled when macros get expanded.
+            StatementNode::Repeat(_) => {}
+
+            StatementNode::Break(node) => {
+                if ctx.loop_depth == 0 {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: node.span.into_source_span(),
+                        error: miette!("`break` can only be used inside of a loop!"),
+                    }
+                    .into());
+                }
+            }
+
+            StatementNode::Continue(node) => {
+                if ctx.loop_depth == 0 {
+                    return Err(ProcessorError {
+                        src: ctx.tree.src.clone().into(),
+                        location: node.span.into_source_span(),
+                        error: miette!("`continue` can only be used inside of a loop!"),
+                    }
+                    .into());
+                }
             }
         };
 
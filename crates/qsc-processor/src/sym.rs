@@ -8,6 +8,7 @@ impl Processor {
         if let Some(func) = &ctx.func {
             if !func.variables().contains_key(&sym.value)
                 && !ctx.tree.globals().contains_key(&sym.value)
+                && !self.is_function_value(ctx, &sym.value)
             {
                 return Err(ProcessorError {
                     src: ctx.tree.src.clone().into(),
@@ -20,7 +21,8 @@ impl Processor {
             return Ok(NodeData::Symbol(sym));
         }
 
-        if !ctx.tree.globals().contains_key(&sym.value) {
+        if !ctx.tree.globals().contains_key(&sym.value) && !self.is_function_value(ctx, &sym.value)
+        {
             return Err(ProcessorError {
                 src: ctx.tree.src.clone().into(),
                 location: sym.span.into_source_span(),
@@ -31,4 +33,16 @@ impl Processor {
 
         Ok(NodeData::Symbol(sym))
     }
+
+    /// Whether `name` refers to a function or an extern rather than a
+    /// variable, when used as a value.
+    pub fn is_function_value(&self, ctx: &ProcessorContext, name: &str) -> bool {
+        let shadowed = ctx
+            .func
+            .as_ref()
+            .is_some_and(|func| func.variables().contains_key(name))
+            || ctx.tree.globals().contains_key(name);
+
+        !shadowed && ctx.tree.function_type(name).is_some()
+    }
 }
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use qsc_ast::{
+    ast::{
+        decl::{
+            func::{FunctionArgument, FunctionNode},
+            implementation::{ImplNode, SELF_TYPE},
+        },
+        expr::ExpressionNode,
+        node::{data::NodeData, ty::TypeNode},
+        stmt::{call::CallNode, StatementNode},
+    },
+    compat::WrappedNamedSource,
+    span::StaticSpan,
+};
+use qsc_core::{conv::IntoSourceSpan, error::processor::ProcessorError};
+
+use crate::{ctx::ProcessorContext, generic::substitute, Processor, Result};
+
+impl Processor {
+    /// Check that the functions of a trait impl have the same signatures as
+    /// the ones in the trait, with `Self` being the type they are for.
+    pub fn check_trait_signatures(&self, ctx: &ProcessorContext, item: &ImplNode) -> Result<()> {
+        let Some(name) = &item.trait_ else {
+            return Ok(());
+        };
+
+        let self_type = SELF_TYPE.to_string();
+        let types = HashMap::from([(&self_type, &item.name)]);
+
+        let signature = |args: &[FunctionArgument], ret: &Option<TypeNode>| {
+            let params = args
+                .iter()
+                .map(|arg| substitute(&arg.type_.as_str(), &types))
+                .collect::<Vec<_>>();
+
+            let ret = ret
+                .as_ref()
+                .map(|ret| substitute(&ret.as_str(), &types))
+                .filter(|ret| ret != "void");
+
+            TypeNode::function(&params, ret.as_deref())
+        };
+
+        let functions = ctx.tree.functions();
+
+        for expected in &ctx.tree.traits()[name].functions {
+            let func = &functions[&format!("{}::{}", item.name, expected.name)];
+            let wanted = signature(&expected.args, &expected.ret);
+            let found = signature(&func.args, &func.ret);
+
+            if wanted != found {
+                return Err(ProcessorError {
+                    src: ctx.tree.src.clone().into(),
+                    location: func.span.into_source_span(),
+                    error: miette!(
+                        "{} should have type {} to implement {}, but has type {}!",
+                        func.name,
+                        wanted,
+                        name,
+                        found
+                    ),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the types a generic function is called with implement the
+    /// traits its type parameters are bounded by.
+    pub fn check_bounds(
+        &self,
+        template: &FunctionNode,
+        types: &[String],
+        call: &CallNode,
+        src: &WrappedNamedSource<String>,
+    ) -> Result<()> {
+        for bound in &template.bounds {
+            let idx = template
+                .generics
+                .iter()
+                .position(|name| *name == bound.param)
+                .unwrap();
+
+            if !self.ast.implements(&types[idx], &bound.trait_) {
+                return Err(ProcessorError {
+                    src: src.clone().into(),
+                    location: call.span.into_source_span(),
+                    error: miette!(
+                        "Type {} doesn't implement {}, which {} needs for {}!",
+                        types[idx],
+                        bound.trait_,
+                        template.name,
+                        bound.param
+                    ),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that a generic function only uses the methods of its type
+    /// parameters that the traits they are bounded by have.
+    pub fn check_bound_usage(
+        &self,
+        template: &FunctionNode,
+        src: &WrappedNamedSource<String>,
+    ) -> Result<()> {
+        let vars = template.variables();
+        let traits = self.ast.traits();
+
+        let param_of = |ty: &str| {
+            let ty = TypeNode::pointee(ty).unwrap_or(ty);
+
+            template.generics.iter().find(|name| *name == ty).cloned()
+        };
+
+        let mut res = Ok(());
+
+        let mut check = |param: String, method: &str, span: &StaticSpan| {
+            let found = template
+                .bounds
+                .iter()
+                .filter(|bound| bound.param == param)
+                .any(|bound| traits[&bound.trait_].function(method).is_some());
+
+            if !found && res.is_ok() {
+                res = Err(ProcessorError {
+                    src: src.clone().into(),
+                    location: span.into_source_span(),
+                    error: miette!("{} has no bound with a function named {}!", param, method),
+                }
+                .into());
+            }
+        };
+
+        template.content.walk(&mut |node| match &*node.data {
+            NodeData::Expr(ExpressionNode::Method(expr)) => {
+                let param = expr
+                    .value
+                    .data
+                    .as_symbol()
+                    .ok()
+                    .and_then(|sym| vars.get(&sym.value).cloned())
+                    .and_then(|var| var.type_)
+                    .and_then(|ty| param_of(&ty.as_str()));
+
+                if let Some(param) = param {
+                    check(param, &expr.method.value, &expr.method.span);
+                }
+            }
+
+            NodeData::Statement(StatementNode::Call(call)) => {
+                if let Some((ty, method)) = call.func.rsplit_once("::") {
+                    if let Some(param) = param_of(ty) {
+                        check(param, method, &call.span);
+                    }
+                }
+            }
+
+            _ => {}
+        });
+
+        res
+    }
+}
@@ -5,7 +5,7 @@ use crate::{ctx::ProcessorContext, Processor, Result};
 
 impl Processor {
     pub fn process_type(&self, ctx: &ProcessorContext, ty: TypeNode) -> Result<NodeData> {
-        if !ctx.tree.types().contains(&ty.as_str().as_str()) {
+        if !self.type_exists(ctx, &ty.as_str()) {
             return Err(ProcessorError {
                 src: ctx.tree.src.clone().into(),
                 location: ty.span.into_source_span(),
@@ -16,4 +16,27 @@ impl Processor {
 
         Ok(NodeData::Type(ty))
     }
+
+    pub fn type_exists(&self, ctx: &ProcessorContext, ty: &str) -> bool {
+        if let Some((elem, _)) = TypeNode::array_parts(ty) {
+            return self.type_exists(ctx, elem);
+        }
+
+        if let Some(pointee) = TypeNode::pointee(ty) {
+            return self.type_exists(ctx, pointee);
+        }
+
+        if let Some(elems) = TypeNode::tuple_parts(ty) {
+            return elems.iter().all(|ty| self.type_exists(ctx, ty));
+        }
+
+        if let Some((params, ret)) = TypeNode::function_parts(ty) {
+            return params
+                .iter()
+                .chain(ret.as_ref())
+                .all(|ty| self.type_exists(ctx, ty));
+        }
+
+        ty == "ptr" || ctx.tree.types().iter().any(|v| v == ty)
+    }
 }
@@ -0,0 +1,54 @@
+use qsc_ast::ast::AbstractTree;
+use qsc_lexer::lexer::Lexer;
+use qsc_processor::Processor;
+
+fn process(source: &str) -> AbstractTree {
+    let ast = Lexer::new("test.qs", source).lex().unwrap();
+
+    Processor::new(ast).process().unwrap()
+}
+
+fn var_type(tree: &AbstractTree, func: &str, var: &str) -> String {
+    tree.functions()[func].variables()[var]
+        .type_
+        .as_ref()
+        .unwrap()
+        .as_str()
+}
+
+#[test]
+fn later_statements_see_coerced_literals() {
+    let tree = process(
+        "fn main() -> i32 {
+            let n: i64 = 5;
+            let z = n - 1;
+            let m: u8 = 7;
+            let w = m + 1;
+
+            return z as i32 + w as i32;
+        }",
+    );
+
+    assert_eq!(var_type(&tree, "main", "z"), "i64");
+    assert_eq!(var_type(&tree, "main", "w"), "u8");
+}
+
+#[test]
+fn nested_blocks_see_coerced_literals() {
+    let tree = process(
+        "fn main() -> i32 {
+            let n: i64 = 5;
+
+            if n > 1 {
+                let z = n * 2;
+                let y = z + 1;
+
+                return y as i32;
+            }
+
+            return 0;
+        }",
+    );
+
+    assert_eq!(var_type(&tree, "main", "y"), "i64");
+}
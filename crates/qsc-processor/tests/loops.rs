@@ -0,0 +1,66 @@
+use qsc_ast::ast::{
+    node::{data::NodeData, Node},
+    stmt::StatementNode,
+    AbstractTree,
+};
+use qsc_lexer::lexer::Lexer;
+use qsc_processor::Processor;
+
+fn process(source: &str) -> AbstractTree {
+    let ast = Lexer::new("test.qs", source).lex().unwrap();
+
+    Processor::new(ast).process().unwrap()
+}
+
+fn loop_types(tree: &AbstractTree, func: &str) -> Vec<String> {
+    let mut types = Vec::new();
+
+    tree.functions()[func].content.walk(&mut |node: &Node| {
+        if let NodeData::Statement(StatementNode::For(item)) = &*node.data {
+            types.push(item.type_.as_ref().unwrap().as_str());
+        }
+    });
+
+    types
+}
+
+#[test]
+fn literal_start_takes_the_type_of_the_end() {
+    let tree = process(
+        "fn main() -> i32 {
+            let n: i64 = 5;
+            let mut s: i64 = 0;
+
+            for i in 0..n {
+                s += i;
+            }
+
+            return s as i32;
+        }",
+    );
+
+    assert_eq!(loop_types(&tree, "main"), ["i64"]);
+}
+
+#[test]
+fn loop_variables_can_be_reused() {
+    let tree = process(
+        "fn main() -> i32 {
+            let n: u8 = 5;
+            let mut s: u8 = 0;
+
+            for i in 0..=n {
+                let k = i + 1;
+                s += k;
+            }
+
+            for i in n - 1..n {
+                s += i * 10;
+            }
+
+            return s as i32;
+        }",
+    );
+
+    assert_eq!(loop_types(&tree, "main"), ["u8", "u8"]);
+}